@@ -5,7 +5,11 @@ use arrow2::{
 };
 use std::sync::Arc;
 
-use arrow2::{array::Array, datatypes::PrimitiveType, scalar::Scalar};
+use arrow2::{
+    array::Array,
+    datatypes::{DataType, PrimitiveType},
+    scalar::Scalar,
+};
 
 use crate::error::Error;
 
@@ -21,6 +25,44 @@ impl ColumnarValue {
             ColumnarValue::Scalar(scalar) => scalar_to_array(scalar, len).unwrap(),
         }
     }
+
+    /// Data type of the underlying array or scalar.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ColumnarValue::Array(arr) => arr.data_type().clone(),
+            ColumnarValue::Scalar(scalar) => scalar.data_type().clone(),
+        }
+    }
+
+    fn scalar<T: 'static>(&self) -> Option<&T> {
+        match self {
+            ColumnarValue::Array(_) => None,
+            ColumnarValue::Scalar(scalar) => scalar.as_any().downcast_ref::<T>(),
+        }
+    }
+
+    /// The value of an Int32 scalar. Arrays, null scalars and scalars of other types are `None`.
+    pub fn as_i32(&self) -> Option<i32> {
+        self.scalar::<PrimitiveScalar<i32>>()
+            .filter(|x| x.data_type() == &DataType::Int32)
+            .and_then(|x| x.value())
+    }
+
+    /// The value of a Float64 scalar, see `as_i32`.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.scalar::<PrimitiveScalar<f64>>()
+            .and_then(|x| x.value())
+    }
+
+    /// The value of a Utf8 scalar, see `as_i32`.
+    pub fn as_str(&self) -> Option<&str> {
+        self.scalar::<Utf8Scalar<i32>>().and_then(|x| x.value())
+    }
+
+    /// The value of a Boolean scalar, see `as_i32`.
+    pub fn as_bool(&self) -> Option<bool> {
+        self.scalar::<BooleanScalar>().and_then(|x| x.value())
+    }
 }
 
 pub fn scalar_to_array(scalar: Box<dyn Scalar>, len: usize) -> Result<Arc<dyn Array>, Error> {
@@ -28,31 +70,45 @@ pub fn scalar_to_array(scalar: Box<dyn Scalar>, len: usize) -> Result<Arc<dyn Ar
         PhysicalType::Primitive(PrimitiveType::Int32) => scalar
             .as_any()
             .downcast_ref::<PrimitiveScalar<i32>>()
-            .and_then(|x| x.value())
-            .map(|val| Arc::new(PrimitiveArray::from_vec(vec![val; len])) as Arc<dyn Array>)
+            .map(|x| {
+                Arc::new(
+                    PrimitiveArray::<i32>::from(vec![x.value(); len]).to(x.data_type().clone()),
+                ) as Arc<dyn Array>
+            })
+            .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
+        PhysicalType::Primitive(PrimitiveType::Int64) => scalar
+            .as_any()
+            .downcast_ref::<PrimitiveScalar<i64>>()
+            .map(|x| {
+                Arc::new(
+                    PrimitiveArray::<i64>::from(vec![x.value(); len]).to(x.data_type().clone()),
+                ) as Arc<dyn Array>
+            })
+            .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
+        PhysicalType::Primitive(PrimitiveType::Float32) => scalar
+            .as_any()
+            .downcast_ref::<PrimitiveScalar<f32>>()
+            .map(|x| Arc::new(PrimitiveArray::<f32>::from(vec![x.value(); len])) as Arc<dyn Array>)
             .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
         PhysicalType::Primitive(PrimitiveType::Float64) => scalar
             .as_any()
             .downcast_ref::<PrimitiveScalar<f64>>()
-            .and_then(|x| x.value())
-            .map(|val| Arc::new(PrimitiveArray::from_vec(vec![val; len])) as Arc<dyn Array>)
+            .map(|x| Arc::new(PrimitiveArray::<f64>::from(vec![x.value(); len])) as Arc<dyn Array>)
             .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
         PhysicalType::Utf8 => scalar
             .as_any()
             .downcast_ref::<Utf8Scalar<i32>>()
-            .and_then(|x| x.value())
-            .map(|val| {
-                Arc::new(Utf8Array::<i32>::from::<&str, &Vec<Option<&str>>>(&vec![
-                    Some(val);
-                    len
-                ])) as Arc<dyn Array>
-            })
+            .map(|x| Arc::new(Utf8Array::<i32>::from(vec![x.value(); len])) as Arc<dyn Array>)
+            .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
+        PhysicalType::LargeUtf8 => scalar
+            .as_any()
+            .downcast_ref::<Utf8Scalar<i64>>()
+            .map(|x| Arc::new(Utf8Array::<i64>::from(vec![x.value(); len])) as Arc<dyn Array>)
             .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
         PhysicalType::Boolean => scalar
             .as_any()
             .downcast_ref::<BooleanScalar>()
-            .and_then(|x| x.value())
-            .map(|val| Arc::new(BooleanArray::from(&vec![Some(val); len])) as Arc<dyn Array>)
+            .map(|x| Arc::new(BooleanArray::from(&vec![x.value(); len])) as Arc<dyn Array>)
             .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
         _ => Err(Error::ScalarToArrayError(format!("{:?}", scalar))),
     }
@@ -0,0 +1,162 @@
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow2::array::Array;
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::Schema;
+use arrow2::error::ArrowError;
+use arrow2::io::parquet::write::{
+    Compression, Encoding, FileWriter, RowGroupIterator, Version, WriteOptions,
+};
+
+use crate::error::Error;
+use crate::physical_plan::{concat_chunks, Batch};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParquetCompression {
+    Uncompressed,
+    Snappy,
+    Zstd,
+}
+
+impl From<ParquetCompression> for Compression {
+    fn from(compression: ParquetCompression) -> Self {
+        match compression {
+            ParquetCompression::Uncompressed => Compression::Uncompressed,
+            ParquetCompression::Snappy => Compression::Snappy,
+            ParquetCompression::Zstd => Compression::Zstd,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WriteParquetOptions {
+    /// Number of rows per row group. Only the last row group may be smaller.
+    pub row_group_size: usize,
+    pub compression: ParquetCompression,
+}
+
+impl Default for WriteParquetOptions {
+    fn default() -> Self {
+        WriteParquetOptions {
+            row_group_size: 1024 * 1024,
+            compression: ParquetCompression::Snappy,
+        }
+    }
+}
+
+/// Writes the batches to a parquet file at `path`. The file is first written next to its
+/// destination and only moved into place once it is complete, so an existing file is never
+/// left half overwritten.
+pub fn write_parquet(
+    path: &str,
+    schema: &Schema,
+    batches: impl Iterator<Item = Batch>,
+    options: &WriteParquetOptions,
+) -> Result<(), Error> {
+    let tmp_path = format!("{}.tmp", path);
+    let result = write_parquet_file(path, Path::new(&tmp_path), schema, batches, options)
+        .and_then(|_| fs::rename(&tmp_path, path).map_err(Error::IoError));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Writes the file at `path`. Errors of the batches name `destination`, the path of the
+/// finished file.
+fn write_parquet_file(
+    destination: &str,
+    path: &Path,
+    schema: &Schema,
+    batches: impl Iterator<Item = Batch>,
+    options: &WriteParquetOptions,
+) -> Result<(), Error> {
+    let write_options = WriteOptions {
+        write_statistics: true,
+        compression: options.compression.into(),
+        version: Version::V2,
+    };
+    let encodings = schema.fields.iter().map(|_| Encoding::Plain).collect();
+    let row_groups = RowGroupIterator::try_new(
+        RowGroupChunks::new(batches, options.row_group_size).map(|chunk| {
+            chunk.map_err(|err| {
+                ArrowError::External(format!(" while writing `{}`", destination), Box::new(err))
+            })
+        }),
+        schema,
+        write_options,
+        encodings,
+    )
+    .map_err(Error::ArrowError)?;
+    let file = File::create(path).map_err(Error::IoError)?;
+    let mut writer =
+        FileWriter::try_new(file, schema.clone(), write_options).map_err(Error::ArrowError)?;
+    writer.start().map_err(Error::ArrowError)?;
+    for group in row_groups {
+        let (group, len) = group.map_err(Error::ArrowError)?;
+        writer.write(group, len).map_err(Error::ArrowError)?;
+    }
+    writer.end(None).map_err(Error::ArrowError)?;
+    Ok(())
+}
+
+/// Regroups a stream of batches into chunks of exactly `size` rows, except for the last one.
+struct RowGroupChunks<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    size: usize,
+    pending: Vec<Chunk<Arc<dyn Array>>>,
+    pending_rows: usize,
+}
+
+impl<I: Iterator<Item = Batch>> RowGroupChunks<I> {
+    fn new(input_iter: I, size: usize) -> Self {
+        RowGroupChunks {
+            input_iter,
+            size: size.max(1),
+            pending: Vec::new(),
+            pending_rows: 0,
+        }
+    }
+
+    /// Emits the first `size` pending rows and keeps the rest pending.
+    fn split_off(&mut self) -> Batch {
+        let chunk = concat_chunks(&std::mem::take(&mut self.pending))?;
+        let length = self.size.min(chunk.len());
+        let slice = |offset: usize, length: usize| {
+            Chunk::new(
+                chunk
+                    .arrays()
+                    .iter()
+                    .map(|array| Arc::from(array.slice(offset, length)))
+                    .collect::<Vec<Arc<dyn Array>>>(),
+            )
+        };
+        if length < chunk.len() {
+            self.pending.push(slice(length, chunk.len() - length));
+        }
+        self.pending_rows = chunk.len() - length;
+        Ok(slice(0, length))
+    }
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for RowGroupChunks<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending_rows < self.size {
+            match self.input_iter.next() {
+                Some(Ok(chunk)) => {
+                    if !chunk.is_empty() {
+                        self.pending_rows += chunk.len();
+                        self.pending.push(chunk);
+                    }
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None if self.pending.is_empty() => return None,
+                None => break,
+            }
+        }
+        Some(self.split_off())
+    }
+}
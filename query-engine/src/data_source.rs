@@ -1,29 +1,259 @@
 use std::fs::File;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use crate::error::Error;
-use arrow2::datatypes::Schema;
+use crate::execution_context::CoercionMode;
+use crate::logical_plan::optimizer::{PrunablePredicate, PruningLiteral, PruningOperator};
+use crate::physical_plan::{Batch, LimitIterator};
+use arrow2::array::{Array, BooleanArray, PrimitiveArray, Utf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::compute;
+use arrow2::compute::cast::CastOptions;
+use arrow2::datatypes::{DataType, Field, Schema};
+use arrow2::io::parquet::read::statistics::{
+    deserialize_statistics, PrimitiveStatistics, Statistics,
+};
 use arrow2::io::parquet::read::{infer_schema, read_metadata, FileMetaData, FileReader};
+use arrow2::scalar::{BooleanScalar, PrimitiveScalar, Scalar, Utf8Scalar};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 
 pub enum DataSource {
     Parquet(ParquetDataSource),
+    ParquetFiles(Box<ParquetFilesDataSource>),
+    Memory(MemoryDataSource),
+    Csv(CsvDataSource),
 }
 
 impl DataSource {
     pub fn schema(&self) -> Schema {
         match self {
-            DataSource::Parquet(ds) => ds.schema(),
+            DataSource::Parquet(ds) => ds.schema().clone(),
+            DataSource::ParquetFiles(ds) => ds.schema(),
+            DataSource::Memory(ds) => ds.schema(),
+            DataSource::Csv(ds) => ds.schema(),
         }
     }
-    pub fn scan(self, projection: Option<Vec<String>>) -> FileReader<File> {
+    /// Scans the columns at the positions in `projection`, which have to be in ascending order.
+    /// Parquet sources stop decoding after `limit` rows, other sources ignore the limit.
+    pub fn scan(
+        self,
+        projection: Option<Vec<usize>>,
+        filters: Vec<PrunablePredicate>,
+        limit: Option<usize>,
+    ) -> Box<dyn Iterator<Item = Batch>> {
         match self {
-            DataSource::Parquet(ds) => ds.scan(projection),
+            DataSource::Parquet(ds) => ds.scan(projection, filters, limit),
+            DataSource::ParquetFiles(ds) => ds.scan(projection, filters, limit),
+            DataSource::Memory(ds) => ds.scan(projection, filters),
+            DataSource::Csv(ds) => ds.scan(projection, filters),
         }
     }
+    /// Positions of the columns named in `projection`.
+    pub fn ordinals(&self, projection: &[String]) -> Vec<usize> {
+        self.schema()
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| projection.contains(&x.name))
+            .map(|(i, _)| i)
+            .collect()
+    }
+    /// Number of rows of the source, if it is known without scanning it.
+    pub fn num_rows(&self) -> Option<usize> {
+        match self {
+            DataSource::Parquet(ds) => Some(ds.metadata.num_rows as usize),
+            DataSource::ParquetFiles(ds) => {
+                Some(ds.files.iter().map(|x| x.metadata.num_rows as usize).sum())
+            }
+            DataSource::Memory(ds) => Some(ds.chunks.iter().map(|x| x.len()).sum()),
+            DataSource::Csv(_) => None,
+        }
+    }
+    /// Statistics of the named column, if the source stores them, i.e. in the metadata of a
+    /// parquet file.
+    pub fn statistics(&self, column: &str) -> Option<ColumnStatistics> {
+        match self {
+            DataSource::Parquet(ds) => ds.statistics(column),
+            _ => None,
+        }
+    }
+    /// Columns the rows of the source are sorted on in ascending order with nulls first, the
+    /// most significant column first.
+    pub fn sorted_by(&self) -> &[String] {
+        match self {
+            DataSource::Memory(ds) => &ds.sorted_by,
+            _ => &[],
+        }
+    }
+    /// Number of times the source has been scanned, including scans of its clones.
+    pub fn scans(&self) -> usize {
+        match self {
+            DataSource::Parquet(ds) => ds.scans.load(Ordering::Relaxed),
+            DataSource::ParquetFiles(ds) => ds.scans.load(Ordering::Relaxed),
+            DataSource::Memory(ds) => ds.scans.load(Ordering::Relaxed),
+            DataSource::Csv(ds) => ds.scans.load(Ordering::Relaxed),
+        }
+    }
+    /// Number of rows decoded from parquet files by scans of the source, including scans of its
+    /// clones, before any filter or limit is applied. Other sources report zero.
+    pub fn decoded_rows(&self) -> usize {
+        match self {
+            DataSource::Parquet(ds) => ds.decoded_rows.load(Ordering::Relaxed),
+            DataSource::ParquetFiles(ds) => ds
+                .files
+                .iter()
+                .map(|x| x.decoded_rows.load(Ordering::Relaxed))
+                .sum(),
+            DataSource::Memory(_) | DataSource::Csv(_) => 0,
+        }
+    }
+    pub fn path(&self) -> &str {
+        match self {
+            DataSource::Parquet(ds) => &ds.path,
+            DataSource::ParquetFiles(ds) => &ds.pattern,
+            DataSource::Memory(_) => "memory",
+            DataSource::Csv(ds) => &ds.path,
+        }
+    }
+    /// Opens the underlying source again, so that it can be scanned independently.
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        match self {
+            DataSource::Parquet(ds) => Ok(DataSource::Parquet(ParquetDataSource {
+                scans: ds.scans.clone(),
+                decoded_rows: ds.decoded_rows.clone(),
+                ..ParquetDataSource::new(&ds.path)?
+            })),
+            DataSource::ParquetFiles(ds) => {
+                Ok(DataSource::ParquetFiles(Box::new(ParquetFilesDataSource {
+                    pattern: ds.pattern.clone(),
+                    options: ds.options.clone(),
+                    files: ds
+                        .files
+                        .iter()
+                        .map(|x| {
+                            Ok(ParquetDataSource {
+                                decoded_rows: x.decoded_rows.clone(),
+                                ..ParquetDataSource::new(&x.path)?
+                            })
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?,
+                    partitions: ds.partitions.clone(),
+                    schema: ds.schema.clone(),
+                    scans: ds.scans.clone(),
+                })))
+            }
+            DataSource::Memory(ds) => Ok(DataSource::Memory(ds.clone())),
+            DataSource::Csv(ds) => Ok(DataSource::Csv(ds.clone())),
+        }
+    }
+}
+
+/// Statistics of a column over all rows of a data source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnStatistics {
+    /// Smallest non-null value. Only known for Int32 and Float64 columns.
+    pub min: Option<PruningLiteral>,
+    /// Largest non-null value. Only known for Int32 and Float64 columns.
+    pub max: Option<PruningLiteral>,
+    pub null_count: usize,
+}
+
+/// Serialized form of a data source. Only the location of the data is stored, the source is
+/// opened again when it is deserialized.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "lowercase")]
+enum DataSourceDescription {
+    Parquet {
+        path: String,
+    },
+    ParquetFiles {
+        pattern: String,
+        #[serde(default)]
+        options: ParquetReadOptions,
+    },
+    Csv {
+        path: String,
+        options: CsvReadOptions,
+    },
+}
+
+impl Serialize for DataSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            DataSource::Parquet(ds) => DataSourceDescription::Parquet {
+                path: ds.path.clone(),
+            }
+            .serialize(serializer),
+            DataSource::ParquetFiles(ds) => DataSourceDescription::ParquetFiles {
+                pattern: ds.pattern.clone(),
+                options: ds.options.clone(),
+            }
+            .serialize(serializer),
+            DataSource::Csv(ds) => DataSourceDescription::Csv {
+                path: ds.path.clone(),
+                options: ds.options.clone(),
+            }
+            .serialize(serializer),
+            DataSource::Memory(_) => Err(ser::Error::custom(
+                "memory data sources can't be serialized",
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DataSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match DataSourceDescription::deserialize(deserializer)? {
+            DataSourceDescription::Parquet { path } => ParquetDataSource::new(&path)
+                .map(DataSource::Parquet)
+                .map_err(de::Error::custom),
+            DataSourceDescription::ParquetFiles { pattern, options } => {
+                ParquetFilesDataSource::glob_with(&pattern, options)
+                    .map(|ds| DataSource::ParquetFiles(Box::new(ds)))
+                    .map_err(de::Error::custom)
+            }
+            DataSourceDescription::Csv { path, options } => CsvDataSource::new(&path, options)
+                .map(DataSource::Csv)
+                .map_err(de::Error::custom),
+        }
+    }
+}
+
+/// Minimum and maximum of a column chunk. Byte array statistics may be truncated by the writer,
+/// so only the bounds of Int32 and Float64 columns are exact.
+fn statistics_bounds(statistics: &dyn Statistics) -> Option<(PruningLiteral, PruningLiteral)> {
+    match statistics.data_type() {
+        DataType::Int32 => {
+            let statistics = statistics
+                .as_any()
+                .downcast_ref::<PrimitiveStatistics<i32>>()?;
+            Some((
+                PruningLiteral::Integer(statistics.min_value?),
+                PruningLiteral::Integer(statistics.max_value?),
+            ))
+        }
+        DataType::Float64 => {
+            let statistics = statistics
+                .as_any()
+                .downcast_ref::<PrimitiveStatistics<f64>>()?;
+            Some((
+                PruningLiteral::Float(statistics.min_value?),
+                PruningLiteral::Float(statistics.max_value?),
+            ))
+        }
+        _ => None,
+    }
 }
 
 pub struct ParquetDataSource {
+    path: String,
     file: File,
-    metadata: FileMetaData,
+    metadata: Box<FileMetaData>,
+    /// Inferred from the metadata once when the file is opened.
+    schema: Schema,
+    scans: Arc<AtomicUsize>,
+    decoded_rows: Arc<AtomicUsize>,
 }
 
 impl ParquetDataSource {
@@ -31,9 +261,14 @@ impl ParquetDataSource {
         match File::open(path) {
             Ok(mut file) => {
                 let metadata = read_metadata(&mut file).map_err(|err| Error::ArrowError(err))?;
+                let schema = infer_schema(&metadata).map_err(Error::ArrowError)?;
                 Ok(ParquetDataSource {
+                    path: path.to_string(),
                     file: file,
-                    metadata: metadata,
+                    metadata: Box::new(metadata),
+                    schema,
+                    scans: Arc::new(AtomicUsize::new(0)),
+                    decoded_rows: Arc::new(AtomicUsize::new(0)),
                 })
             }
             Err(err) => Err(Error::IoError(err)),
@@ -42,31 +277,640 @@ impl ParquetDataSource {
 }
 
 impl ParquetDataSource {
-    fn schema(&self) -> Schema {
-        infer_schema(&self.metadata).unwrap()
+    /// The schema inferred when opening the file.
+    pub(crate) fn schema(&self) -> &Schema {
+        &self.schema
     }
-    pub fn scan(self, projection: Option<Vec<String>>) -> FileReader<File> {
-        let projection: Option<Vec<usize>> = projection.map(|projection| {
-            self.schema()
-                .fields
-                .into_iter()
-                .enumerate()
-                .filter_map(|(i, x)| {
-                    if projection.contains(&x.name) {
-                        Some(i)
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        });
-        FileReader::try_new(
+    /// Combines the statistics of the column chunks of all row groups. Returns None if a row
+    /// group has no statistics for the column. The bounds are unknown if a row group with
+    /// non-null values has none, row groups with only nulls don't have bounds.
+    fn statistics(&self, column: &str) -> Option<ColumnStatistics> {
+        let field = self.schema().fields.iter().find(|x| x.name == column)?;
+        let mut null_count = 0;
+        let mut bounds = Vec::new();
+        for row_group in &self.metadata.row_groups {
+            let statistics = deserialize_statistics(field, row_group.columns())
+                .ok()?
+                .pop()??;
+            let nulls = statistics.null_count()? as usize;
+            null_count += nulls;
+            if nulls < row_group.num_rows() as usize {
+                bounds.push(statistics_bounds(statistics.as_ref()));
+            }
+        }
+        let (min, max) = match bounds.into_iter().collect::<Option<Vec<_>>>() {
+            Some(bounds) => (
+                bounds
+                    .iter()
+                    .map(|(min, _)| min.clone())
+                    .reduce(|acc, x| if x < acc { x } else { acc }),
+                bounds
+                    .into_iter()
+                    .map(|(_, max)| max)
+                    .reduce(|acc, x| if x > acc { x } else { acc }),
+            ),
+            None => (None, None),
+        };
+        Some(ColumnStatistics {
+            min,
+            max,
+            null_count,
+        })
+    }
+    /// Reads the projected columns. The filters are applied to every chunk as soon as it is
+    /// decoded. With a limit, row groups are decoded in chunks of `limit` rows and the scan stops
+    /// after `limit` rows, so the remaining chunks and row groups are never read.
+    pub fn scan(
+        self,
+        projection: Option<Vec<usize>>,
+        filters: Vec<PrunablePredicate>,
+        limit: Option<usize>,
+    ) -> Box<dyn Iterator<Item = Batch>> {
+        self.scans.fetch_add(1, Ordering::Relaxed);
+        let columns = projected_columns(self.schema(), &projection);
+        // The limit of the reader itself drops the row group that exceeds it, so the chunk size
+        // is used instead.
+        let reader = FileReader::try_new(
             self.file,
             projection.as_ref().map(|x| x.as_slice()),
+            limit,
             None,
             None,
-            None,
         )
-        .unwrap()
+        .unwrap();
+        let decoded_rows = self.decoded_rows;
+        let batches = reader.map(move |chunk| {
+            chunk.map_err(Error::ArrowError).and_then(|chunk| {
+                decoded_rows.fetch_add(chunk.len(), Ordering::Relaxed);
+                filter_chunk(chunk, &columns, &filters)
+            })
+        });
+        match limit {
+            Some(limit) => Box::new(LimitIterator::new(batches, limit)),
+            None => Box::new(batches),
+        }
+    }
+}
+
+/// Options to read several parquet files.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ParquetReadOptions {
+    /// Adds a column for every `key=value` directory in the file paths, e.g. a `country` column
+    /// for `country=Canada/data.parquet`. The values are strings and the columns are appended to
+    /// the columns of the files.
+    pub hive_partitioning: bool,
+    /// Integer type that all integer columns are cast to while scanning, so that files storing
+    /// a column with different integer widths can be read together.
+    pub unify_integer_width: Option<DataType>,
+}
+
+/// The parquet files matching a glob pattern, which are scanned one after the other. All files
+/// need the schema of the first file in path order.
+pub struct ParquetFilesDataSource {
+    pattern: String,
+    options: ParquetReadOptions,
+    files: Vec<ParquetDataSource>,
+    /// Values of the partition columns of every file.
+    partitions: Vec<Vec<String>>,
+    schema: Schema,
+    scans: Arc<AtomicUsize>,
+}
+
+impl ParquetFilesDataSource {
+    /// Opens the files matching `pattern`, e.g. `data/year=*/*.parquet`.
+    pub fn glob(pattern: &str) -> Result<Self, Error> {
+        Self::glob_with(pattern, ParquetReadOptions::default())
+    }
+
+    pub fn glob_with(pattern: &str, options: ParquetReadOptions) -> Result<Self, Error> {
+        let files = glob::glob(pattern)
+            .map_err(Error::GlobError)?
+            .map(|path| {
+                let path = path.map_err(|err| Error::IoError(err.into()))?;
+                ParquetDataSource::new(&path.to_string_lossy())
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let first = files
+            .first()
+            .ok_or_else(|| Error::NoMatchingFiles(pattern.to_string()))?;
+        if let Some(width) = options
+            .unify_integer_width
+            .as_ref()
+            .filter(|x| !is_integer(x))
+        {
+            return Err(Error::CastError(
+                "integer columns".to_string(),
+                format!("{:?}", width),
+            ));
+        }
+        let file_schema = |file: &ParquetDataSource| Schema::from(unified_fields(file, &options));
+        let schema = file_schema(first);
+        let partitions = files
+            .iter()
+            .map(|x| match options.hive_partitioning {
+                true => partition_values(&x.path),
+                false => vec![],
+            })
+            .collect::<Vec<_>>();
+        let keys = |partitions: &[(String, String)]| {
+            partitions
+                .iter()
+                .map(|(key, _)| key.clone())
+                .collect::<Vec<_>>()
+        };
+        if let Some((file, _)) = files.iter().zip(&partitions).find(|(x, partition)| {
+            file_schema(x) != schema || keys(partition) != keys(&partitions[0])
+        }) {
+            return Err(Error::IncompatibleFiles(
+                file.path.clone(),
+                first.path.clone(),
+            ));
+        }
+        let fields = schema
+            .fields
+            .into_iter()
+            .chain(
+                keys(&partitions[0])
+                    .iter()
+                    .map(|key| Field::new(key, DataType::Utf8, false)),
+            )
+            .collect::<Vec<_>>();
+        Ok(ParquetFilesDataSource {
+            pattern: pattern.to_string(),
+            options,
+            files,
+            partitions: partitions
+                .into_iter()
+                .map(|x| x.into_iter().map(|(_, value)| value).collect())
+                .collect(),
+            schema: fields.into(),
+            scans: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Paths of the matched files in scan order.
+    pub fn paths(&self) -> Vec<&str> {
+        self.files.iter().map(|x| x.path.as_str()).collect()
+    }
+}
+
+fn is_integer(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+    )
+}
+
+/// Fields of `file` after the integer columns were cast to the unified width of `options`.
+fn unified_fields(file: &ParquetDataSource, options: &ParquetReadOptions) -> Vec<Field> {
+    file.schema()
+        .fields
+        .iter()
+        .cloned()
+        .map(|x| match &options.unify_integer_width {
+            Some(width) if is_integer(&x.data_type) => Field {
+                data_type: width.clone(),
+                ..x
+            },
+            _ => x,
+        })
+        .collect()
+}
+
+/// The `key=value` directories of `path`, from the outermost to the innermost.
+fn partition_values(path: &str) -> Vec<(String, String)> {
+    std::path::Path::new(path)
+        .parent()
+        .into_iter()
+        .flat_map(|x| x.components())
+        .filter_map(|x| {
+            let (key, value) = x.as_os_str().to_str()?.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+impl ParquetFilesDataSource {
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+    /// Scans the files one after the other. The filters on the columns of the files are applied
+    /// by the files while decoding. The filters on the partition columns, and on columns a file
+    /// stores with a different type than the source, are applied after the partition columns
+    /// were appended and the columns were cast. Every file stops decoding after `limit` rows.
+    /// A file whose schema differs from the source, e.g. because it was rewritten after the
+    /// pattern was matched, fails the scan.
+    pub fn scan(
+        self,
+        projection: Option<Vec<usize>>,
+        filters: Vec<PrunablePredicate>,
+        limit: Option<usize>,
+    ) -> Box<dyn Iterator<Item = Batch>> {
+        self.scans.fetch_add(1, Ordering::Relaxed);
+        let columns = projected_columns(&self.schema, &projection);
+        let file_columns = self.schema.fields.len() - self.partitions[0].len();
+        let file_fields = self.schema.fields[..file_columns].to_vec();
+        let projection =
+            projection.unwrap_or_else(|| (0..self.schema.fields.len()).collect::<Vec<_>>());
+        let (file_projection, partition_projection): (Vec<usize>, Vec<usize>) =
+            projection.into_iter().partition(|i| *i < file_columns);
+        // A scan without any column doesn't know the number of rows, so the first column is
+        // read to size the partition columns and dropped again.
+        let drop_first = file_projection.is_empty();
+        let file_projection = match drop_first {
+            true => vec![0],
+            false => file_projection,
+        };
+        // Columns are cast to the type of the source when the file stores them differently.
+        let file_types = file_projection
+            .iter()
+            .map(|i| self.schema.fields[*i].data_type.clone())
+            .collect::<Vec<_>>();
+        if let Some(file) = self
+            .files
+            .iter()
+            .find(|file| unified_fields(file, &self.options) != file_fields)
+        {
+            return Box::new(std::iter::once(Err(Error::SchemaMismatch(format!(
+                "file `{}` changed its schema",
+                file.path
+            )))));
+        }
+        Box::new(
+            self.files
+                .into_iter()
+                .zip(self.partitions)
+                .flat_map(move |(file, partition)| {
+                    let values = partition_projection
+                        .iter()
+                        .map(|i| partition[*i - file_columns].clone())
+                        .collect::<Vec<_>>();
+                    let columns = columns.clone();
+                    let file_types = file_types.clone();
+                    let (file_filters, filters): (Vec<_>, Vec<_>) =
+                        filters.iter().cloned().partition(|filter| {
+                            file.schema()
+                                .fields
+                                .iter()
+                                .zip(&file_fields)
+                                .any(|(stored, field)| {
+                                    stored.name == filter.column
+                                        && stored.data_type == field.data_type
+                                })
+                        });
+                    file.scan(Some(file_projection.clone()), file_filters, limit)
+                        .map(move |chunk| {
+                            let chunk = chunk?;
+                            let len = chunk.len();
+                            let arrays = chunk
+                                .into_arrays()
+                                .into_iter()
+                                .zip(&file_types)
+                                .map(|(array, data_type)| match array.data_type() == data_type {
+                                    true => Ok(array),
+                                    false => compute::cast::cast(
+                                        array.as_ref(),
+                                        data_type,
+                                        CastOptions::default(),
+                                    )
+                                    .map(Arc::from)
+                                    .map_err(Error::ArrowError),
+                                })
+                                .collect::<Result<Vec<_>, Error>>()?
+                                .into_iter()
+                                .skip(drop_first as usize)
+                                .chain(values.iter().map(|value| {
+                                    Arc::new(Utf8Array::<i32>::from_slice(vec![
+                                        value.as_str();
+                                        len
+                                    ])) as Arc<dyn Array>
+                                }))
+                                .collect();
+                            filter_chunk(Chunk::new(arrays), &columns, &filters)
+                        })
+                }),
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct MemoryDataSource {
+    schema: Schema,
+    chunks: Vec<Chunk<Arc<dyn Array>>>,
+    sorted_by: Vec<String>,
+    scans: Arc<AtomicUsize>,
+}
+
+impl MemoryDataSource {
+    pub fn new(schema: Schema, chunks: Vec<Chunk<Arc<dyn Array>>>) -> Self {
+        MemoryDataSource {
+            schema,
+            chunks,
+            sorted_by: Vec::new(),
+            scans: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Declares that the rows, across all chunks, are sorted on the given columns in ascending
+    /// order with nulls first. The order isn't checked.
+    pub fn with_sorted_by(mut self, columns: Vec<String>) -> Self {
+        self.sorted_by = columns;
+        self
+    }
+}
+
+impl MemoryDataSource {
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+    pub fn scan(
+        self,
+        projection: Option<Vec<usize>>,
+        filters: Vec<PrunablePredicate>,
+    ) -> Box<dyn Iterator<Item = Batch>> {
+        self.scans.fetch_add(1, Ordering::Relaxed);
+        let columns = projected_columns(&self.schema, &projection);
+        let width = self.schema.fields.len();
+        Box::new(self.chunks.into_iter().map(move |chunk| {
+            // The width is checked before projecting, the projection would hide extra columns.
+            if chunk.arrays().len() != width {
+                return Err(Error::SchemaMismatch(format!(
+                    "expected {} columns but got {}",
+                    width,
+                    chunk.arrays().len()
+                )));
+            }
+            let chunk = match &projection {
+                Some(projection) => Chunk::new(
+                    projection
+                        .iter()
+                        .map(|i| chunk[*i].clone())
+                        .collect::<Vec<Arc<dyn Array>>>(),
+                ),
+                None => chunk,
+            };
+            filter_chunk(chunk, &columns, &filters)
+        }))
+    }
+}
+
+/// Options to read a CSV file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CsvReadOptions {
+    /// Whether the first row contains the column names. Without a header the columns are
+    /// named `col0`, `col1`, ... and the first row is read as data.
+    pub has_header: bool,
+    pub delimiter: u8,
+    /// Number of rows the column types are inferred from.
+    pub infer_schema_rows: usize,
+    /// How values of later rows that don't match the inferred type are read. They either
+    /// fail the scan or become null.
+    pub coercion_mode: CoercionMode,
+}
+
+impl Default for CsvReadOptions {
+    fn default() -> Self {
+        CsvReadOptions {
+            has_header: true,
+            delimiter: b',',
+            infer_schema_rows: 100,
+            coercion_mode: CoercionMode::Strict,
+        }
+    }
+}
+
+/// Number of rows of the chunks produced by a CSV scan.
+const CSV_BATCH_SIZE: usize = 1024;
+
+/// A CSV file. The column types are inferred from the first rows when the source is created:
+/// columns whose values all parse as booleans, integers or floats get the respective type,
+/// all others are strings. Empty values are null.
+#[derive(Clone)]
+pub struct CsvDataSource {
+    path: String,
+    options: CsvReadOptions,
+    schema: Schema,
+    scans: Arc<AtomicUsize>,
+}
+
+impl CsvDataSource {
+    pub fn new(path: &str, options: CsvReadOptions) -> Result<Self, Error> {
+        let mut reader = csv_reader(path, &options)?;
+        let header = match options.has_header {
+            true => Some(reader.headers().map_err(Error::CsvError)?.clone()),
+            false => None,
+        };
+        let records = reader
+            .records()
+            .take(options.infer_schema_rows)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::CsvError)?;
+        let columns = match (&header, records.first()) {
+            (Some(header), _) => header.len(),
+            (None, Some(record)) => record.len(),
+            (None, None) => 0,
+        };
+        let fields = (0..columns)
+            .map(|i| {
+                let name = match &header {
+                    Some(header) => header[i].to_string(),
+                    None => format!("col{}", i),
+                };
+                let data_type = infer_csv_type(records.iter().filter_map(|x| x.get(i)));
+                Field::new(&name, data_type, true)
+            })
+            .collect::<Vec<Field>>();
+        Ok(CsvDataSource {
+            path: path.to_string(),
+            options,
+            schema: fields.into(),
+            scans: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+}
+
+impl CsvDataSource {
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+    pub fn scan(
+        self,
+        projection: Option<Vec<usize>>,
+        filters: Vec<PrunablePredicate>,
+    ) -> Box<dyn Iterator<Item = Batch>> {
+        self.scans.fetch_add(1, Ordering::Relaxed);
+        let columns = projected_columns(&self.schema, &projection);
+        let ordinals = projection.unwrap_or_else(|| (0..self.schema.fields.len()).collect());
+        let mut records = match csv_reader(&self.path, &self.options) {
+            Ok(reader) => reader.into_records(),
+            Err(err) => return Box::new(std::iter::once(Err(err))),
+        };
+        let fields = self.schema.fields;
+        let mode = self.options.coercion_mode;
+        Box::new(std::iter::from_fn(move || {
+            let batch = records
+                .by_ref()
+                .take(CSV_BATCH_SIZE)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(Error::CsvError);
+            match batch {
+                Ok(batch) if batch.is_empty() => None,
+                Ok(batch) => Some(
+                    ordinals
+                        .iter()
+                        .map(|i| parse_csv_column(&batch, *i, &fields[*i].data_type, mode))
+                        .collect::<Result<Vec<_>, Error>>()
+                        .and_then(|arrays| filter_chunk(Chunk::new(arrays), &columns, &filters)),
+                ),
+                Err(err) => Some(Err(err)),
+            }
+        }))
+    }
+}
+
+fn csv_reader(path: &str, options: &CsvReadOptions) -> Result<csv::Reader<File>, Error> {
+    csv::ReaderBuilder::new()
+        .has_headers(options.has_header)
+        .delimiter(options.delimiter)
+        .from_path(path)
+        .map_err(Error::CsvError)
+}
+
+/// The narrowest of Boolean, Int64, Float64 and Utf8 that can hold all non-empty values.
+fn infer_csv_type<'a>(values: impl Iterator<Item = &'a str>) -> DataType {
+    let values = values.filter(|x| !x.is_empty()).collect::<Vec<_>>();
+    if values.is_empty() {
+        DataType::Utf8
+    } else if values.iter().all(|x| x.parse::<bool>().is_ok()) {
+        DataType::Boolean
+    } else if values.iter().all(|x| x.parse::<i64>().is_ok()) {
+        DataType::Int64
+    } else if values.iter().all(|x| x.parse::<f64>().is_ok()) {
+        DataType::Float64
+    } else {
+        DataType::Utf8
+    }
+}
+
+fn parse_csv_value<T: std::str::FromStr>(
+    value: Option<&str>,
+    data_type: &DataType,
+    mode: CoercionMode,
+) -> Result<Option<T>, Error> {
+    match value.map(|x| (x, x.parse::<T>())) {
+        None => Ok(None),
+        Some((_, Ok(value))) => Ok(Some(value)),
+        Some((_, Err(_))) if mode == CoercionMode::Lenient => Ok(None),
+        Some((x, Err(_))) => Err(Error::CastError(x.to_string(), format!("{:?}", data_type))),
+    }
+}
+
+/// Parses the values of column `i` as `data_type`. Empty and missing values are null, values
+/// that can't be parsed are handled according to `mode`.
+fn parse_csv_column(
+    records: &[csv::StringRecord],
+    i: usize,
+    data_type: &DataType,
+    mode: CoercionMode,
+) -> Result<Arc<dyn Array>, Error> {
+    let values = records.iter().map(|x| x.get(i).filter(|x| !x.is_empty()));
+    Ok(match data_type {
+        DataType::Boolean => Arc::new(
+            values
+                .map(|x| parse_csv_value::<bool>(x, data_type, mode))
+                .collect::<Result<BooleanArray, Error>>()?,
+        ),
+        DataType::Int64 => Arc::new(
+            values
+                .map(|x| parse_csv_value::<i64>(x, data_type, mode))
+                .collect::<Result<PrimitiveArray<i64>, Error>>()?,
+        ),
+        DataType::Float64 => Arc::new(
+            values
+                .map(|x| parse_csv_value::<f64>(x, data_type, mode))
+                .collect::<Result<PrimitiveArray<f64>, Error>>()?,
+        ),
+        _ => Arc::new(values.collect::<Utf8Array<i32>>()),
+    })
+}
+
+/// Names of the columns of the chunks produced by a scan with the given projection.
+fn projected_columns(schema: &Schema, projection: &Option<Vec<usize>>) -> Vec<String> {
+    schema
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| match projection {
+            Some(projection) => projection.contains(i),
+            None => true,
+        })
+        .map(|(_, x)| x.name.clone())
+        .collect()
+}
+
+/// Keeps the rows of `chunk` that satisfy all `filters`. Rows for which a filter evaluates to
+/// null are dropped.
+fn filter_chunk(
+    chunk: Chunk<Arc<dyn Array>>,
+    columns: &[String],
+    filters: &[PrunablePredicate],
+) -> Batch {
+    let mut mask: Option<BooleanArray> = None;
+    for filter in filters {
+        let array = columns
+            .iter()
+            .position(|x| x == &filter.column)
+            .map(|i| chunk[i].as_ref())
+            .ok_or_else(|| Error::NoFieldInLogicalPlan(filter.column.clone()))?;
+        let literal = to_scalar(&filter.literal);
+        let result = match filter.op {
+            PruningOperator::Eq => compute::comparison::eq_scalar(array, &*literal),
+            PruningOperator::Neq => compute::comparison::neq_scalar(array, &*literal),
+            PruningOperator::Gt => compute::comparison::gt_scalar(array, &*literal),
+            PruningOperator::GtEq => compute::comparison::gt_eq_scalar(array, &*literal),
+            PruningOperator::Lt => compute::comparison::lt_scalar(array, &*literal),
+            PruningOperator::LtEq => compute::comparison::lt_eq_scalar(array, &*literal),
+        };
+        let result = match result.validity() {
+            Some(validity) => {
+                BooleanArray::from_data(DataType::Boolean, result.values() & validity, None)
+            }
+            None => result,
+        };
+        mask = Some(match mask {
+            Some(mask) => compute::boolean::and(&mask, &result).map_err(Error::ArrowError)?,
+            None => result,
+        });
+    }
+    match mask {
+        Some(mask) => Ok(Chunk::new(
+            compute::filter::filter_chunk(&chunk, &mask)
+                .map_err(Error::ArrowError)?
+                .into_arrays()
+                .into_iter()
+                .map(Arc::from)
+                .collect(),
+        )),
+        None => Ok(chunk),
+    }
+}
+
+fn to_scalar(literal: &PruningLiteral) -> Box<dyn Scalar> {
+    match literal {
+        PruningLiteral::Bool(value) => Box::new(BooleanScalar::new(Some(*value))),
+        PruningLiteral::String(value) => Box::new(Utf8Scalar::<i32>::new(Some(value.as_str()))),
+        PruningLiteral::Integer(value) => {
+            Box::new(PrimitiveScalar::new(DataType::Int32, Some(*value)))
+        }
+        PruningLiteral::Float(value) => {
+            Box::new(PrimitiveScalar::new(DataType::Float64, Some(*value)))
+        }
     }
 }
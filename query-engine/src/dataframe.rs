@@ -3,11 +3,19 @@ use std::sync::Arc;
 use arrow2::{array::Array, chunk::Chunk, datatypes::Schema};
 
 use crate::{
-    data_source::{DataSource, ParquetDataSource},
+    data_sink::{write_parquet, WriteParquetOptions},
+    data_source::{
+        CsvDataSource, CsvReadOptions, DataSource, MemoryDataSource, ParquetDataSource,
+        ParquetFilesDataSource, ParquetReadOptions,
+    },
     error::Error,
+    execution_context::ExecutionConfig,
     logical_plan::{
-        logical_expression::LogicalExpression, Aggregate, LogicalPlan, Projection, Scan, Selection,
+        logical_expression::{Column, LogicalExpression, LogicalExpressionMethods},
+        Aggregate, Distinct, Join, Limit, LogicalPlan, Projection, Sample, Scan, Selection, Unnest,
+        Values, Window,
     },
+    physical_plan::Batch,
 };
 
 pub trait DataFrameTrait {
@@ -15,45 +23,168 @@ pub trait DataFrameTrait {
 
     fn filter(self, exprs: LogicalExpression) -> Self;
 
+    /// Keeps the rows satisfying all predicates. An empty list keeps every row.
+    fn filter_all(self, exprs: Vec<LogicalExpression>) -> Self
+    where
+        Self: Sized,
+    {
+        match exprs.into_iter().reduce(|acc, x| acc.and(x)) {
+            Some(predicate) => self.filter(predicate),
+            None => self,
+        }
+    }
+
     fn aggregate(
         self,
         group_by: Vec<LogicalExpression>,
         aggregate_expr: Vec<LogicalExpression>,
     ) -> Self;
 
-    fn schema(&self) -> &Schema;
+    /// Keeps every row with probability `fraction`. The same seed selects the same rows.
+    fn sample(self, fraction: f64, seed: u64) -> Self;
+
+    /// Keeps the first `n` rows.
+    fn limit(self, n: usize) -> Self;
+
+    /// Inner join with `right` on pairs of (left column, right column) keys. Rows match if all
+    /// key pairs are equal.
+    fn join(self, right: Self, on: Vec<(&str, &str)>) -> Self;
+
+    /// Keeps the first row for every distinct combination of values of the named columns.
+    fn distinct_on(self, columns: Vec<&str>) -> Self;
+
+    /// Turns every element of the list column `column` into its own row, repeating the other
+    /// columns. Rows with an empty or null list are dropped.
+    fn unnest(self, column: &str) -> Self;
+
+    /// Like `unnest`, but keeps rows with an empty or null list as a single row with a null
+    /// element.
+    fn unnest_preserve_empty(self, column: &str) -> Self;
+
+    /// Appends a column for every window expression, e.g. `cumsum(col("salary"))`.
+    fn window(self, exprs: Vec<LogicalExpression>) -> Self;
+
+    fn schema(&self) -> Result<&Schema, Error>;
 
     fn logical_plan(self) -> LogicalPlan;
 }
 
+/// A rewrite of a logical plan, see `DataFrame::transform`.
+type PlanTransform = Arc<dyn Fn(LogicalPlan) -> LogicalPlan>;
+
 pub struct DataFrame {
     plan: LogicalPlan,
+    transforms: Vec<PlanTransform>,
 }
 
 impl DataFrame {
-    fn new(plan: LogicalPlan) -> Self {
-        DataFrame { plan: plan }
+    pub(crate) fn new(plan: LogicalPlan) -> Self {
+        DataFrame {
+            plan: plan,
+            transforms: Vec::new(),
+        }
+    }
+
+    /// Registers a rewrite of the logical plan, e.g. a custom optimization. DataFrames built on
+    /// top of this one keep the rewrite. Before execution the whole plan is optimized and the
+    /// rewrites are applied to the optimized plan in the order they were registered, see
+    /// `optimized_logical_plan`. `logical_plan` returns the plan as built, without rewrites.
+    pub fn transform<F>(mut self, f: F) -> Self
+    where
+        F: Fn(LogicalPlan) -> LogicalPlan + 'static,
+    {
+        self.transforms.push(Arc::new(f));
+        self
+    }
+
+    /// Replaces the plan with `f(plan)`, keeping the transforms.
+    fn map_plan<F: FnOnce(LogicalPlan) -> LogicalPlan>(self, f: F) -> Self {
+        DataFrame {
+            plan: f(self.plan),
+            transforms: self.transforms,
+        }
+    }
+
+    /// The only place where the transforms are applied: to the optimized plan, or to the plan as
+    /// built if `optimize` is false.
+    fn transformed_plan(self, optimize: bool) -> LogicalPlan {
+        let plan = if optimize {
+            self.plan.optimize()
+        } else {
+            self.plan
+        };
+        self.transforms.iter().fold(plan, |plan, f| f(plan))
     }
 
     pub fn parquet(path: &str) -> Self {
         let ds = DataSource::Parquet(ParquetDataSource::new(path).unwrap());
         Self::new(LogicalPlan::Scan(Scan::new(path, ds, None)))
     }
+
+    /// Scans the columns at the given positions of the parquet file, which selects the right
+    /// column even if several columns share a name. Fails if a position is outside of the schema
+    /// of the file.
+    pub fn parquet_columns(path: &str, ordinals: Vec<usize>) -> Result<Self, Error> {
+        let ds = DataSource::Parquet(ParquetDataSource::new(path)?);
+        Ok(Self::new(LogicalPlan::Scan(
+            Scan::new(path, ds, None).with_ordinals(ordinals)?,
+        )))
+    }
+
+    /// Scans all parquet files matching the glob `pattern`, which need to have the same schema.
+    pub fn parquet_glob(pattern: &str) -> Self {
+        let ds = DataSource::ParquetFiles(Box::new(ParquetFilesDataSource::glob(pattern).unwrap()));
+        Self::new(LogicalPlan::Scan(Scan::new(pattern, ds, None)))
+    }
+
+    pub fn parquet_glob_with(pattern: &str, options: ParquetReadOptions) -> Self {
+        let ds = DataSource::ParquetFiles(Box::new(
+            ParquetFilesDataSource::glob_with(pattern, options).unwrap(),
+        ));
+        Self::new(LogicalPlan::Scan(Scan::new(pattern, ds, None)))
+    }
+
+    pub fn csv(path: &str) -> Self {
+        Self::csv_with(path, CsvReadOptions::default())
+    }
+
+    pub fn csv_with(path: &str, options: CsvReadOptions) -> Self {
+        let ds = DataSource::Csv(CsvDataSource::new(path, options).unwrap());
+        Self::new(LogicalPlan::Scan(Scan::new(path, ds, None)))
+    }
+
+    /// A constant table of literal rows, e.g. `vec![vec![lit_int(1), lit_string("a")]]`.
+    pub fn values(schema: Schema, rows: Vec<Vec<LogicalExpression>>) -> Self {
+        Self::new(LogicalPlan::Values(Values::new(schema, rows)))
+    }
+
+    pub fn memory(schema: Schema, chunks: Vec<Chunk<Arc<dyn Array>>>) -> Self {
+        let ds = DataSource::Memory(MemoryDataSource::new(schema, chunks));
+        Self::new(LogicalPlan::Scan(Scan::new("memory", ds, None)))
+    }
+
+    /// A memory table whose rows are sorted on the columns `sorted_by`, see
+    /// `MemoryDataSource::with_sorted_by`.
+    pub fn memory_sorted(
+        schema: Schema,
+        chunks: Vec<Chunk<Arc<dyn Array>>>,
+        sorted_by: Vec<&str>,
+    ) -> Self {
+        let ds = DataSource::Memory(
+            MemoryDataSource::new(schema, chunks)
+                .with_sorted_by(sorted_by.into_iter().map(|x| x.to_string()).collect()),
+        );
+        Self::new(LogicalPlan::Scan(Scan::new("memory", ds, None)))
+    }
 }
 
 impl DataFrameTrait for DataFrame {
     fn project(self, exprs: Vec<LogicalExpression>) -> Self {
-        Self::new(LogicalPlan::Projection(Projection::new(
-            self.logical_plan(),
-            exprs,
-        )))
+        self.map_plan(|plan| LogicalPlan::Projection(Projection::new(plan, exprs)))
     }
 
     fn filter(self, exprs: LogicalExpression) -> Self {
-        Self::new(LogicalPlan::Selection(Selection::new(
-            self.logical_plan(),
-            exprs,
-        )))
+        self.map_plan(|plan| LogicalPlan::Selection(Selection::new(plan, exprs)))
     }
 
     fn aggregate(
@@ -61,15 +192,56 @@ impl DataFrameTrait for DataFrame {
         group_by: Vec<LogicalExpression>,
         aggregate_expr: Vec<LogicalExpression>,
     ) -> Self {
-        Self::new(LogicalPlan::Aggregate(Aggregate::new(
-            self.logical_plan(),
-            group_by,
-            aggregate_expr,
-        )))
+        self.map_plan(|plan| LogicalPlan::Aggregate(Aggregate::new(plan, group_by, aggregate_expr)))
+    }
+
+    fn sample(self, fraction: f64, seed: u64) -> Self {
+        self.map_plan(|plan| LogicalPlan::Sample(Sample::new(plan, fraction, seed)))
+    }
+
+    fn limit(self, n: usize) -> Self {
+        self.map_plan(|plan| LogicalPlan::Limit(Limit::new(plan, n)))
+    }
+
+    fn join(mut self, right: Self, on: Vec<(&str, &str)>) -> Self {
+        // The transforms of the right side are applied after the ones of the left side.
+        self.transforms.extend(right.transforms);
+        self.map_plan(|plan| {
+            LogicalPlan::Join(Join::new(
+                plan,
+                right.plan,
+                on.into_iter()
+                    .map(|(left, right)| (left.to_string(), right.to_string()))
+                    .collect(),
+            ))
+        })
+    }
+
+    fn distinct_on(self, columns: Vec<&str>) -> Self {
+        self.map_plan(|plan| {
+            LogicalPlan::Distinct(Distinct::new(
+                plan,
+                columns.into_iter().map(|x| x.to_string()).collect(),
+            ))
+        })
+    }
+
+    fn unnest(self, column: &str) -> Self {
+        self.map_plan(|plan| LogicalPlan::Unnest(Unnest::new(plan, column.to_string())))
+    }
+
+    fn unnest_preserve_empty(self, column: &str) -> Self {
+        self.map_plan(|plan| {
+            LogicalPlan::Unnest(Unnest::new(plan, column.to_string()).with_preserve_empty(true))
+        })
+    }
+
+    fn window(self, exprs: Vec<LogicalExpression>) -> Self {
+        self.map_plan(|plan| LogicalPlan::Window(Window::new(plan, exprs)))
     }
 
-    fn schema(&self) -> &Schema {
-        self.plan.schema().unwrap()
+    fn schema(&self) -> Result<&Schema, Error> {
+        self.plan.schema()
     }
 
     fn logical_plan(self) -> LogicalPlan {
@@ -79,6 +251,124 @@ impl DataFrameTrait for DataFrame {
 
 impl DataFrame {
     pub fn execute(self) -> Result<Vec<Chunk<Arc<dyn Array>>>, Error> {
-        self.plan.to_physical_plan()?.execute()?.collect()
+        self.optimized_logical_plan()
+            .to_physical_plan()?
+            .execute()?
+            .collect()
+    }
+
+    /// Executes the plan as built, including transforms but without the built-in optimizations.
+    pub fn execute_unoptimized(self) -> Result<Vec<Chunk<Arc<dyn Array>>>, Error> {
+        self.transformed_plan(false)
+            .to_physical_plan()?
+            .execute()?
+            .collect()
+    }
+
+    /// Executes the query and returns the physical plan annotated with the rows produced by every
+    /// operator and the time spent in it. The time of an operator includes the time of its inputs.
+    pub fn explain_analyze(self) -> Result<String, Error> {
+        let (plan, metrics) = self
+            .optimized_logical_plan()
+            .to_physical_plan()?
+            .with_metrics();
+        plan.execute()?.try_for_each(|batch| batch.map(|_| ()))?;
+        Ok(metrics.to_string())
+    }
+
+    /// Returns the plan after optimization and the registered transforms, i.e. the plan that is
+    /// executed and that `output_schema` is derived from. `logical_plan` returns the plan as it
+    /// was built.
+    pub fn optimized_logical_plan(self) -> LogicalPlan {
+        self.transformed_plan(true)
+    }
+
+    /// Returns the schema of the optimized physical plan, i.e. the schema of the batches
+    /// produced by `execute`.
+    pub fn output_schema(self) -> Result<Schema, Error> {
+        Ok(self
+            .optimized_logical_plan()
+            .to_physical_plan()?
+            .schema()
+            .clone())
+    }
+
+    /// Renames the output columns by position. There has to be one name per column.
+    pub fn rename_columns(self, names: Vec<&str>) -> Result<DataFrame, Error> {
+        let fields = &self.plan.schema()?.fields;
+        if fields.len() != names.len() {
+            return Err(Error::ColumnCountMismatch(fields.len(), names.len()));
+        }
+        let exprs = fields
+            .iter()
+            .zip(names)
+            .map(|(field, name)| {
+                LogicalExpression::Column(Column::new(field.name.clone())).alias(name)
+            })
+            .collect();
+        Ok(self.project(exprs))
+    }
+
+    /// Executes the plan once and returns a DataFrame over the resulting batches, which can be
+    /// queried repeatedly without executing the plan again.
+    pub fn cache(self) -> Result<DataFrame, Error> {
+        let plan = self.optimized_logical_plan();
+        let schema = plan.schema()?.clone();
+        let chunks = plan
+            .to_physical_plan()?
+            .execute()?
+            .collect::<Result<_, _>>()?;
+        Ok(DataFrame::memory(schema, chunks))
+    }
+
+    /// Copies the DataFrame, see `LogicalPlan::try_clone`.
+    pub fn try_clone(&self) -> Result<DataFrame, Error> {
+        Ok(DataFrame {
+            plan: self.plan.try_clone()?,
+            transforms: self.transforms.clone(),
+        })
+    }
+
+    pub fn execute_stream(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        self.optimized_logical_plan().to_physical_plan()?.execute()
+    }
+
+    /// Streams the result and calls `f` with the cumulative row count after every batch.
+    pub fn execute_with_progress<F: FnMut(usize) + 'static>(
+        self,
+        mut f: F,
+    ) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut rows = 0;
+        Ok(Box::new(self.execute_stream()?.inspect(move |batch| {
+            if let Ok(chunk) = batch {
+                rows += chunk.len();
+                f(rows);
+            }
+        })))
+    }
+
+    pub fn write_parquet(self, path: &str) -> Result<(), Error> {
+        self.write_parquet_with(path, &WriteParquetOptions::default())
+    }
+
+    pub fn write_parquet_with(
+        self,
+        path: &str,
+        options: &WriteParquetOptions,
+    ) -> Result<(), Error> {
+        let plan = self.optimized_logical_plan();
+        let schema = plan.schema()?.clone();
+        let batches = plan.to_physical_plan()?.execute()?;
+        write_parquet(path, &schema, batches, options)
+    }
+
+    pub fn execute_with_config(
+        self,
+        config: &ExecutionConfig,
+    ) -> Result<Vec<Chunk<Arc<dyn Array>>>, Error> {
+        self.optimized_logical_plan()
+            .create_physical_plan(config)?
+            .execute()?
+            .collect()
     }
 }
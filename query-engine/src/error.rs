@@ -6,6 +6,8 @@ pub enum Error {
     ExceedingBoundsError(usize),
     #[error("Field `{0}` is not contained in logical plan.")]
     NoFieldInLogicalPlan(String),
+    #[error("Column `{0}` is not contained in the input.")]
+    ColumnNotFound(String),
     #[error("The expressions `{0}` and `{1}` have different sizes.")]
     DifferentSizes(String, String),
     #[error("Physical expression `{0}` is not supported.")]
@@ -20,6 +22,8 @@ pub enum Error {
     MissingChildren(String),
     #[error("Physical plan `{0}` is missing its input.")]
     MissingInputPhysicalPlan(String),
+    #[error("Physical plan `{0}` expects {1} input(s) but got {2}.")]
+    InputCountMismatch(String, usize, usize),
     #[error("The hashmap for aggregation is empty.")]
     EmptyHashmapForAggregate,
     #[error("Error wile downcasting Array.")]
@@ -28,8 +32,56 @@ pub enum Error {
     ScalarToArrayError(String),
     #[error("Expr doesn't evaluate to a boolean array, which is needed to filter.")]
     NoBooleanArrayForFilter,
+    #[error(
+        "Predicate `{0}` of type `{1}` doesn't evaluate to a boolean, which is needed to filter."
+    )]
+    NonBooleanPredicate(String, String),
+    #[error("Can't compare values of type `{0}` with values of type `{1}`.")]
+    TypeMismatch(String, String),
+    #[error("Table `{0}` is not registered in the execution context.")]
+    TableNotFound(String),
+    #[error("Aggregate `{0}` is not registered in the execution context.")]
+    AggregateNotFound(String),
+    #[error(
+        "Column `{0}` is not an aggregate expression. Plain columns belong in the group by list."
+    )]
+    NotAnAggregateExpression(String),
+    #[error("Function `{0}` is not registered in the execution context.")]
+    FunctionNotFound(String),
+    #[error("Function `{0}` returned values of type `{1}` instead of `{2}`.")]
+    ReturnTypeMismatch(String, String, String),
+    #[error("A projection needs at least one expression.")]
+    EmptyProjection,
+    #[error("The column name `{0}` is used by more than one output column.")]
+    DuplicateColumnName(String),
+    #[error("Column `{0}` of type `{1}` is not a list.")]
+    NotAList(String, String),
+    #[error("A repartition needs at least one partition.")]
+    ZeroPartitions,
+    #[error("A batch needs at least one row.")]
+    ZeroBatchSize,
+    #[error("Expected {0} column names but got {1}.")]
+    ColumnCountMismatch(usize, usize),
+    #[error("Wildcard `{0}` can only be used in a projection.")]
+    UnexpandedWildcard(String),
+    #[error("Couldn't cast `{0}` to {1}.")]
+    CastError(String, String),
+    #[error("Integer overflow while computing `{0}`.")]
+    IntegerOverflow(String),
+    #[error("Invalid values table: {0}.")]
+    InvalidValues(String),
+    #[error("Scanned batch doesn't match the schema of the scan: {0}.")]
+    SchemaMismatch(String),
+    #[error("File `{0}` doesn't have the schema of `{1}`.")]
+    IncompatibleFiles(String, String),
+    #[error("No files match `{0}`.")]
+    NoMatchingFiles(String),
     #[error("IoError: `{0}`.")]
     IoError(std::io::Error),
     #[error("IoError: `{0}`.")]
     ArrowError(arrow2::error::ArrowError),
+    #[error("CsvError: `{0}`.")]
+    CsvError(csv::Error),
+    #[error("GlobError: `{0}`.")]
+    GlobError(glob::PatternError),
 }
@@ -0,0 +1,120 @@
+use std::{collections::HashMap, sync::Arc};
+
+use arrow2::{array::Array, chunk::Chunk};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    columnar_value::ColumnarValue,
+    data_source::{DataSource, ParquetDataSource},
+    dataframe::DataFrame,
+    error::Error,
+    logical_plan::{LogicalPlan, Scan},
+    physical_plan::physical_expressions::{AggregateUdf, ScalarUdf},
+};
+
+/// How casts treat values that can't be converted to the target type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoercionMode {
+    /// Fail the query with an error.
+    #[default]
+    Strict,
+    /// Replace the value with null.
+    Lenient,
+}
+
+/// Options that influence how a logical plan is turned into a physical plan.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionConfig {
+    /// Maximum number of rows per batch produced by scans. `None` emits batches as read, zero
+    /// fails planning with [`Error::ZeroBatchSize`].
+    pub batch_size: Option<usize>,
+    /// If set, selections combine consecutive filtered batches until they hold at least
+    /// this many rows before emitting them.
+    pub target_batch_size: Option<usize>,
+    /// Aggregations grouped by a single string column group on dictionary codes of the
+    /// strings, which are only materialized again for the result.
+    pub dictionary_group_keys: bool,
+    /// Null keys match other null keys in joins. By default they don't match any row.
+    pub null_equals_null: bool,
+    /// How casts treat values that can't be converted, e.g. the string `"abc"` cast to Int32.
+    /// Strict, the default, fails the query with [`Error::CastError`], lenient turns the value
+    /// into null.
+    pub coercion_mode: CoercionMode,
+    /// User-defined aggregates by name.
+    pub aggregates: HashMap<String, Arc<dyn AggregateUdf>>,
+    /// User-defined scalar functions by name.
+    pub functions: HashMap<String, Arc<dyn ScalarUdf>>,
+}
+
+pub struct ExecutionContext {
+    tables: HashMap<String, DataSource>,
+    config: ExecutionConfig,
+}
+
+impl ExecutionContext {
+    pub fn new() -> Self {
+        Self::with_config(ExecutionConfig::default())
+    }
+
+    pub fn with_config(config: ExecutionConfig) -> Self {
+        ExecutionContext {
+            tables: HashMap::new(),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &ExecutionConfig {
+        &self.config
+    }
+
+    pub fn execute(&self, df: DataFrame) -> Result<Vec<Chunk<Arc<dyn Array>>>, Error> {
+        df.execute_with_config(&self.config)
+    }
+
+    pub fn register_parquet(&mut self, name: &str, path: &str) -> Result<(), Error> {
+        let ds = DataSource::Parquet(ParquetDataSource::new(path)?);
+        self.register_data_source(name, ds);
+        Ok(())
+    }
+
+    pub fn register_data_source(&mut self, name: &str, data_source: DataSource) {
+        self.tables.insert(name.to_string(), data_source);
+    }
+
+    /// Makes `udf` available to queries under its name, replacing an aggregate of the same name.
+    pub fn register_aggregate(&mut self, udf: Arc<dyn AggregateUdf>) {
+        self.config.aggregates.insert(udf.name().to_string(), udf);
+    }
+
+    /// Makes `udf` available to queries as `name`, replacing a function of the same name.
+    pub fn register_udf<F>(&mut self, name: &str, udf: F)
+    where
+        F: Fn(&[ColumnarValue]) -> Result<ColumnarValue, Error> + 'static,
+    {
+        self.config
+            .functions
+            .insert(name.to_string(), Arc::new(udf));
+    }
+
+    pub fn deregister(&mut self, name: &str) -> Option<DataSource> {
+        self.tables.remove(name)
+    }
+
+    pub fn table(&self, name: &str) -> Result<DataFrame, Error> {
+        let ds = self
+            .tables
+            .get(name)
+            .ok_or_else(|| Error::TableNotFound(name.to_string()))?
+            .try_clone()?;
+        let path = ds.path().to_string();
+        Ok(DataFrame::new(LogicalPlan::Scan(Scan::new(
+            &path, ds, None,
+        ))))
+    }
+}
+
+impl Default for ExecutionContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
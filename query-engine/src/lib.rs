@@ -1,7 +1,9 @@
 pub mod columnar_value;
+pub mod data_sink;
 pub mod data_source;
 pub mod dataframe;
 pub mod error;
+pub mod execution_context;
 pub mod logical_plan;
 pub mod physical_plan;
 pub mod prelude;
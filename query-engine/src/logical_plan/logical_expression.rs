@@ -5,18 +5,28 @@ use arrow2::{
     datatypes::{Field, Metadata},
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::error::Error;
 
 use super::LogicalPlan;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum LogicalExpression {
     Column(Column),
+    Wildcard(Wildcard),
+    Alias(Box<Alias>),
+    Cast(Box<Cast>),
     LiteralBool(LiteralBool),
     LiteralString(LiteralString),
     LiteralInteger(LiteralInteger),
+    LiteralLong(LiteralLong),
     LiteralFloat(LiteralFloat),
+    LiteralInterval(LiteralInterval),
+    LiteralNull(LiteralNull),
     Eq(Box<Eq>),
     Neq(Box<Neq>),
+    NullSafeEq(Box<NullSafeEq>),
     Gt(Box<Gt>),
     GtEq(Box<GtEq>),
     Lt(Box<Lt>),
@@ -28,23 +38,37 @@ pub enum LogicalExpression {
     Mul(Box<Mul>),
     Div(Box<Div>),
     Mod(Box<Mod>),
+    Year(Box<Year>),
+    Month(Box<Month>),
+    Day(Box<Day>),
+    ScalarUdf(Box<ScalarFunction>),
     Sum(Box<Sum>),
     Avg(Box<Avg>),
     Max(Box<Max>),
     Min(Box<Min>),
     Count(Box<Count>),
+    AggregateFunction(Box<AggregateFunction>),
+    CumSum(Box<CumSum>),
+    Rank(Box<Rank>),
 }
 
 impl LogicalExpression {
     pub fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
         match self {
             LogicalExpression::Column(col) => col.to_field(input),
+            LogicalExpression::Wildcard(wildcard) => wildcard.to_field(input),
+            LogicalExpression::Alias(alias) => alias.to_field(input),
+            LogicalExpression::Cast(cast) => cast.to_field(input),
             LogicalExpression::LiteralBool(bool) => bool.to_field(input),
             LogicalExpression::LiteralString(string) => string.to_field(input),
             LogicalExpression::LiteralInteger(int) => int.to_field(input),
+            LogicalExpression::LiteralLong(long) => long.to_field(input),
             LogicalExpression::LiteralFloat(float) => float.to_field(input),
+            LogicalExpression::LiteralInterval(interval) => interval.to_field(input),
+            LogicalExpression::LiteralNull(null) => null.to_field(input),
             LogicalExpression::Eq(eq) => eq.to_field(input),
             LogicalExpression::Neq(neq) => neq.to_field(input),
+            LogicalExpression::NullSafeEq(eq) => eq.to_field(input),
             LogicalExpression::Gt(gt) => gt.to_field(input),
             LogicalExpression::GtEq(gteq) => gteq.to_field(input),
             LogicalExpression::Lt(lt) => lt.to_field(input),
@@ -56,11 +80,18 @@ impl LogicalExpression {
             LogicalExpression::Mul(mul) => mul.to_field(input),
             LogicalExpression::Div(div) => div.to_field(input),
             LogicalExpression::Mod(modu) => modu.to_field(input),
+            LogicalExpression::Year(year) => year.to_field(input),
+            LogicalExpression::Month(month) => month.to_field(input),
+            LogicalExpression::Day(day) => day.to_field(input),
+            LogicalExpression::ScalarUdf(fun) => fun.to_field(input),
             LogicalExpression::Sum(sum) => sum.to_field(input),
             LogicalExpression::Avg(avg) => avg.to_field(input),
             LogicalExpression::Max(max) => max.to_field(input),
             LogicalExpression::Min(min) => min.to_field(input),
             LogicalExpression::Count(count) => count.to_field(input),
+            LogicalExpression::AggregateFunction(fun) => fun.to_field(input),
+            LogicalExpression::CumSum(cumsum) => cumsum.to_field(input),
+            LogicalExpression::Rank(rank) => rank.to_field(input),
         }
     }
 }
@@ -69,12 +100,19 @@ impl Display for LogicalExpression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             LogicalExpression::Column(col) => write!(f, "{}", col),
+            LogicalExpression::Wildcard(wildcard) => write!(f, "{}", wildcard),
+            LogicalExpression::Alias(alias) => write!(f, "{}", alias),
+            LogicalExpression::Cast(cast) => write!(f, "{}", cast),
             LogicalExpression::LiteralBool(bool) => write!(f, "{}", bool),
             LogicalExpression::LiteralString(string) => write!(f, "{}", string),
             LogicalExpression::LiteralInteger(int) => write!(f, "{}", int),
+            LogicalExpression::LiteralLong(long) => write!(f, "{}", long),
             LogicalExpression::LiteralFloat(float) => write!(f, "{}", float),
+            LogicalExpression::LiteralInterval(interval) => write!(f, "{}", interval),
+            LogicalExpression::LiteralNull(null) => write!(f, "{}", null),
             LogicalExpression::Eq(eq) => write!(f, "{}", eq),
             LogicalExpression::Neq(neq) => write!(f, "{}", neq),
+            LogicalExpression::NullSafeEq(eq) => write!(f, "{}", eq),
             LogicalExpression::Gt(gt) => write!(f, "{}", gt),
             LogicalExpression::GtEq(gteq) => write!(f, "{}", gteq),
             LogicalExpression::Lt(lt) => write!(f, "{}", lt),
@@ -86,16 +124,24 @@ impl Display for LogicalExpression {
             LogicalExpression::Mul(mul) => write!(f, "{}", mul),
             LogicalExpression::Div(div) => write!(f, "{}", div),
             LogicalExpression::Mod(modu) => write!(f, "{}", modu),
+            LogicalExpression::Year(year) => write!(f, "{}", year),
+            LogicalExpression::Month(month) => write!(f, "{}", month),
+            LogicalExpression::Day(day) => write!(f, "{}", day),
+            LogicalExpression::ScalarUdf(fun) => write!(f, "{}", fun),
             LogicalExpression::Sum(sum) => write!(f, "{}", sum),
             LogicalExpression::Avg(avg) => write!(f, "{}", avg),
             LogicalExpression::Max(max) => write!(f, "{}", max),
             LogicalExpression::Min(min) => write!(f, "{}", min),
             LogicalExpression::Count(count) => write!(f, "{}", count),
+            LogicalExpression::AggregateFunction(fun) => write!(f, "{}", fun),
+            LogicalExpression::CumSum(cumsum) => write!(f, "{}", cumsum),
+            LogicalExpression::Rank(rank) => write!(f, "{}", rank),
         }
     }
 }
 
 // Column expression
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Column {
     pub name: String,
 }
@@ -126,8 +172,123 @@ impl fmt::Display for Column {
     }
 }
 
+// Wildcard expression
+
+/// All columns of the input except the ones in `except`. Projections expand it into the
+/// individual columns.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Wildcard {
+    pub except: Vec<String>,
+}
+
+impl Wildcard {
+    pub fn new(except: Vec<String>) -> Self {
+        Wildcard { except }
+    }
+
+    pub(crate) fn expand(&self, input: &LogicalPlan) -> Result<Vec<LogicalExpression>, Error> {
+        Ok(self
+            .columns(input)?
+            .into_iter()
+            .map(|x| LogicalExpression::Column(Column::new(x)))
+            .collect())
+    }
+
+    /// Names of the columns of `input` the wildcard selects.
+    pub(crate) fn columns(&self, input: &LogicalPlan) -> Result<Vec<String>, Error> {
+        Ok(input
+            .schema()?
+            .fields
+            .iter()
+            .filter(|x| !self.except.contains(&x.name))
+            .map(|x| x.name.clone())
+            .collect())
+    }
+}
+
+impl Wildcard {
+    #[inline]
+    fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+        Err(Error::UnexpandedWildcard(self.to_string()))
+    }
+}
+
+impl fmt::Display for Wildcard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.except.is_empty() {
+            write!(f, "*")
+        } else {
+            write!(f, "* EXCEPT ({})", self.except.join(", "))
+        }
+    }
+}
+
+// Alias expression
+
+/// Renames the output field of the wrapped expression.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Alias {
+    pub name: String,
+    pub(crate) expr: LogicalExpression,
+}
+
+impl Alias {
+    pub fn new(expr: LogicalExpression, name: String) -> Self {
+        Alias { name, expr }
+    }
+}
+
+impl Alias {
+    #[inline]
+    fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
+        self.expr.to_field(input).map(|field| Field {
+            name: self.name.clone(),
+            ..field
+        })
+    }
+}
+
+impl fmt::Display for Alias {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} AS {}", self.expr, self.name)
+    }
+}
+
+// Cast expression
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Cast {
+    pub data_type: datatypes::DataType,
+    pub(crate) expr: LogicalExpression,
+}
+
+impl Cast {
+    pub fn new(expr: LogicalExpression, data_type: datatypes::DataType) -> Self {
+        Cast { data_type, expr }
+    }
+}
+
+impl Cast {
+    #[inline]
+    fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
+        // Values that can't be cast may become null, depending on the coercion mode.
+        self.expr.to_field(input).map(|field| Field {
+            data_type: self.data_type.clone(),
+            is_nullable: true,
+            ..field
+        })
+    }
+}
+
+impl fmt::Display for Cast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CAST({} AS {:?})", self.expr, self.data_type)
+    }
+}
+
 // LiteralString expression
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LiteralBool {
     pub value: bool,
 }
@@ -155,6 +316,7 @@ impl fmt::Display for LiteralBool {
         write!(f, "'{}'", self.value)
     }
 }
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LiteralString {
     pub value: String,
 }
@@ -168,8 +330,9 @@ impl LiteralString {
 impl LiteralString {
     #[inline]
     fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+        // Named like it is displayed, so that it can't be confused with a column of that name.
         Ok(Field {
-            name: self.value.clone(),
+            name: self.to_string(),
             data_type: datatypes::DataType::Utf8,
             is_nullable: false,
             metadata: Metadata::default(),
@@ -183,6 +346,7 @@ impl fmt::Display for LiteralString {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LiteralInteger {
     pub value: i32,
 }
@@ -211,6 +375,37 @@ impl fmt::Display for LiteralInteger {
     }
 }
 
+/// A 64 bit integer literal. It's compared with Int32 values as Int64.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LiteralLong {
+    pub value: i64,
+}
+
+impl LiteralLong {
+    pub fn new(value: i64) -> Self {
+        LiteralLong { value }
+    }
+}
+
+impl LiteralLong {
+    #[inline]
+    fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+        Ok(Field {
+            name: self.value.to_string(),
+            data_type: datatypes::DataType::Int64,
+            is_nullable: false,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl fmt::Display for LiteralLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}'", self.value)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LiteralFloat {
     pub value: f64,
 }
@@ -239,10 +434,89 @@ impl fmt::Display for LiteralFloat {
     }
 }
 
+/// A fixed length of time in seconds. Adding it to a timestamp or date column shifts the column
+/// while keeping its data type.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LiteralInterval {
+    pub seconds: i64,
+}
+
+impl LiteralInterval {
+    pub fn new(seconds: i64) -> Self {
+        LiteralInterval { seconds }
+    }
+}
+
+impl LiteralInterval {
+    #[inline]
+    fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+        Ok(Field {
+            name: self.to_string(),
+            data_type: datatypes::DataType::Duration(datatypes::TimeUnit::Second),
+            is_nullable: false,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl fmt::Display for LiteralInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{} seconds'", self.seconds)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LiteralNull {
+    pub data_type: datatypes::DataType,
+}
+
+impl LiteralNull {
+    pub fn new(data_type: datatypes::DataType) -> Self {
+        LiteralNull { data_type }
+    }
+}
+
+impl LiteralNull {
+    #[inline]
+    fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+        Ok(Field {
+            name: self.to_string(),
+            data_type: self.data_type.clone(),
+            is_nullable: true,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl fmt::Display for LiteralNull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NULL")
+    }
+}
+
 // BinaryExpression expression
 
+/// Booleans can only be compared with booleans. Other types are coerced when the comparison
+/// is evaluated.
+fn check_comparable(
+    left: &LogicalExpression,
+    right: &LogicalExpression,
+    input: &LogicalPlan,
+) -> Result<(), Error> {
+    let left = left.to_field(input)?.data_type;
+    let right = right.to_field(input)?.data_type;
+    match (&left, &right) {
+        (datatypes::DataType::Boolean, datatypes::DataType::Boolean) => Ok(()),
+        (datatypes::DataType::Boolean, _) | (_, datatypes::DataType::Boolean) => Err(
+            Error::TypeMismatch(format!("{:?}", left), format!("{:?}", right)),
+        ),
+        _ => Ok(()),
+    }
+}
+
 macro_rules! booleanBinaryExpression {
-    ($i: ident, $name: expr, $op: expr) => {
+    ($i: ident, $name: expr, $op: expr, $comparison: expr) => {
+        #[derive(Clone, Serialize, Deserialize)]
         pub struct $i {
             name: String,
             op: String,
@@ -255,15 +529,18 @@ macro_rules! booleanBinaryExpression {
                 $i {
                     name: $name,
                     op: $op,
-                    left: left,
-                    right: right,
+                    left,
+                    right,
                 }
             }
         }
 
         impl $i {
             #[inline]
-            fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+            fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
+                if $comparison {
+                    check_comparable(&self.left, &self.right, input)?;
+                }
                 Ok(Field {
                     name: self.name.clone(),
                     data_type: datatypes::DataType::Boolean,
@@ -281,22 +558,44 @@ macro_rules! booleanBinaryExpression {
     };
 }
 
-booleanBinaryExpression!(Eq, "eq".to_string(), "==".to_string());
-booleanBinaryExpression!(Neq, "neq".to_string(), "!=".to_string());
-booleanBinaryExpression!(Gt, "gt".to_string(), ">".to_string());
-booleanBinaryExpression!(GtEq, "gteq".to_string(), ">=".to_string());
-booleanBinaryExpression!(Lt, "lt".to_string(), "<".to_string());
-booleanBinaryExpression!(LtEq, "lteq".to_string(), "<=".to_string());
+booleanBinaryExpression!(Eq, "eq".to_string(), "==".to_string(), true);
+booleanBinaryExpression!(Neq, "neq".to_string(), "!=".to_string(), true);
+// Equality where two nulls are equal and a null is unequal to any value.
+booleanBinaryExpression!(
+    NullSafeEq,
+    "null_safe_eq".to_string(),
+    "<=>".to_string(),
+    true
+);
+booleanBinaryExpression!(Gt, "gt".to_string(), ">".to_string(), true);
+booleanBinaryExpression!(GtEq, "gteq".to_string(), ">=".to_string(), true);
+booleanBinaryExpression!(Lt, "lt".to_string(), "<".to_string(), true);
+booleanBinaryExpression!(LtEq, "lteq".to_string(), "<=".to_string(), true);
 
 // BooleanExpressions
 
-booleanBinaryExpression!(And, "and".to_string(), "&&".to_string());
-booleanBinaryExpression!(Or, "or".to_string(), "||".to_string());
+booleanBinaryExpression!(And, "and".to_string(), "&&".to_string(), false);
+booleanBinaryExpression!(Or, "or".to_string(), "||".to_string(), false);
 
 // MathExpressions
 
+/// Type of an arithmetic expression. Numbers of different types are promoted to their common
+/// type, otherwise the result has the type of the left operand, e.g. a date plus an interval.
+fn arithmetic_type(left: datatypes::DataType, right: datatypes::DataType) -> datatypes::DataType {
+    use datatypes::DataType::{Float32, Float64, Int32, Int64};
+    match (left, right) {
+        (Float32 | Float64, Int32 | Int64)
+        | (Int32 | Int64, Float32 | Float64)
+        | (Float32, Float64)
+        | (Float64, Float32) => Float64,
+        (Int32, Int64) | (Int64, Int32) => Int64,
+        (left, _) => left,
+    }
+}
+
 macro_rules! mathExpression {
     ($i: ident, $name: expr, $op: expr) => {
+        #[derive(Clone, Serialize, Deserialize)]
         pub struct $i {
             name: String,
             op: String,
@@ -320,7 +619,10 @@ macro_rules! mathExpression {
             fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
                 Ok(Field {
                     name: self.name.clone(),
-                    data_type: self.left.to_field(input)?.data_type,
+                    data_type: arithmetic_type(
+                        self.left.to_field(input)?.data_type,
+                        self.right.to_field(input)?.data_type,
+                    ),
                     is_nullable: false,
                     metadata: Metadata::default(),
                 })
@@ -341,12 +643,104 @@ mathExpression!(Mul, "mul".to_string(), "*".to_string());
 mathExpression!(Div, "div".to_string(), "/".to_string());
 mathExpression!(Mod, "mod".to_string(), "%".to_string());
 
+// TemporalExpressions
+
+macro_rules! temporalExpression {
+    ($i: ident, $name: expr) => {
+        #[derive(Clone, Serialize, Deserialize)]
+        pub struct $i {
+            name: String,
+            pub(crate) expr: LogicalExpression,
+        }
+
+        impl $i {
+            pub fn new(expr: LogicalExpression) -> Self {
+                $i { name: $name, expr }
+            }
+        }
+
+        impl $i {
+            #[inline]
+            fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+                Ok(Field {
+                    name: self.name.clone(),
+                    data_type: datatypes::DataType::Int32,
+                    is_nullable: true,
+                    metadata: Metadata::default(),
+                })
+            }
+        }
+
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} ({})", self.name, self.expr)
+            }
+        }
+    };
+}
+
+temporalExpression!(Year, "year".to_string());
+temporalExpression!(Month, "month".to_string());
+temporalExpression!(Day, "day".to_string());
+
+/// A user-defined scalar function. The function is looked up by `name` in the functions of the
+/// execution config. Its values have the declared `data_type`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScalarFunction {
+    name: String,
+    data_type: datatypes::DataType,
+    pub(crate) args: Vec<LogicalExpression>,
+}
+
+impl ScalarFunction {
+    pub fn new(name: String, data_type: datatypes::DataType, args: Vec<LogicalExpression>) -> Self {
+        ScalarFunction {
+            name,
+            data_type,
+            args,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn data_type(&self) -> &datatypes::DataType {
+        &self.data_type
+    }
+}
+
+impl ScalarFunction {
+    #[inline]
+    fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+        Ok(Field {
+            name: self.name.clone(),
+            data_type: self.data_type.clone(),
+            is_nullable: true,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl fmt::Display for ScalarFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{} ({})", self.name, args)
+    }
+}
+
 // AggregateExpressions
 
 pub trait LogicalAggregateExpression {}
 
 macro_rules! aggregateExpression {
     ($i: ident, $name: expr) => {
+        #[derive(Clone, Serialize, Deserialize)]
         pub struct $i {
             name: String,
             pub(crate) expr: LogicalExpression,
@@ -383,13 +777,58 @@ macro_rules! aggregateExpression {
     };
 }
 
-aggregateExpression!(Sum, "sum".to_string());
-aggregateExpression!(Avg, "avg".to_string());
 aggregateExpression!(Max, "max".to_string());
 aggregateExpression!(Min, "min".to_string());
 
+// Sum Expression
+
+/// Type of the sum of values of type `data_type`.
+fn sum_type(data_type: datatypes::DataType) -> datatypes::DataType {
+    match data_type {
+        datatypes::DataType::Int32 | datatypes::DataType::Int64 => datatypes::DataType::Int64,
+        datatypes::DataType::Float32 => datatypes::DataType::Float64,
+        data_type => data_type,
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sum {
+    name: String,
+    pub(crate) expr: LogicalExpression,
+}
+
+impl Sum {
+    pub fn new(expr: LogicalExpression) -> Self {
+        Sum {
+            name: "sum".to_string(),
+            expr: expr,
+        }
+    }
+}
+
+impl Sum {
+    /// Integer columns are summed into an Int64 so that large groups don't overflow.
+    #[inline]
+    pub(crate) fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
+        let data_type = sum_type(self.expr.to_field(input)?.data_type);
+        Ok(Field {
+            name: self.name.clone(),
+            data_type,
+            is_nullable: true,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl fmt::Display for Sum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.expr)
+    }
+}
+
 // Count Expression
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Count {
     name: String,
     pub(crate) expr: LogicalExpression,
@@ -406,10 +845,10 @@ impl Count {
 
 impl Count {
     #[inline]
-    fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
+    fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
         Ok(Field {
             name: self.name.clone(),
-            data_type: self.expr.to_field(input)?.data_type,
+            data_type: datatypes::DataType::Int64,
             is_nullable: false,
             metadata: Metadata::default(),
         })
@@ -422,11 +861,166 @@ impl fmt::Display for Count {
     }
 }
 
+// Avg Expression
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Avg {
+    name: String,
+    pub(crate) expr: LogicalExpression,
+}
+
+impl Avg {
+    pub fn new(expr: LogicalExpression) -> Self {
+        Avg {
+            name: "avg".to_string(),
+            expr: expr,
+        }
+    }
+}
+
+impl Avg {
+    #[inline]
+    fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+        Ok(Field {
+            name: self.name.clone(),
+            data_type: datatypes::DataType::Float64,
+            is_nullable: true,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl fmt::Display for Avg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.expr)
+    }
+}
+
+// AggregateFunction Expression
+
+/// A user-defined aggregate. The physical aggregate is looked up by `name` in the aggregates of
+/// the execution config.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AggregateFunction {
+    name: String,
+    data_type: datatypes::DataType,
+    pub(crate) expr: LogicalExpression,
+}
+
+impl AggregateFunction {
+    pub fn new(name: String, data_type: datatypes::DataType, expr: LogicalExpression) -> Self {
+        AggregateFunction {
+            name,
+            data_type,
+            expr,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl AggregateFunction {
+    #[inline]
+    fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+        Ok(Field {
+            name: self.name.clone(),
+            data_type: self.data_type.clone(),
+            is_nullable: true,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.expr)
+    }
+}
+
+// Window Expressions
+
+/// Running total of the values in input order. Nulls don't change the total.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CumSum {
+    name: String,
+    pub(crate) expr: LogicalExpression,
+}
+
+impl CumSum {
+    pub fn new(expr: LogicalExpression) -> Self {
+        CumSum {
+            name: "cumsum".to_string(),
+            expr,
+        }
+    }
+}
+
+impl CumSum {
+    #[inline]
+    pub(crate) fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
+        Ok(Field {
+            name: self.name.clone(),
+            data_type: sum_type(self.expr.to_field(input)?.data_type),
+            is_nullable: false,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl fmt::Display for CumSum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.expr)
+    }
+}
+
+/// Rank of every row by the sort key `expr`, where the input has to be sorted by the key. Rows
+/// with equal keys get the same rank. After ties `rank` skips ranks, `dense_rank` doesn't.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rank {
+    name: String,
+    pub(crate) dense: bool,
+    pub(crate) expr: LogicalExpression,
+}
+
+impl Rank {
+    pub fn new(expr: LogicalExpression, dense: bool) -> Self {
+        Rank {
+            name: if dense { "dense_rank" } else { "rank" }.to_string(),
+            dense,
+            expr,
+        }
+    }
+}
+
+impl Rank {
+    #[inline]
+    fn to_field(&self, _input: &LogicalPlan) -> Result<Field, Error> {
+        Ok(Field {
+            name: self.name.clone(),
+            data_type: datatypes::DataType::Int64,
+            is_nullable: false,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.expr)
+    }
+}
+
 pub trait LogicalExpressionMethods {
     fn eq(self, other: LogicalExpression) -> LogicalExpression
     where
         Self: Sized;
     fn neq(self, other: LogicalExpression) -> LogicalExpression
+    where
+        Self: Sized;
+    /// Equality that is true for two nulls and false if only one side is null, `self <=> other`.
+    fn null_safe_eq(self, other: LogicalExpression) -> LogicalExpression
     where
         Self: Sized;
     fn gt(self, other: LogicalExpression) -> LogicalExpression
@@ -447,9 +1041,43 @@ pub trait LogicalExpressionMethods {
     fn or(self, other: LogicalExpression) -> LogicalExpression
     where
         Self: Sized;
+    fn add(self, other: LogicalExpression) -> LogicalExpression
+    where
+        Self: Sized;
+    fn sub(self, other: LogicalExpression) -> LogicalExpression
+    where
+        Self: Sized;
+    fn mul(self, other: LogicalExpression) -> LogicalExpression
+    where
+        Self: Sized;
+    fn div(self, other: LogicalExpression) -> LogicalExpression
+    where
+        Self: Sized;
+    /// Remainder of the division, `self % other`.
+    fn modu(self, other: LogicalExpression) -> LogicalExpression
+    where
+        Self: Sized;
+    /// Inclusive range check, equivalent to `self >= low && self <= high`.
+    fn between(self, low: LogicalExpression, high: LogicalExpression) -> LogicalExpression
+    where
+        Self: Sized;
+    /// Membership check, equivalent to `self == list[0] || self == list[1] || ...`. An empty
+    /// list is false.
+    #[allow(clippy::wrong_self_convention)]
+    fn is_in(self, list: Vec<LogicalExpression>) -> LogicalExpression
+    where
+        Self: Sized;
+    /// Names the output field of the expression.
+    fn alias(self, name: &str) -> LogicalExpression
+    where
+        Self: Sized;
+    /// Converts the values of the expression to `data_type`.
+    fn cast(self, data_type: datatypes::DataType) -> LogicalExpression
+    where
+        Self: Sized;
 }
 
-macro_rules! booleanMethod {
+macro_rules! binaryMethod {
     ($name: ident, $t: ident) => {
         fn $name(self, other: LogicalExpression) -> LogicalExpression
         where
@@ -461,12 +1089,66 @@ macro_rules! booleanMethod {
 }
 
 impl LogicalExpressionMethods for LogicalExpression {
-    booleanMethod!(eq, Eq);
-    booleanMethod!(neq, Neq);
-    booleanMethod!(gt, Gt);
-    booleanMethod!(gteq, GtEq);
-    booleanMethod!(lt, Lt);
-    booleanMethod!(lteq, LtEq);
-    booleanMethod!(and, And);
-    booleanMethod!(or, Or);
+    binaryMethod!(eq, Eq);
+    binaryMethod!(neq, Neq);
+    binaryMethod!(null_safe_eq, NullSafeEq);
+    binaryMethod!(gt, Gt);
+    binaryMethod!(gteq, GtEq);
+    binaryMethod!(lt, Lt);
+    binaryMethod!(lteq, LtEq);
+    binaryMethod!(and, And);
+    binaryMethod!(or, Or);
+    binaryMethod!(add, Add);
+    binaryMethod!(sub, Sub);
+    binaryMethod!(mul, Mul);
+    binaryMethod!(div, Div);
+    binaryMethod!(modu, Mod);
+
+    fn between(self, low: LogicalExpression, high: LogicalExpression) -> LogicalExpression
+    where
+        Self: Sized,
+    {
+        self.clone().gteq(low).and(self.lteq(high))
+    }
+
+    fn is_in(self, list: Vec<LogicalExpression>) -> LogicalExpression
+    where
+        Self: Sized,
+    {
+        list.into_iter()
+            .map(|x| self.clone().eq(x))
+            .reduce(|acc, x| acc.or(x))
+            .unwrap_or_else(|| LogicalExpression::LiteralBool(LiteralBool::new(false)))
+    }
+
+    fn alias(self, name: &str) -> LogicalExpression
+    where
+        Self: Sized,
+    {
+        LogicalExpression::Alias(Box::new(Alias::new(self, name.to_string())))
+    }
+
+    fn cast(self, data_type: datatypes::DataType) -> LogicalExpression
+    where
+        Self: Sized,
+    {
+        LogicalExpression::Cast(Box::new(Cast::new(self, data_type)))
+    }
+}
+
+macro_rules! binaryOperator {
+    ($trait: ident, $name: ident, $t: ident) => {
+        impl std::ops::$trait for LogicalExpression {
+            type Output = LogicalExpression;
+
+            fn $name(self, other: LogicalExpression) -> LogicalExpression {
+                LogicalExpression::$t(Box::new($t::new(self, other)))
+            }
+        }
+    };
 }
+
+binaryOperator!(Add, add, Add);
+binaryOperator!(Sub, sub, Sub);
+binaryOperator!(Mul, mul, Mul);
+binaryOperator!(Div, div, Div);
@@ -1,18 +1,27 @@
 use crate::data_source::DataSource;
 use crate::error::Error;
-use arrow2::datatypes::{Field, Schema};
-use std::fmt;
+use arrow2::datatypes::{DataType, Field, Schema};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt};
 
-use self::logical_expression::LogicalExpression;
+use self::{logical_expression::LogicalExpression, optimizer::PrunablePredicate};
 
 pub mod logical_expression;
 pub mod optimizer;
 
+#[derive(Serialize, Deserialize)]
 pub enum LogicalPlan {
     Scan(Scan),
     Projection(Projection),
     Selection(Selection),
     Aggregate(Aggregate),
+    Sample(Sample),
+    Values(Values),
+    Join(Join),
+    Distinct(Distinct),
+    Window(Window),
+    Limit(Limit),
+    Unnest(Unnest),
 }
 
 impl LogicalPlan {
@@ -22,16 +31,191 @@ impl LogicalPlan {
             LogicalPlan::Projection(proj) => proj.schema(),
             LogicalPlan::Selection(sel) => sel.schema(),
             LogicalPlan::Aggregate(agg) => agg.schema(),
+            LogicalPlan::Sample(sample) => sample.schema(),
+            LogicalPlan::Values(values) => values.schema(),
+            LogicalPlan::Join(join) => join.schema(),
+            LogicalPlan::Distinct(distinct) => distinct.schema(),
+            LogicalPlan::Window(window) => window.schema(),
+            LogicalPlan::Limit(limit) => limit.schema(),
+            LogicalPlan::Unnest(unnest) => unnest.schema(),
         }
     }
+    /// Estimated number of rows produced by the plan, based on the row counts of the scanned
+    /// sources. Selections and aggregates are assumed to keep every row, joins to produce as
+    /// many rows as their larger input.
+    pub fn estimated_rows(&self) -> Option<usize> {
+        match self {
+            LogicalPlan::Scan(scan) => scan.data_source.num_rows(),
+            LogicalPlan::Projection(proj) => proj.children[0].estimated_rows(),
+            LogicalPlan::Selection(sel) => sel.children[0].estimated_rows(),
+            LogicalPlan::Aggregate(agg) => agg.children[0].estimated_rows(),
+            LogicalPlan::Sample(sample) => sample.children[0]
+                .estimated_rows()
+                .map(|x| (x as f64 * sample.fraction).ceil() as usize),
+            LogicalPlan::Values(values) => Some(values.rows.len()),
+            LogicalPlan::Join(join) => {
+                let left = join.children[0].estimated_rows()?;
+                let right = join.children[1].estimated_rows()?;
+                Some(left.max(right))
+            }
+            LogicalPlan::Distinct(distinct) => distinct.children[0].estimated_rows(),
+            LogicalPlan::Window(window) => window.children[0].estimated_rows(),
+            LogicalPlan::Limit(limit) => limit.children[0].estimated_rows().map(|x| x.min(limit.n)),
+            // The number of rows depends on the lengths of the lists.
+            LogicalPlan::Unnest(_) => None,
+        }
+    }
+    /// Columns the output of the plan is known to be sorted on, the most significant first. Only
+    /// the order of the scanned source is tracked, operators that keep the order of their input
+    /// pass it on.
+    pub fn sorted_by(&self) -> Vec<String> {
+        match self {
+            LogicalPlan::Scan(scan) => scan
+                .data_source
+                .sorted_by()
+                .iter()
+                .take_while(|x| scan.schema.fields.iter().any(|field| &field.name == *x))
+                .cloned()
+                .collect(),
+            LogicalPlan::Projection(proj) => {
+                let exprs =
+                    expand_wildcards(proj.exprs.clone(), &proj.children[0]).unwrap_or_default();
+                proj.children[0]
+                    .sorted_by()
+                    .iter()
+                    .map_while(|name| {
+                        exprs.iter().find_map(|expr| match expr {
+                            LogicalExpression::Column(col) if &col.name == name => {
+                                Some(col.name.clone())
+                            }
+                            LogicalExpression::Alias(alias) => match &alias.expr {
+                                LogicalExpression::Column(col) if &col.name == name => {
+                                    Some(alias.name.clone())
+                                }
+                                _ => None,
+                            },
+                            _ => None,
+                        })
+                    })
+                    .collect()
+            }
+            LogicalPlan::Selection(sel) => sel.children[0].sorted_by(),
+            LogicalPlan::Sample(sample) => sample.children[0].sorted_by(),
+            LogicalPlan::Distinct(distinct) => distinct.children[0].sorted_by(),
+            LogicalPlan::Window(window) => window.children[0].sorted_by(),
+            LogicalPlan::Limit(limit) => limit.children[0].sorted_by(),
+            // Rows are repeated in input order, only the unnested column loses its order.
+            LogicalPlan::Unnest(unnest) => unnest.children[0]
+                .sorted_by()
+                .into_iter()
+                .take_while(|x| x != &unnest.column)
+                .collect(),
+            LogicalPlan::Aggregate(_) | LogicalPlan::Values(_) | LogicalPlan::Join(_) => Vec::new(),
+        }
+    }
+    /// Copies the plan. Data sources are opened again, see `DataSource::try_clone`.
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        let children = |children: &[LogicalPlan]| {
+            children
+                .iter()
+                .map(|x| x.try_clone())
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match self {
+            LogicalPlan::Scan(scan) => LogicalPlan::Scan(Scan {
+                path: scan.path.clone(),
+                data_source: scan.data_source.try_clone()?,
+                projection: scan.projection.clone(),
+                ordinals: scan.ordinals.clone(),
+                filters: scan.filters.clone(),
+                limit: scan.limit,
+                schema: scan.schema.clone(),
+            }),
+            LogicalPlan::Projection(proj) => LogicalPlan::Projection(Projection {
+                exprs: proj.exprs.clone(),
+                children: children(&proj.children)?,
+                schema: proj.schema.clone(),
+            }),
+            LogicalPlan::Selection(sel) => LogicalPlan::Selection(Selection {
+                expr: sel.expr.clone(),
+                children: children(&sel.children)?,
+                schema: sel.schema.clone(),
+            }),
+            LogicalPlan::Aggregate(agg) => LogicalPlan::Aggregate(Aggregate {
+                group_exprs: agg.group_exprs.clone(),
+                aggregate_exprs: agg.aggregate_exprs.clone(),
+                children: children(&agg.children)?,
+                schema: agg.schema.clone(),
+            }),
+            LogicalPlan::Sample(sample) => LogicalPlan::Sample(Sample {
+                fraction: sample.fraction,
+                seed: sample.seed,
+                children: children(&sample.children)?,
+                schema: sample.schema.clone(),
+            }),
+            LogicalPlan::Values(values) => LogicalPlan::Values(Values {
+                rows: values.rows.clone(),
+                schema: values.schema.clone(),
+            }),
+            LogicalPlan::Join(join) => LogicalPlan::Join(Join {
+                on: join.on.clone(),
+                children: children(&join.children)?,
+                schema: join.schema.clone(),
+            }),
+            LogicalPlan::Distinct(distinct) => LogicalPlan::Distinct(Distinct {
+                on: distinct.on.clone(),
+                children: children(&distinct.children)?,
+                schema: distinct.schema.clone(),
+            }),
+            LogicalPlan::Window(window) => LogicalPlan::Window(Window {
+                exprs: window.exprs.clone(),
+                children: children(&window.children)?,
+                schema: window.schema.clone(),
+            }),
+            LogicalPlan::Limit(limit) => LogicalPlan::Limit(Limit {
+                n: limit.n,
+                children: children(&limit.children)?,
+                schema: limit.schema.clone(),
+            }),
+            LogicalPlan::Unnest(unnest) => LogicalPlan::Unnest(Unnest {
+                column: unnest.column.clone(),
+                preserve_empty: unnest.preserve_empty,
+                children: children(&unnest.children)?,
+                schema: unnest.schema.clone(),
+            }),
+        })
+    }
     fn children(&self) -> Option<&[LogicalPlan]> {
         match self {
             LogicalPlan::Scan(scan) => scan.children(),
             LogicalPlan::Projection(proj) => proj.children(),
             LogicalPlan::Selection(sel) => sel.children(),
             LogicalPlan::Aggregate(agg) => agg.children(),
+            LogicalPlan::Sample(sample) => sample.children(),
+            LogicalPlan::Values(values) => values.children(),
+            LogicalPlan::Join(join) => join.children(),
+            LogicalPlan::Distinct(distinct) => distinct.children(),
+            LogicalPlan::Window(window) => window.children(),
+            LogicalPlan::Limit(limit) => limit.children(),
+            LogicalPlan::Unnest(unnest) => unnest.children(),
         }
     }
+
+    /// Number of nodes of the plan, including the root.
+    pub fn node_count(&self) -> usize {
+        1 + self
+            .children()
+            .map(|children| children.iter().map(|x| x.node_count()).sum())
+            .unwrap_or(0)
+    }
+
+    /// Number of nodes on the longest path from the root to a leaf.
+    pub fn depth(&self) -> usize {
+        1 + self
+            .children()
+            .and_then(|children| children.iter().map(|x| x.depth()).max())
+            .unwrap_or(0)
+    }
 }
 
 impl fmt::Display for LogicalPlan {
@@ -41,6 +225,13 @@ impl fmt::Display for LogicalPlan {
             LogicalPlan::Projection(proj) => write!(f, "{}", proj),
             LogicalPlan::Selection(sel) => write!(f, "{}", sel),
             LogicalPlan::Aggregate(agg) => write!(f, "{}", agg),
+            LogicalPlan::Sample(sample) => write!(f, "{}", sample),
+            LogicalPlan::Values(values) => write!(f, "{}", values),
+            LogicalPlan::Join(join) => write!(f, "{}", join),
+            LogicalPlan::Distinct(distinct) => write!(f, "{}", distinct),
+            LogicalPlan::Window(window) => write!(f, "{}", window),
+            LogicalPlan::Limit(limit) => write!(f, "{}", limit),
+            LogicalPlan::Unnest(unnest) => write!(f, "{}", unnest),
         }
     }
 }
@@ -59,10 +250,14 @@ pub fn format_logical_plan(plan: &LogicalPlan, indent: usize) -> String {
 
 // Scan logical plan
 
+#[derive(Serialize, Deserialize)]
 pub struct Scan {
     pub(crate) path: String,
     pub(crate) data_source: DataSource,
     pub(crate) projection: Option<Vec<String>>,
+    pub(crate) ordinals: Option<Vec<usize>>,
+    pub(crate) filters: Vec<PrunablePredicate>,
+    pub(crate) limit: Option<usize>,
     pub(crate) schema: Schema,
 }
 
@@ -73,9 +268,46 @@ impl Scan {
             schema: Self::derive_schema(&data_source, &projection),
             data_source: data_source,
             projection: projection,
+            ordinals: None,
+            filters: Vec::new(),
+            limit: None,
         }
     }
 
+    /// Projects the columns at the given positions of the data source schema instead of
+    /// projecting by name. This is unambiguous even if several columns share a name. Fails if a
+    /// position is outside of the data source schema.
+    pub fn with_ordinals(mut self, mut ordinals: Vec<usize>) -> Result<Self, Error> {
+        ordinals.sort_unstable();
+        ordinals.dedup();
+        let fields = self.data_source.schema().fields;
+        self.schema = ordinals
+            .iter()
+            .map(|i| {
+                fields
+                    .get(*i)
+                    .cloned()
+                    .ok_or(Error::ExceedingBoundsError(*i))
+            })
+            .collect::<Result<Vec<Field>, Error>>()?
+            .into();
+        self.projection = None;
+        self.ordinals = Some(ordinals);
+        Ok(self)
+    }
+
+    /// Predicates that the data source applies to the rows while reading them.
+    pub fn with_filters(mut self, filters: Vec<PrunablePredicate>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Number of rows after which the data source may stop reading.
+    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
     fn derive_schema(data_source: &DataSource, projection: &Option<Vec<String>>) -> Schema {
         match projection {
             Some(pro) => data_source
@@ -93,8 +325,17 @@ impl Scan {
 
 impl fmt::Display for Scan {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.projection {
-            Some(proj) => write!(
+        match (&self.ordinals, &self.projection) {
+            (Some(ordinals), _) => write!(
+                f,
+                "Scan: {}; ordinals={}",
+                self.path,
+                ordinals
+                    .iter()
+                    .map(|x| format!("{}, ", x))
+                    .collect::<String>()
+            ),
+            (None, Some(proj)) => write!(
                 f,
                 "Scan: {}; projection={}",
                 self.path,
@@ -103,8 +344,22 @@ impl fmt::Display for Scan {
                     x
                 }))
             ),
-            None => write!(f, "Scan: {}; projection=None", self.path),
+            (None, None) => write!(f, "Scan: {}; projection=None", self.path),
+        }?;
+        if !self.filters.is_empty() {
+            write!(
+                f,
+                "; filters={}",
+                self.filters
+                    .iter()
+                    .map(|filter| format!("{}, ", filter))
+                    .collect::<String>()
+            )?;
         }
+        if let Some(limit) = self.limit {
+            write!(f, "; limit={}", limit)?;
+        }
+        Ok(())
     }
 }
 
@@ -120,31 +375,81 @@ impl Scan {
 }
 
 // Projection
+#[derive(Serialize, Deserialize)]
 pub struct Projection {
     pub(crate) exprs: Vec<LogicalExpression>,
     pub(crate) children: Vec<LogicalPlan>,
-    pub(crate) schema: Schema,
+    pub(crate) schema: Option<Schema>,
 }
 
 impl Projection {
+    /// Wildcards are kept in the expressions and only expanded to the columns of the input when
+    /// the schema is derived and the projection is planned. Projection push down can therefore
+    /// narrow the columns they select. Building a plan can't fail, an empty projection or
+    /// duplicate column names are reported by `schema` instead.
     pub fn new(input: LogicalPlan, exprs: Vec<LogicalExpression>) -> Self {
         Projection {
-            schema: Self::derive_schema(&exprs, &input),
+            schema: Self::derive_schema(&exprs, &input).ok(),
             exprs: exprs,
             children: vec![input],
         }
     }
 
-    fn derive_schema(exprs: &Vec<LogicalExpression>, input: &LogicalPlan) -> Schema {
-        exprs
+    /// Fails for a projection without any column or with several columns of the same name.
+    fn derive_schema(exprs: &[LogicalExpression], input: &LogicalPlan) -> Result<Schema, Error> {
+        let schema: Schema = expand_wildcards(exprs.to_vec(), input)?
             .iter()
             .map(|expr| expr.to_field(input))
-            .collect::<Result<Vec<Field>, Error>>()
-            .map(|x| x.into())
-            .unwrap()
+            .collect::<Result<Vec<Field>, Error>>()?
+            .into();
+        if schema.fields.is_empty() {
+            return Err(Error::EmptyProjection);
+        }
+        check_unique_names(&schema)?;
+        Ok(schema)
     }
 }
 
+/// Returns the schema derived when a plan node was built. Deriving it fails for invalid plans,
+/// e.g. for a column that isn't in the input. Building a plan can't fail though, so the node
+/// keeps no schema then and `derive` is called again to report the error.
+fn derived_schema(
+    schema: &Option<Schema>,
+    derive: impl FnOnce() -> Result<Schema, Error>,
+) -> Result<&Schema, Error> {
+    match schema {
+        Some(schema) => Ok(schema),
+        None => Err(derive().expect_err("The schema couldn't be derived when the node was built.")),
+    }
+}
+
+/// Checks that the output columns of a plan node have distinct names, so that they can be
+/// referenced by name.
+fn check_unique_names(schema: &Schema) -> Result<(), Error> {
+    let mut names = HashSet::new();
+    match schema.fields.iter().find(|x| !names.insert(&x.name)) {
+        Some(field) => Err(Error::DuplicateColumnName(field.name.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Replaces every wildcard by the columns of `input` it selects.
+pub(crate) fn expand_wildcards(
+    exprs: Vec<LogicalExpression>,
+    input: &LogicalPlan,
+) -> Result<Vec<LogicalExpression>, Error> {
+    Ok(exprs
+        .into_iter()
+        .map(|expr| match expr {
+            LogicalExpression::Wildcard(wildcard) => wildcard.expand(input),
+            expr => Ok(vec![expr]),
+        })
+        .collect::<Result<Vec<_>, Error>>()?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
 impl fmt::Display for Projection {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -160,8 +465,10 @@ impl fmt::Display for Projection {
 
 impl Projection {
     #[inline]
-    fn schema(&self) -> Result<&Schema, Error> {
-        Ok(&self.schema)
+    pub(crate) fn schema(&self) -> Result<&Schema, Error> {
+        derived_schema(&self.schema, || {
+            Self::derive_schema(&self.exprs, &self.children[0])
+        })
     }
     #[inline]
     fn children(&self) -> Option<&[LogicalPlan]> {
@@ -171,26 +478,24 @@ impl Projection {
 
 // Selection
 
+#[derive(Serialize, Deserialize)]
 pub struct Selection {
     pub(crate) expr: LogicalExpression,
     pub(crate) children: Vec<LogicalPlan>,
-    pub(crate) schema: Schema,
+    pub(crate) schema: Option<Schema>,
 }
 
 impl Selection {
     pub fn new(input: LogicalPlan, expr: LogicalExpression) -> Self {
         Selection {
-            schema: Self::derive_schema(&expr, &input),
+            schema: Self::derive_schema(&expr, &input).ok(),
             expr: expr,
             children: vec![input],
         }
     }
 
-    fn derive_schema(expr: &LogicalExpression, input: &LogicalPlan) -> Schema {
-        expr.to_field(input)
-            .map(|x| vec![x])
-            .map(|x| x.into())
-            .unwrap()
+    fn derive_schema(_expr: &LogicalExpression, input: &LogicalPlan) -> Result<Schema, Error> {
+        input.schema().cloned()
     }
 }
 
@@ -202,8 +507,10 @@ impl fmt::Display for Selection {
 
 impl Selection {
     #[inline]
-    fn schema(&self) -> Result<&Schema, Error> {
-        Ok(&self.schema)
+    pub(crate) fn schema(&self) -> Result<&Schema, Error> {
+        derived_schema(&self.schema, || {
+            Self::derive_schema(&self.expr, &self.children[0])
+        })
     }
     #[inline]
     fn children(&self) -> Option<&[LogicalPlan]> {
@@ -212,11 +519,12 @@ impl Selection {
 }
 
 // Aggregate
+#[derive(Serialize, Deserialize)]
 pub struct Aggregate {
     pub(crate) group_exprs: Vec<LogicalExpression>,
     pub(crate) aggregate_exprs: Vec<LogicalExpression>,
     pub(crate) children: Vec<LogicalPlan>,
-    pub(crate) schema: Schema,
+    pub(crate) schema: Option<Schema>,
 }
 
 impl Aggregate {
@@ -226,25 +534,27 @@ impl Aggregate {
         aggregate_exprs: Vec<LogicalExpression>,
     ) -> Self {
         Aggregate {
-            schema: Self::derive_schema(&group_exprs, &aggregate_exprs, &input),
+            schema: Self::derive_schema(&group_exprs, &aggregate_exprs, &input).ok(),
             group_exprs: group_exprs,
             aggregate_exprs: aggregate_exprs,
             children: vec![input],
         }
     }
 
+    /// Fails if several output columns have the same name.
     fn derive_schema(
         group_exprs: &Vec<LogicalExpression>,
         aggregate_exprs: &Vec<LogicalExpression>,
         input: &LogicalPlan,
-    ) -> Schema {
-        group_exprs
+    ) -> Result<Schema, Error> {
+        let schema: Schema = group_exprs
             .iter()
             .chain(aggregate_exprs.iter())
             .map(|expr| expr.to_field(input))
-            .collect::<Result<Vec<Field>, Error>>()
-            .map(|x| x.into())
-            .unwrap()
+            .collect::<Result<Vec<Field>, Error>>()?
+            .into();
+        check_unique_names(&schema)?;
+        Ok(schema)
     }
 }
 
@@ -263,11 +573,354 @@ impl fmt::Display for Aggregate {
 }
 
 impl Aggregate {
+    #[inline]
+    pub(crate) fn schema(&self) -> Result<&Schema, Error> {
+        derived_schema(&self.schema, || {
+            Self::derive_schema(&self.group_exprs, &self.aggregate_exprs, &self.children[0])
+        })
+    }
+    #[inline]
+    fn children(&self) -> Option<&[LogicalPlan]> {
+        Some(&self.children)
+    }
+}
+
+// Sample
+
+#[derive(Serialize, Deserialize)]
+pub struct Sample {
+    pub(crate) fraction: f64,
+    pub(crate) seed: u64,
+    pub(crate) children: Vec<LogicalPlan>,
+    pub(crate) schema: Option<Schema>,
+}
+
+impl Sample {
+    pub fn new(input: LogicalPlan, fraction: f64, seed: u64) -> Self {
+        Sample {
+            schema: input.schema().ok().cloned(),
+            fraction,
+            seed,
+            children: vec![input],
+        }
+    }
+}
+
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sample: fraction={}, seed={}", self.fraction, self.seed)
+    }
+}
+
+impl Sample {
+    #[inline]
+    pub(crate) fn schema(&self) -> Result<&Schema, Error> {
+        derived_schema(&self.schema, || self.children[0].schema().cloned())
+    }
+    #[inline]
+    fn children(&self) -> Option<&[LogicalPlan]> {
+        Some(&self.children)
+    }
+}
+
+// Limit
+
+/// Keeps the first `n` rows of the input.
+#[derive(Serialize, Deserialize)]
+pub struct Limit {
+    pub(crate) n: usize,
+    pub(crate) children: Vec<LogicalPlan>,
+    pub(crate) schema: Option<Schema>,
+}
+
+impl Limit {
+    pub fn new(input: LogicalPlan, n: usize) -> Self {
+        Limit {
+            schema: input.schema().ok().cloned(),
+            n,
+            children: vec![input],
+        }
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Limit: {}", self.n)
+    }
+}
+
+impl Limit {
+    #[inline]
+    pub(crate) fn schema(&self) -> Result<&Schema, Error> {
+        derived_schema(&self.schema, || self.children[0].schema().cloned())
+    }
+    #[inline]
+    fn children(&self) -> Option<&[LogicalPlan]> {
+        Some(&self.children)
+    }
+}
+
+// Values
+
+/// A constant table made of literal rows.
+#[derive(Serialize, Deserialize)]
+pub struct Values {
+    pub(crate) rows: Vec<Vec<LogicalExpression>>,
+    pub(crate) schema: Schema,
+}
+
+impl Values {
+    pub fn new(schema: Schema, rows: Vec<Vec<LogicalExpression>>) -> Self {
+        Values { rows, schema }
+    }
+}
+
+impl fmt::Display for Values {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Values: rows={}", self.rows.len())
+    }
+}
+
+impl Values {
     #[inline]
     fn schema(&self) -> Result<&Schema, Error> {
         Ok(&self.schema)
     }
     #[inline]
+    fn children(&self) -> Option<&[LogicalPlan]> {
+        None
+    }
+}
+
+// Join
+
+/// Inner equi-join of two plans. Two rows match if every pair of key columns in `on` is equal,
+/// rows with a null key don't match any row. The schema is the left schema followed by the
+/// right one.
+///
+/// Unlike projections and aggregates, a join may have several columns of the same name, e.g.
+/// every column of a self-join appears twice. A name refers to the leftmost of these columns,
+/// the others can only be told apart by position.
+#[derive(Serialize, Deserialize)]
+pub struct Join {
+    pub(crate) on: Vec<(String, String)>,
+    pub(crate) children: Vec<LogicalPlan>,
+    pub(crate) schema: Option<Schema>,
+}
+
+impl Join {
+    pub fn new(left: LogicalPlan, right: LogicalPlan, on: Vec<(String, String)>) -> Self {
+        Join {
+            schema: Self::derive_schema(&left, &right).ok(),
+            on,
+            children: vec![left, right],
+        }
+    }
+
+    fn derive_schema(left: &LogicalPlan, right: &LogicalPlan) -> Result<Schema, Error> {
+        Ok(left
+            .schema()?
+            .fields
+            .iter()
+            .chain(right.schema()?.fields.iter())
+            .cloned()
+            .collect::<Vec<Field>>()
+            .into())
+    }
+}
+
+impl fmt::Display for Join {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Join: on={}",
+            self.on
+                .iter()
+                .map(|(left, right)| format!("#{} = #{}", left, right))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl Join {
+    #[inline]
+    pub(crate) fn schema(&self) -> Result<&Schema, Error> {
+        derived_schema(&self.schema, || {
+            Self::derive_schema(&self.children[0], &self.children[1])
+        })
+    }
+    #[inline]
+    fn children(&self) -> Option<&[LogicalPlan]> {
+        Some(&self.children)
+    }
+}
+
+// Distinct
+
+/// Keeps the first row for every distinct combination of the values of the columns `on`.
+/// Nulls are equal to each other.
+#[derive(Serialize, Deserialize)]
+pub struct Distinct {
+    pub(crate) on: Vec<String>,
+    pub(crate) children: Vec<LogicalPlan>,
+    pub(crate) schema: Option<Schema>,
+}
+
+impl Distinct {
+    pub fn new(input: LogicalPlan, on: Vec<String>) -> Self {
+        Distinct {
+            schema: input.schema().ok().cloned(),
+            on,
+            children: vec![input],
+        }
+    }
+}
+
+impl fmt::Display for Distinct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Distinct: on={}",
+            self.on
+                .iter()
+                .map(|x| format!("#{}", x))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl Distinct {
+    #[inline]
+    pub(crate) fn schema(&self) -> Result<&Schema, Error> {
+        derived_schema(&self.schema, || self.children[0].schema().cloned())
+    }
+    #[inline]
+    fn children(&self) -> Option<&[LogicalPlan]> {
+        Some(&self.children)
+    }
+}
+
+// Window
+
+/// Appends a column for every window expression, e.g. `cumsum`. The expressions are computed
+/// over all rows of the input in input order.
+#[derive(Serialize, Deserialize)]
+pub struct Window {
+    pub(crate) exprs: Vec<LogicalExpression>,
+    pub(crate) children: Vec<LogicalPlan>,
+    pub(crate) schema: Option<Schema>,
+}
+
+impl Window {
+    pub fn new(input: LogicalPlan, exprs: Vec<LogicalExpression>) -> Self {
+        Window {
+            schema: Self::derive_schema(&exprs, &input).ok(),
+            exprs,
+            children: vec![input],
+        }
+    }
+
+    fn derive_schema(exprs: &[LogicalExpression], input: &LogicalPlan) -> Result<Schema, Error> {
+        let mut fields = input.schema()?.fields.clone();
+        fields.extend(
+            exprs
+                .iter()
+                .map(|expr| expr.to_field(input))
+                .collect::<Result<Vec<Field>, Error>>()?,
+        );
+        Ok(fields.into())
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Window: {}",
+            self.exprs
+                .iter()
+                .map(|expr| format!("{}, ", expr))
+                .collect::<String>()
+        )
+    }
+}
+
+impl Window {
+    #[inline]
+    pub(crate) fn schema(&self) -> Result<&Schema, Error> {
+        derived_schema(&self.schema, || {
+            Self::derive_schema(&self.exprs, &self.children[0])
+        })
+    }
+    #[inline]
+    fn children(&self) -> Option<&[LogicalPlan]> {
+        Some(&self.children)
+    }
+}
+
+// Unnest
+
+/// Turns every element of the list column `column` into its own row, repeating the values of
+/// the other columns. Rows with an empty or null list are dropped, unless `preserve_empty` is
+/// set, which keeps them as a single row with a null element.
+#[derive(Serialize, Deserialize)]
+pub struct Unnest {
+    pub(crate) column: String,
+    pub(crate) preserve_empty: bool,
+    pub(crate) children: Vec<LogicalPlan>,
+    pub(crate) schema: Option<Schema>,
+}
+
+impl Unnest {
+    pub fn new(input: LogicalPlan, column: String) -> Self {
+        Unnest {
+            schema: Self::derive_schema(&input, &column).ok(),
+            column,
+            preserve_empty: false,
+            children: vec![input],
+        }
+    }
+
+    /// Keeps rows with an empty or null list as a single row with a null element.
+    pub fn with_preserve_empty(mut self, preserve_empty: bool) -> Self {
+        self.preserve_empty = preserve_empty;
+        self
+    }
+
+    /// The list column is replaced by a column of its elements. Other types are kept, the
+    /// planner rejects them.
+    fn derive_schema(input: &LogicalPlan, column: &str) -> Result<Schema, Error> {
+        Ok(input
+            .schema()?
+            .fields
+            .iter()
+            .map(|field| match &field.data_type {
+                DataType::List(inner) | DataType::LargeList(inner) if field.name == column => {
+                    Field::new(&field.name, inner.data_type.clone(), true)
+                }
+                _ => field.clone(),
+            })
+            .collect::<Vec<Field>>()
+            .into())
+    }
+}
+
+impl fmt::Display for Unnest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unnest: #{}", self.column)
+    }
+}
+
+impl Unnest {
+    #[inline]
+    pub(crate) fn schema(&self) -> Result<&Schema, Error> {
+        derived_schema(&self.schema, || {
+            Self::derive_schema(&self.children[0], &self.column)
+        })
+    }
+    #[inline]
     fn children(&self) -> Option<&[LogicalPlan]> {
         Some(&self.children)
     }
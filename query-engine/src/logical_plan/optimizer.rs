@@ -1,24 +1,216 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt};
+
+use arrow2::datatypes::DataType;
+use serde::{Deserialize, Serialize};
 
 use super::{
-    logical_expression::LogicalExpression, Aggregate, LogicalPlan, Projection, Scan, Selection,
+    logical_expression::{
+        LiteralBool, LiteralFloat, LiteralInteger, LiteralLong, LiteralString, LogicalExpression,
+        LogicalExpressionMethods,
+    },
+    Aggregate, Distinct, Join, Limit, LogicalPlan, Projection, Sample, Scan, Selection, Unnest,
+    Values, Window,
 };
 
 impl LogicalPlan {
     pub fn optimize(self) -> Self {
-        self.projection_push_down()
+        self.filter_push_down().projection_push_down()
+    }
+}
+
+// Filter push down
+
+impl LogicalPlan {
+    /// Moves the `column <op> literal` conjuncts of a selection directly above a scan into the
+    /// scan, so that the data source filters the rows while reading them. The remaining
+    /// conjuncts stay in the selection.
+    fn filter_push_down(self) -> Self {
+        match self {
+            LogicalPlan::Scan(scan) => LogicalPlan::Scan(scan),
+            LogicalPlan::Values(values) => LogicalPlan::Values(values),
+            LogicalPlan::Selection(mut sel) => {
+                match sel.children.pop().unwrap().filter_push_down() {
+                    LogicalPlan::Scan(scan) => {
+                        let mut filters = scan.filters.clone();
+                        let mut remaining = Vec::new();
+                        split_conjuncts(sel.expr, &scan, &mut filters, &mut remaining);
+                        let scan = LogicalPlan::Scan(scan.with_filters(filters));
+                        match remaining.into_iter().reduce(|left, right| left.and(right)) {
+                            Some(expr) => LogicalPlan::Selection(Selection::new(scan, expr)),
+                            None => scan,
+                        }
+                    }
+                    input => LogicalPlan::Selection(Selection::new(input, sel.expr)),
+                }
+            }
+            LogicalPlan::Projection(mut proj) => LogicalPlan::Projection(Projection::new(
+                proj.children.pop().unwrap().filter_push_down(),
+                proj.exprs,
+            )),
+            LogicalPlan::Aggregate(mut agg) => LogicalPlan::Aggregate(Aggregate::new(
+                agg.children.pop().unwrap().filter_push_down(),
+                agg.group_exprs,
+                agg.aggregate_exprs,
+            )),
+            LogicalPlan::Sample(mut sample) => LogicalPlan::Sample(Sample::new(
+                sample.children.pop().unwrap().filter_push_down(),
+                sample.fraction,
+                sample.seed,
+            )),
+            LogicalPlan::Limit(mut limit) => LogicalPlan::Limit(Limit::new(
+                limit.children.pop().unwrap().filter_push_down(),
+                limit.n,
+            )),
+            LogicalPlan::Join(mut join) => {
+                let right = join.children.pop().unwrap().filter_push_down();
+                let left = join.children.pop().unwrap().filter_push_down();
+                LogicalPlan::Join(Join::new(left, right, join.on))
+            }
+            LogicalPlan::Distinct(mut distinct) => LogicalPlan::Distinct(Distinct::new(
+                distinct.children.pop().unwrap().filter_push_down(),
+                distinct.on,
+            )),
+            LogicalPlan::Window(mut window) => LogicalPlan::Window(Window::new(
+                window.children.pop().unwrap().filter_push_down(),
+                window.exprs,
+            )),
+            LogicalPlan::Unnest(mut unnest) => LogicalPlan::Unnest(
+                Unnest::new(
+                    unnest.children.pop().unwrap().filter_push_down(),
+                    unnest.column,
+                )
+                .with_preserve_empty(unnest.preserve_empty),
+            ),
+        }
+    }
+}
+
+/// Splits `expr` at its `And`s into the predicates the scan can apply and the remaining ones.
+/// A predicate is only handed to the scan if its literal has the type of the column.
+fn split_conjuncts(
+    expr: LogicalExpression,
+    scan: &Scan,
+    filters: &mut Vec<PrunablePredicate>,
+    remaining: &mut Vec<LogicalExpression>,
+) {
+    if let LogicalExpression::And(and) = expr {
+        let and = *and;
+        split_conjuncts(and.left, scan, filters, remaining);
+        split_conjuncts(and.right, scan, filters, remaining);
+        return;
+    }
+    let mut predicates = Vec::new();
+    extract_prunable_predicates(&expr, &mut predicates);
+    match predicates.pop() {
+        Some(predicate)
+            if scan.schema.fields.iter().any(|x| {
+                x.name == predicate.column && x.data_type == predicate.literal.data_type()
+            }) =>
+        {
+            filters.push(predicate)
+        }
+        _ => remaining.push(expr),
+    }
+}
+
+// Limit push down
+
+impl LogicalPlan {
+    /// Passes the row count of a limit to the scan below it, so that the data source can stop
+    /// reading early. Only projections may be between the limit and the scan, and the scan must
+    /// not filter rows.
+    pub(crate) fn limit_push_down(self, n: usize) -> Self {
+        match self {
+            LogicalPlan::Scan(scan) if scan.filters.is_empty() => {
+                LogicalPlan::Scan(scan.with_limit(Some(n)))
+            }
+            LogicalPlan::Projection(mut proj) => LogicalPlan::Projection(Projection::new(
+                proj.children.pop().unwrap().limit_push_down(n),
+                proj.exprs,
+            )),
+            plan => plan,
+        }
+    }
+}
+
+// Aggregates from statistics
+
+impl Aggregate {
+    /// Answers a min, max or count of columns without group by directly from the column
+    /// statistics of an unfiltered scan, e.g. the metadata of a parquet file, so the source
+    /// doesn't have to be read. Returns None if any of the aggregates can't be answered from the
+    /// statistics.
+    pub(crate) fn statistics_values(&self) -> Option<Values> {
+        if !self.group_exprs.is_empty() {
+            return None;
+        }
+        let scan = match &self.children[0] {
+            LogicalPlan::Scan(scan)
+                if scan.filters.is_empty() && scan.limit.is_none() && scan.ordinals.is_none() =>
+            {
+                scan
+            }
+            _ => return None,
+        };
+        let schema = self.schema.as_ref()?;
+        let row = self
+            .aggregate_exprs
+            .iter()
+            .zip(&schema.fields)
+            .map(|(expr, field)| statistics_value(expr, scan, &field.data_type))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Values::new(schema.clone(), vec![row]))
+    }
+}
+
+fn statistics_value(
+    expr: &LogicalExpression,
+    scan: &Scan,
+    data_type: &DataType,
+) -> Option<LogicalExpression> {
+    let bound = |expr: &LogicalExpression, max: bool| match expr {
+        LogicalExpression::Column(column) => {
+            let statistics = scan.data_source.statistics(&column.name)?;
+            let bound = if max { statistics.max } else { statistics.min }?;
+            (&bound.data_type() == data_type).then(|| bound.to_expression())
+        }
+        _ => None,
+    };
+    match expr {
+        LogicalExpression::Alias(alias) => statistics_value(&alias.expr, scan, data_type),
+        LogicalExpression::Max(max) => bound(&max.expr, true),
+        LogicalExpression::Min(min) => bound(&min.expr, false),
+        LogicalExpression::Count(count) => match &count.expr {
+            LogicalExpression::Column(column) => {
+                let rows = scan.data_source.num_rows()?;
+                let statistics = scan.data_source.statistics(&column.name)?;
+                Some(LogicalExpression::LiteralLong(LiteralLong::new(
+                    (rows - statistics.null_count) as i64,
+                )))
+            }
+            _ => None,
+        },
+        _ => None,
     }
 }
 
 // Projection push down
 
 impl LogicalPlan {
+    /// Restricts the scans to the columns used by the plan. The columns of the plan's own
+    /// output are always kept, e.g. for a join or selection at the root of the plan.
     fn projection_push_down(self) -> Self {
-        let mut hash_set = HashSet::new();
+        let mut hash_set = match self.schema() {
+            Ok(schema) => schema.fields.iter().map(|x| x.name.clone()).collect(),
+            Err(_) => HashSet::new(),
+        };
         self.push_down(&mut hash_set)
     }
     fn push_down(self, hash_set: &mut HashSet<String>) -> Self {
         match self {
+            LogicalPlan::Values(values) => LogicalPlan::Values(values),
+            // Names can be ambiguous, so an ordinal projection is kept as it is.
+            LogicalPlan::Scan(scan) if scan.ordinals.is_some() => LogicalPlan::Scan(scan),
             LogicalPlan::Scan(scan) => {
                 let hash_set = match scan.projection {
                     Some(projs) => projs.into_iter().fold(hash_set, |hash_set, x| {
@@ -27,32 +219,101 @@ impl LogicalPlan {
                     }),
                     None => hash_set,
                 };
-                LogicalPlan::Scan(Scan::new(
-                    &scan.path,
-                    scan.data_source,
-                    Some(hash_set.iter().map(|x| x.clone()).collect::<Vec<String>>()),
-                ))
+                scan.filters.iter().for_each(|filter| {
+                    hash_set.insert(filter.column.clone());
+                });
+                LogicalPlan::Scan(
+                    Scan::new(
+                        &scan.path,
+                        scan.data_source,
+                        Some(hash_set.iter().map(|x| x.clone()).collect::<Vec<String>>()),
+                    )
+                    .with_filters(scan.filters),
+                )
             }
+            // Aggregates and projections define new columns, the columns used above them
+            // don't refer to their input.
             LogicalPlan::Aggregate(mut agg) => {
                 let input = agg.children.pop().unwrap();
-                extract_all_columns(&agg.group_exprs, &input, hash_set);
-                extract_all_columns(&agg.aggregate_exprs, &input, hash_set);
+                let mut hash_set = HashSet::new();
+                extract_all_columns(&agg.group_exprs, &input, &mut hash_set);
+                extract_all_columns(&agg.aggregate_exprs, &input, &mut hash_set);
                 LogicalPlan::Aggregate(Aggregate::new(
-                    input.push_down(hash_set),
+                    input.push_down(&mut hash_set),
                     agg.group_exprs,
                     agg.aggregate_exprs,
                 ))
             }
             LogicalPlan::Projection(mut proj) => {
                 let input = proj.children.pop().unwrap();
-                extract_all_columns(&proj.exprs, &input, hash_set);
-                LogicalPlan::Projection(Projection::new(input.push_down(hash_set), proj.exprs))
+                let mut columns = HashSet::new();
+                proj.exprs.iter().for_each(|expr| match expr {
+                    // A wildcard only needs the columns that are used above the projection. If
+                    // none of them are, it keeps all columns so that it doesn't select nothing.
+                    LogicalExpression::Wildcard(wildcard) => {
+                        let names = wildcard.columns(&input).unwrap_or_default();
+                        let used = names
+                            .iter()
+                            .filter(|x| hash_set.contains(*x))
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        columns.extend(if used.is_empty() { names } else { used });
+                    }
+                    expr => extract_columns(expr, &input, &mut columns),
+                });
+                LogicalPlan::Projection(Projection::new(input.push_down(&mut columns), proj.exprs))
             }
             LogicalPlan::Selection(mut sel) => {
                 let input = sel.children.pop().unwrap();
                 extract_columns(&sel.expr, &input, hash_set);
                 LogicalPlan::Selection(Selection::new(input.push_down(hash_set), sel.expr))
             }
+            LogicalPlan::Sample(mut sample) => {
+                let input = sample.children.pop().unwrap();
+                LogicalPlan::Sample(Sample::new(
+                    input.push_down(hash_set),
+                    sample.fraction,
+                    sample.seed,
+                ))
+            }
+            LogicalPlan::Limit(mut limit) => {
+                let input = limit.children.pop().unwrap();
+                LogicalPlan::Limit(Limit::new(input.push_down(hash_set), limit.n))
+            }
+            // Both inputs only keep the columns they contain, so they can share the set.
+            LogicalPlan::Join(mut join) => {
+                join.on.iter().for_each(|(left, right)| {
+                    hash_set.insert(left.clone());
+                    hash_set.insert(right.clone());
+                });
+                let right = join
+                    .children
+                    .pop()
+                    .unwrap()
+                    .push_down(&mut hash_set.clone());
+                let left = join.children.pop().unwrap().push_down(hash_set);
+                LogicalPlan::Join(Join::new(left, right, join.on))
+            }
+            LogicalPlan::Distinct(mut distinct) => {
+                distinct.on.iter().for_each(|x| {
+                    hash_set.insert(x.clone());
+                });
+                let input = distinct.children.pop().unwrap();
+                LogicalPlan::Distinct(Distinct::new(input.push_down(hash_set), distinct.on))
+            }
+            LogicalPlan::Window(mut window) => {
+                let input = window.children.pop().unwrap();
+                extract_all_columns(&window.exprs, &input, hash_set);
+                LogicalPlan::Window(Window::new(input.push_down(hash_set), window.exprs))
+            }
+            LogicalPlan::Unnest(mut unnest) => {
+                hash_set.insert(unnest.column.clone());
+                let input = unnest.children.pop().unwrap();
+                LogicalPlan::Unnest(
+                    Unnest::new(input.push_down(hash_set), unnest.column)
+                        .with_preserve_empty(unnest.preserve_empty),
+                )
+            }
         }
     }
 }
@@ -73,10 +334,20 @@ fn extract_columns(expr: &LogicalExpression, plan: &LogicalPlan, hash_set: &mut
         LogicalExpression::Column(column) => {
             hash_set.insert(column.name.clone());
         }
+        LogicalExpression::Wildcard(wildcard) => {
+            if let Ok(columns) = wildcard.expand(plan) {
+                extract_all_columns(&columns, plan, hash_set)
+            }
+        }
+        LogicalExpression::Alias(alias) => extract_columns(&alias.expr, plan, hash_set),
+        LogicalExpression::Cast(cast) => extract_columns(&cast.expr, plan, hash_set),
         LogicalExpression::LiteralBool(_) => {}
         LogicalExpression::LiteralString(_) => {}
         LogicalExpression::LiteralInteger(_) => {}
+        LogicalExpression::LiteralLong(_) => {}
         LogicalExpression::LiteralFloat(_) => {}
+        LogicalExpression::LiteralInterval(_) => {}
+        LogicalExpression::LiteralNull(_) => {}
         LogicalExpression::Eq(eq) => {
             extract_columns(&eq.left, plan, hash_set);
             extract_columns(&eq.right, plan, hash_set)
@@ -85,6 +356,10 @@ fn extract_columns(expr: &LogicalExpression, plan: &LogicalPlan, hash_set: &mut
             extract_columns(&neq.left, plan, hash_set);
             extract_columns(&neq.right, plan, hash_set)
         }
+        LogicalExpression::NullSafeEq(eq) => {
+            extract_columns(&eq.left, plan, hash_set);
+            extract_columns(&eq.right, plan, hash_set)
+        }
         LogicalExpression::Gt(gt) => {
             extract_columns(&gt.left, plan, hash_set);
             extract_columns(&gt.right, plan, hash_set)
@@ -129,10 +404,173 @@ fn extract_columns(expr: &LogicalExpression, plan: &LogicalPlan, hash_set: &mut
             extract_columns(&modu.left, plan, hash_set);
             extract_columns(&modu.right, plan, hash_set)
         }
+        LogicalExpression::Year(year) => extract_columns(&year.expr, plan, hash_set),
+        LogicalExpression::Month(month) => extract_columns(&month.expr, plan, hash_set),
+        LogicalExpression::Day(day) => extract_columns(&day.expr, plan, hash_set),
+        LogicalExpression::ScalarUdf(fun) => extract_all_columns(&fun.args, plan, hash_set),
         LogicalExpression::Avg(avg) => extract_columns(&avg.expr, plan, hash_set),
         LogicalExpression::Sum(sum) => extract_columns(&sum.expr, plan, hash_set),
         LogicalExpression::Max(max) => extract_columns(&max.expr, plan, hash_set),
         LogicalExpression::Min(min) => extract_columns(&min.expr, plan, hash_set),
         LogicalExpression::Count(count) => extract_columns(&count.expr, plan, hash_set),
+        LogicalExpression::AggregateFunction(fun) => extract_columns(&fun.expr, plan, hash_set),
+        LogicalExpression::CumSum(cumsum) => extract_columns(&cumsum.expr, plan, hash_set),
+        LogicalExpression::Rank(rank) => extract_columns(&rank.expr, plan, hash_set),
+    }
+}
+
+// Predicate extraction for statistics pruning
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PruningOperator {
+    Eq,
+    Neq,
+    Gt,
+    GtEq,
+    Lt,
+    LtEq,
+}
+
+impl PruningOperator {
+    /// The operator that yields the same result when the operands are swapped.
+    fn flip(self) -> Self {
+        match self {
+            PruningOperator::Eq => PruningOperator::Eq,
+            PruningOperator::Neq => PruningOperator::Neq,
+            PruningOperator::Gt => PruningOperator::Lt,
+            PruningOperator::GtEq => PruningOperator::LtEq,
+            PruningOperator::Lt => PruningOperator::Gt,
+            PruningOperator::LtEq => PruningOperator::GtEq,
+        }
+    }
+}
+
+impl fmt::Display for PruningOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PruningOperator::Eq => write!(f, "=="),
+            PruningOperator::Neq => write!(f, "!="),
+            PruningOperator::Gt => write!(f, ">"),
+            PruningOperator::GtEq => write!(f, ">="),
+            PruningOperator::Lt => write!(f, "<"),
+            PruningOperator::LtEq => write!(f, "<="),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum PruningLiteral {
+    Bool(bool),
+    String(String),
+    Integer(i32),
+    Float(f64),
+}
+
+impl PruningLiteral {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            PruningLiteral::Bool(_) => DataType::Boolean,
+            PruningLiteral::String(_) => DataType::Utf8,
+            PruningLiteral::Integer(_) => DataType::Int32,
+            PruningLiteral::Float(_) => DataType::Float64,
+        }
+    }
+    /// The literal as a logical expression.
+    pub fn to_expression(&self) -> LogicalExpression {
+        match self {
+            PruningLiteral::Bool(value) => LogicalExpression::LiteralBool(LiteralBool::new(*value)),
+            PruningLiteral::String(value) => {
+                LogicalExpression::LiteralString(LiteralString::new(value.clone()))
+            }
+            PruningLiteral::Integer(value) => {
+                LogicalExpression::LiteralInteger(LiteralInteger::new(*value))
+            }
+            PruningLiteral::Float(value) => {
+                LogicalExpression::LiteralFloat(LiteralFloat::new(*value))
+            }
+        }
+    }
+}
+
+impl fmt::Display for PruningLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PruningLiteral::Bool(value) => write!(f, "'{}'", value),
+            PruningLiteral::String(value) => write!(f, "'{}'", value),
+            PruningLiteral::Integer(value) => write!(f, "'{}'", value),
+            PruningLiteral::Float(value) => write!(f, "'{}'", value),
+        }
+    }
+}
+
+/// A simple `column <op> literal` predicate that can be checked against column statistics.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrunablePredicate {
+    pub column: String,
+    pub op: PruningOperator,
+    pub literal: PruningLiteral,
+}
+
+impl fmt::Display for PrunablePredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {} {}", self.column, self.op, self.literal)
+    }
+}
+
+impl Selection {
+    /// Returns the `column <op> literal` conjuncts of the selection predicate. Conjuncts of any
+    /// other shape are skipped, so every returned predicate is a necessary condition for a row
+    /// to be selected.
+    pub fn prunable_predicates(&self) -> Vec<PrunablePredicate> {
+        let mut predicates = Vec::new();
+        extract_prunable_predicates(&self.expr, &mut predicates);
+        predicates
+    }
+}
+
+fn extract_prunable_predicates(expr: &LogicalExpression, predicates: &mut Vec<PrunablePredicate>) {
+    let (op, left, right) = match expr {
+        LogicalExpression::And(and) => {
+            extract_prunable_predicates(&and.left, predicates);
+            extract_prunable_predicates(&and.right, predicates);
+            return;
+        }
+        LogicalExpression::Eq(eq) => (PruningOperator::Eq, &eq.left, &eq.right),
+        LogicalExpression::Neq(neq) => (PruningOperator::Neq, &neq.left, &neq.right),
+        LogicalExpression::Gt(gt) => (PruningOperator::Gt, &gt.left, &gt.right),
+        LogicalExpression::GtEq(gteq) => (PruningOperator::GtEq, &gteq.left, &gteq.right),
+        LogicalExpression::Lt(lt) => (PruningOperator::Lt, &lt.left, &lt.right),
+        LogicalExpression::LtEq(lteq) => (PruningOperator::LtEq, &lteq.left, &lteq.right),
+        _ => return,
+    };
+    let predicate = match (left, right) {
+        (LogicalExpression::Column(column), literal) => {
+            to_pruning_literal(literal).map(|literal| PrunablePredicate {
+                column: column.name.clone(),
+                op,
+                literal,
+            })
+        }
+        (literal, LogicalExpression::Column(column)) => {
+            to_pruning_literal(literal).map(|literal| PrunablePredicate {
+                column: column.name.clone(),
+                op: op.flip(),
+                literal,
+            })
+        }
+        _ => None,
+    };
+    predicates.extend(predicate);
+}
+
+fn to_pruning_literal(expr: &LogicalExpression) -> Option<PruningLiteral> {
+    match expr {
+        LogicalExpression::LiteralBool(bool) => Some(PruningLiteral::Bool(bool.value)),
+        LogicalExpression::LiteralString(string) => {
+            Some(PruningLiteral::String(string.value.clone()))
+        }
+        LogicalExpression::LiteralInteger(int) => Some(PruningLiteral::Integer(int.value)),
+        LogicalExpression::LiteralFloat(float) => Some(PruningLiteral::Float(float.value)),
+        _ => None,
     }
 }
@@ -1,41 +1,174 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
 use std::sync::Arc;
-use std::{borrow::Borrow, collections::HashMap};
+use std::time::{Duration, Instant};
 
 use arrow2::array::{
-    MutableArray, MutableBooleanArray, MutablePrimitiveArray, MutableUtf8Array, PrimitiveArray,
-    Utf8Array,
+    new_empty_array,
+    ord::{build_compare, DynComparator},
+    ListArray, MutableArray, MutableBooleanArray, MutablePrimitiveArray, MutableUtf8Array,
+    PrimitiveArray, Utf8Array,
 };
 use arrow2::bitmap::Bitmap;
-use arrow2::buffer::Buffer;
-use arrow2::compute::arithmetics::ArrayAdd;
 use arrow2::datatypes::{DataType, PhysicalType, PrimitiveType};
 use arrow2::scalar::PrimitiveScalar;
+use arrow2::types::{NativeType, Offset};
 use arrow2::{
     array::{Array, BooleanArray},
     chunk::Chunk,
     compute,
     datatypes::Schema,
-    error::ArrowError,
 };
 
 use crate::{
     columnar_value::{scalar_to_array, ColumnarValue},
     data_source::DataSource,
     error::Error,
+    logical_plan::optimizer::PrunablePredicate,
 };
 
-use self::physical_expressions::{PhysicalAggregateExpression, PhysicalExpression};
+use self::physical_expressions::{
+    Accumulator, PhysicalAggregateExpression, PhysicalExpression, PhysicalWindowExpression,
+};
 
 pub mod physical_expressions;
 
 pub type Batch = Result<Chunk<Arc<dyn Array>>, Error>;
 
+/// Concatenates the columns of several chunks with the same schema into a single chunk.
+pub(crate) fn concat_chunks(chunks: &[Chunk<Arc<dyn Array>>]) -> Batch {
+    let columns = chunks
+        .first()
+        .map(|chunk| chunk.arrays().len())
+        .unwrap_or(0);
+    (0..columns)
+        .map(|i| {
+            let arrays = chunks
+                .iter()
+                .map(|chunk| chunk[i].as_ref())
+                .collect::<Vec<&dyn Array>>();
+            compute::concatenate::concatenate(&arrays)
+                .map(Arc::from)
+                .map_err(Error::ArrowError)
+        })
+        .collect::<Result<Vec<Arc<dyn Array>>, Error>>()
+        .map(Chunk::new)
+}
+
+/// Hashes the values of a column. Floats can't be hashed by arrow, they are hashed by their bits
+/// after mapping -0.0 to 0.0 and every NaN to the same NaN, so that equal values hash equally.
+fn hash_column(column: &dyn Array) -> Result<PrimitiveArray<u64>, Error> {
+    match column.data_type().to_physical_type() {
+        PhysicalType::Primitive(PrimitiveType::Float32) => {
+            let column = column
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f32>>()
+                .ok_or(Error::DowncastError)?;
+            let bits = compute::arity::unary(
+                column,
+                |x| {
+                    if x == 0.0 {
+                        0.0f32.to_bits()
+                    } else if x.is_nan() {
+                        f32::NAN.to_bits()
+                    } else {
+                        x.to_bits()
+                    }
+                },
+                DataType::UInt32,
+            );
+            compute::hash::hash(&bits)
+        }
+        PhysicalType::Primitive(PrimitiveType::Float64) => {
+            let column = column
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .ok_or(Error::DowncastError)?;
+            let bits = compute::arity::unary(
+                column,
+                |x| {
+                    if x == 0.0 {
+                        0.0f64.to_bits()
+                    } else if x.is_nan() {
+                        f64::NAN.to_bits()
+                    } else {
+                        x.to_bits()
+                    }
+                },
+                DataType::UInt64,
+            );
+            compute::hash::hash(&bits)
+        }
+        _ => compute::hash::hash(column),
+    }
+    .map_err(Error::ArrowError)
+}
+
+/// Hashes every row of `columns`. The hashes of the individual columns are combined in column
+/// order, so the same row values always produce the same hash regardless of the operator. A row
+/// with a null in any column hashes to null.
+pub(crate) fn row_hashes(columns: &Chunk<Arc<dyn Array>>) -> Result<PrimitiveArray<u64>, Error> {
+    let mut hashes = columns.iter().map(|column| hash_column(column.as_ref()));
+    let first = hashes
+        .next()
+        .unwrap_or_else(|| Ok(PrimitiveArray::from_vec(vec![])))?;
+    hashes.try_fold(first, |acc, hash| {
+        Ok(compute::arity::binary(
+            &acc,
+            &hash?,
+            DataType::UInt64,
+            |a, b| a.wrapping_mul(31).wrapping_add(b),
+        ))
+    })
+}
+
+/// Hash of a null group key.
+const NULL_HASH: u64 = u64::MAX;
+
+/// Hashes the group keys of every row like `row_hashes`, except that nulls hash to
+/// `NULL_HASH`, so that rows with null keys form a group of their own.
+fn group_hashes(columns: &Chunk<Arc<dyn Array>>) -> Result<PrimitiveArray<u64>, Error> {
+    let hashes = columns
+        .iter()
+        .map(|column| {
+            let hash = hash_column(column.as_ref())?;
+            Ok(Arc::new(PrimitiveArray::from_vec(
+                hash.iter()
+                    .map(|x| x.copied().unwrap_or(NULL_HASH))
+                    .collect(),
+            )) as Arc<dyn Array>)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    row_hashes(&Chunk::new(hashes))
+}
+
+/// Checks that a physical plan is constructed with the number of inputs it operates on.
+fn check_inputs(name: &str, input: &[PhysicalPlan], expected: usize) -> Result<(), Error> {
+    match input.len() {
+        len if len == expected => Ok(()),
+        len => Err(Error::InputCountMismatch(name.to_string(), expected, len)),
+    }
+}
+
 pub enum PhysicalPlan {
     Scan(ScanExec),
     Projection(ProjectionExec),
     Selection(SelectionExec),
     Aggregate(AggregateExec),
+    Sample(SampleExec),
+    Values(ValuesExec),
+    HashJoin(HashJoinExec),
+    SortMergeJoin(SortMergeJoinExec),
+    Distinct(DistinctExec),
+    Window(WindowExec),
+    Limit(LimitExec),
+    Repartition(RepartitionExec),
+    Unnest(UnnestExec),
+    Metrics(MetricsExec),
 }
 
 impl PhysicalPlan {
@@ -45,6 +178,16 @@ impl PhysicalPlan {
             PhysicalPlan::Projection(proj) => proj.schema(),
             PhysicalPlan::Selection(sel) => sel.schema(),
             PhysicalPlan::Aggregate(agg) => agg.schema(),
+            PhysicalPlan::Sample(sample) => sample.schema(),
+            PhysicalPlan::Values(values) => values.schema(),
+            PhysicalPlan::HashJoin(join) => join.schema(),
+            PhysicalPlan::SortMergeJoin(join) => join.schema(),
+            PhysicalPlan::Distinct(distinct) => distinct.schema(),
+            PhysicalPlan::Window(window) => window.schema(),
+            PhysicalPlan::Limit(limit) => limit.schema(),
+            PhysicalPlan::Repartition(repartition) => repartition.schema(),
+            PhysicalPlan::Unnest(unnest) => unnest.schema(),
+            PhysicalPlan::Metrics(metrics) => metrics.schema(),
         }
     }
     pub fn children(&self) -> Option<&[PhysicalPlan]> {
@@ -53,6 +196,16 @@ impl PhysicalPlan {
             PhysicalPlan::Projection(proj) => proj.children(),
             PhysicalPlan::Selection(sel) => sel.children(),
             PhysicalPlan::Aggregate(agg) => agg.children(),
+            PhysicalPlan::Sample(sample) => sample.children(),
+            PhysicalPlan::Values(values) => values.children(),
+            PhysicalPlan::HashJoin(join) => join.children(),
+            PhysicalPlan::SortMergeJoin(join) => join.children(),
+            PhysicalPlan::Distinct(distinct) => distinct.children(),
+            PhysicalPlan::Window(window) => window.children(),
+            PhysicalPlan::Limit(limit) => limit.children(),
+            PhysicalPlan::Repartition(repartition) => repartition.children(),
+            PhysicalPlan::Unnest(unnest) => unnest.children(),
+            PhysicalPlan::Metrics(metrics) => metrics.children(),
         }
     }
     pub fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
@@ -61,14 +214,191 @@ impl PhysicalPlan {
             PhysicalPlan::Projection(proj) => proj.execute(),
             PhysicalPlan::Selection(sel) => sel.execute(),
             PhysicalPlan::Aggregate(agg) => agg.execute(),
+            PhysicalPlan::Sample(sample) => sample.execute(),
+            PhysicalPlan::Values(values) => values.execute(),
+            PhysicalPlan::HashJoin(join) => join.execute(),
+            PhysicalPlan::SortMergeJoin(join) => join.execute(),
+            PhysicalPlan::Distinct(distinct) => distinct.execute(),
+            PhysicalPlan::Window(window) => window.execute(),
+            PhysicalPlan::Limit(limit) => limit.execute(),
+            PhysicalPlan::Repartition(repartition) => repartition.execute(),
+            PhysicalPlan::Unnest(unnest) => unnest.execute(),
+            PhysicalPlan::Metrics(metrics) => metrics.execute(),
+        }
+    }
+    /// Name of the operator, as used in `explain_analyze`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PhysicalPlan::Scan(_) => "Scan",
+            PhysicalPlan::Projection(_) => "Projection",
+            PhysicalPlan::Selection(_) => "Selection",
+            PhysicalPlan::Aggregate(_) => "Aggregate",
+            PhysicalPlan::Sample(_) => "Sample",
+            PhysicalPlan::Values(_) => "Values",
+            PhysicalPlan::HashJoin(_) => "HashJoin",
+            PhysicalPlan::SortMergeJoin(_) => "SortMergeJoin",
+            PhysicalPlan::Distinct(_) => "Distinct",
+            PhysicalPlan::Window(_) => "Window",
+            PhysicalPlan::Limit(_) => "Limit",
+            PhysicalPlan::Repartition(_) => "Repartition",
+            PhysicalPlan::Unnest(_) => "Unnest",
+            PhysicalPlan::Metrics(_) => "Metrics",
+        }
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        match self {
+            PhysicalPlan::Scan(_) | PhysicalPlan::Values(_) => None,
+            PhysicalPlan::Projection(proj) => Some(&mut proj.input),
+            PhysicalPlan::Selection(sel) => Some(&mut sel.input),
+            PhysicalPlan::Aggregate(agg) => Some(&mut agg.input),
+            PhysicalPlan::Sample(sample) => Some(&mut sample.input),
+            PhysicalPlan::HashJoin(join) => Some(&mut join.input),
+            PhysicalPlan::SortMergeJoin(join) => Some(&mut join.input),
+            PhysicalPlan::Distinct(distinct) => Some(&mut distinct.input),
+            PhysicalPlan::Window(window) => Some(&mut window.input),
+            PhysicalPlan::Limit(limit) => Some(&mut limit.input),
+            PhysicalPlan::Repartition(repartition) => Some(&mut repartition.input),
+            PhysicalPlan::Unnest(unnest) => Some(&mut unnest.input),
+            PhysicalPlan::Metrics(metrics) => Some(&mut metrics.input),
+        }
+    }
+    /// Wraps every operator of the plan in a `MetricsExec`. The returned tree holds the metrics
+    /// of the operators, which are filled in while the plan is executed.
+    pub fn with_metrics(mut self) -> (PhysicalPlan, PlanMetrics) {
+        let children = self
+            .children_mut()
+            .map(|input| {
+                let (plans, metrics): (Vec<_>, Vec<_>) = std::mem::take(input)
+                    .into_iter()
+                    .map(Self::with_metrics)
+                    .unzip();
+                *input = plans;
+                metrics
+            })
+            .unwrap_or_default();
+        let metrics = Rc::new(RefCell::new(OperatorMetrics::default()));
+        let tree = PlanMetrics {
+            name: self.name(),
+            metrics: metrics.clone(),
+            children,
+        };
+        (
+            PhysicalPlan::Metrics(MetricsExec {
+                input: vec![self],
+                metrics,
+            }),
+            tree,
+        )
+    }
+}
+
+/// Rows produced by an operator and the time spent in it, including the time spent in its
+/// inputs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OperatorMetrics {
+    pub rows: usize,
+    pub elapsed: Duration,
+}
+
+/// The metrics of every operator of a plan, in the shape of the plan.
+pub struct PlanMetrics {
+    name: &'static str,
+    metrics: Rc<RefCell<OperatorMetrics>>,
+    children: Vec<PlanMetrics>,
+}
+
+impl PlanMetrics {
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    pub fn metrics(&self) -> OperatorMetrics {
+        *self.metrics.borrow()
+    }
+
+    pub fn children(&self) -> &[PlanMetrics] {
+        &self.children
+    }
+
+    fn format(&self, indent: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metrics = self.metrics();
+        (0..indent).try_for_each(|_| write!(f, " \t"))?;
+        writeln!(
+            f,
+            "{} [rows={}, {:.1}ms]",
+            self.name,
+            metrics.rows,
+            metrics.elapsed.as_secs_f64() * 1000.0
+        )?;
+        self.children
+            .iter()
+            .try_for_each(|child| child.format(indent + 1, f))
+    }
+}
+
+impl fmt::Display for PlanMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.format(0, f)
+    }
+}
+
+/// Passes the batches of its input through and records the rows and the time it takes to
+/// produce them.
+pub struct MetricsExec {
+    input: Vec<PhysicalPlan>,
+    metrics: Rc<RefCell<OperatorMetrics>>,
+}
+
+pub struct MetricsIterator {
+    input_iter: Box<dyn Iterator<Item = Batch>>,
+    metrics: Rc<RefCell<OperatorMetrics>>,
+}
+
+impl Iterator for MetricsIterator {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = Instant::now();
+        let batch = self.input_iter.next();
+        let mut metrics = self.metrics.borrow_mut();
+        metrics.elapsed += start.elapsed();
+        if let Some(Ok(chunk)) = &batch {
+            metrics.rows += chunk.len();
         }
+        batch
+    }
+}
+
+impl MetricsExec {
+    fn schema(&self) -> &Schema {
+        self.input[0].schema()
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut input = self.input;
+        let input = input
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Metrics".to_string()))?;
+        // Operators like the hash join consume their input when they are executed.
+        let start = Instant::now();
+        let input_iter = input.execute()?;
+        self.metrics.borrow_mut().elapsed += start.elapsed();
+        Ok(Box::new(MetricsIterator {
+            input_iter,
+            metrics: self.metrics,
+        }))
     }
 }
 
 pub struct ScanExec {
     pub(crate) data_source: DataSource,
     pub(crate) projection: Option<Vec<String>>,
+    pub(crate) ordinals: Option<Vec<usize>>,
+    pub(crate) filters: Vec<PrunablePredicate>,
     pub(crate) schema: Schema,
+    pub(crate) batch_size: Option<usize>,
+    pub(crate) limit: Option<usize>,
 }
 
 impl ScanExec {
@@ -77,22 +407,108 @@ impl ScanExec {
             schema: schema,
             data_source: data_source,
             projection: projection,
+            ordinals: None,
+            filters: Vec::new(),
+            batch_size: None,
+            limit: None,
+        }
+    }
+
+    /// Positions of the scanned columns. Takes precedence over the projection by name.
+    pub fn with_ordinals(mut self, ordinals: Option<Vec<usize>>) -> Self {
+        self.ordinals = ordinals;
+        self
+    }
+
+    /// Predicates that the data source applies while reading.
+    pub fn with_filters(mut self, filters: Vec<PrunablePredicate>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Limits the number of rows of the batches produced by the scan. A batch size of zero is
+    /// rejected, as the scan would never make progress.
+    pub fn with_batch_size(mut self, batch_size: Option<usize>) -> Result<Self, Error> {
+        if batch_size == Some(0) {
+            return Err(Error::ZeroBatchSize);
         }
+        self.batch_size = batch_size;
+        Ok(self)
+    }
+
+    /// Number of rows after which the data source may stop reading.
+    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
     }
 }
 
-pub struct ScanIterator<I: Iterator<Item = Result<Chunk<Arc<dyn Array>>, ArrowError>>> {
+pub struct ScanIterator<I: Iterator<Item = Batch>> {
     input_iter: I,
+    schema: Schema,
+    batch_size: Option<usize>,
+    current: Option<(Chunk<Arc<dyn Array>>, usize)>,
 }
 
-impl<I: Iterator<Item = Result<Chunk<Arc<dyn Array>>, ArrowError>>> Iterator for ScanIterator<I> {
+impl<I: Iterator<Item = Batch>> Iterator for ScanIterator<I> {
     type Item = Batch;
     fn next(&mut self) -> Option<Self::Item> {
-        match self.input_iter.next() {
-            Some(chunk) => Some(chunk.map_err(|err| Error::ArrowError(err))),
-            None => None,
+        let batch_size = match self.batch_size {
+            Some(batch_size) => batch_size,
+            None => {
+                return self
+                    .input_iter
+                    .next()
+                    .map(|batch| batch.and_then(|chunk| validate_chunk(chunk, &self.schema)))
+            }
+        };
+        let (chunk, offset) = match self.current.take() {
+            Some(current) => current,
+            None => match self
+                .input_iter
+                .next()?
+                .and_then(|chunk| validate_chunk(chunk, &self.schema))
+            {
+                Ok(chunk) => (chunk, 0),
+                Err(err) => return Some(Err(err)),
+            },
+        };
+        let length = batch_size.min(chunk.len() - offset);
+        let batch = Chunk::new(
+            chunk
+                .arrays()
+                .iter()
+                .map(|array| Arc::from(array.slice(offset, length)))
+                .collect::<Vec<Arc<dyn Array>>>(),
+        );
+        if offset + length < chunk.len() {
+            self.current = Some((chunk, offset + length));
+        }
+        Some(Ok(batch))
+    }
+}
+
+/// Checks that the columns of a scanned chunk have the number and types declared by the schema
+/// of the scan, so that diverging files or chunks fail the scan instead of later operators.
+fn validate_chunk(chunk: Chunk<Arc<dyn Array>>, schema: &Schema) -> Batch {
+    if chunk.arrays().len() != schema.fields.len() {
+        return Err(Error::SchemaMismatch(format!(
+            "expected {} columns but got {}",
+            schema.fields.len(),
+            chunk.arrays().len()
+        )));
+    }
+    for (array, field) in chunk.arrays().iter().zip(&schema.fields) {
+        if array.data_type() != field.data_type() {
+            return Err(Error::SchemaMismatch(format!(
+                "column `{}` has type {:?} but {:?} was expected",
+                field.name,
+                array.data_type(),
+                field.data_type()
+            )));
         }
     }
+    Ok(chunk)
 }
 
 impl ScanExec {
@@ -104,7 +520,22 @@ impl ScanExec {
     }
     fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
         Ok(Box::new(ScanIterator {
-            input_iter: self.data_source.scan(self.projection),
+            input_iter: {
+                let ordinals = match (self.ordinals, self.projection) {
+                    (Some(ordinals), _) => Some(ordinals),
+                    (None, Some(projection)) => Some(self.data_source.ordinals(&projection)),
+                    (None, None) => None,
+                };
+                let skip_empty = !self.filters.is_empty();
+                let batches = self.data_source.scan(ordinals, self.filters, self.limit);
+                // Like a selection, a scan doesn't pass on batches its filters left empty.
+                batches.filter(move |batch| {
+                    !(skip_empty && matches!(batch, Ok(chunk) if chunk.is_empty()))
+                })
+            },
+            schema: self.schema,
+            batch_size: self.batch_size,
+            current: None,
         }))
     }
 }
@@ -120,16 +551,18 @@ impl ProjectionExec {
         input: Vec<PhysicalPlan>,
         exprs: Vec<Box<dyn PhysicalExpression>>,
         schema: Schema,
-    ) -> Self {
-        ProjectionExec {
+    ) -> Result<Self, Error> {
+        check_inputs("Projection", &input, 1)?;
+        Ok(ProjectionExec {
             schema: schema,
             input: input,
             exprs: exprs,
-        }
+        })
     }
 }
 pub struct ProjectionIterator<I: Iterator<Item = Batch>> {
     input_iter: I,
+    input_schema: Schema,
     exprs: Vec<Box<dyn PhysicalExpression>>,
 }
 
@@ -141,12 +574,13 @@ impl<I: Iterator<Item = Batch>> Iterator for ProjectionIterator<I> {
                 self.exprs
                     .iter()
                     .map(|expr| {
-                        expr.evaluate(&chunk).and_then(|col| match col {
-                            ColumnarValue::Array(array) => Ok(array),
-                            ColumnarValue::Scalar(scalar) => {
-                                Ok(scalar_to_array(scalar, chunk.len())?)
-                            }
-                        })
+                        expr.evaluate(&chunk, &self.input_schema)
+                            .and_then(|col| match col {
+                                ColumnarValue::Array(array) => Ok(array),
+                                ColumnarValue::Scalar(scalar) => {
+                                    Ok(scalar_to_array(scalar, chunk.len())?)
+                                }
+                            })
                     })
                     .collect::<Result<Vec<Arc<dyn Array>>, Error>>()
                     .map(|v| Chunk::new(v))
@@ -169,6 +603,7 @@ impl ProjectionExec {
             .pop()
             .ok_or(Error::MissingInputPhysicalPlan("Projection".to_string()))?;
         Ok(Box::new(ProjectionIterator {
+            input_schema: input.schema().clone(),
             input_iter: input.execute()?,
             exprs: self.exprs,
         }))
@@ -179,6 +614,7 @@ pub struct SelectionExec {
     input: Vec<PhysicalPlan>,
     schema: Schema,
     expr: Box<dyn PhysicalExpression>,
+    target_batch_size: Option<usize>,
 }
 
 impl SelectionExec {
@@ -186,45 +622,102 @@ impl SelectionExec {
         input: Vec<PhysicalPlan>,
         expr: Box<dyn PhysicalExpression>,
         schema: Schema,
-    ) -> Self {
-        SelectionExec {
+    ) -> Result<Self, Error> {
+        check_inputs("Selection", &input, 1)?;
+        Ok(SelectionExec {
             schema: schema,
             input: input,
             expr: expr,
-        }
+            target_batch_size: None,
+        })
+    }
+
+    /// Combines consecutive filtered batches until they hold at least `target_batch_size` rows.
+    pub fn with_target_batch_size(mut self, target_batch_size: Option<usize>) -> Self {
+        self.target_batch_size = target_batch_size;
+        self
     }
 }
 
 pub struct SelectionIterator<I: Iterator<Item = Batch>> {
     input_iter: I,
+    input_schema: Schema,
     expr: Box<dyn PhysicalExpression>,
+    target_batch_size: Option<usize>,
+    buffer: Vec<Chunk<Arc<dyn Array>>>,
+    buffered_rows: usize,
 }
 
-impl<I: Iterator<Item = Batch>> Iterator for SelectionIterator<I> {
-    type Item = Batch;
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.input_iter.next() {
-            Some(res) => Some(res.and_then(|chunk| {
-                let bitvector = self.expr.evaluate(&chunk).and_then(|col| match col {
+impl<I: Iterator<Item = Batch>> SelectionIterator<I> {
+    fn filter_chunk(&self, chunk: Chunk<Arc<dyn Array>>) -> Batch {
+        let bitvector =
+            self.expr
+                .evaluate(&chunk, &self.input_schema)
+                .and_then(|col| match col {
                     ColumnarValue::Array(array) => Ok(array),
                     ColumnarValue::Scalar(scalar) => Ok(scalar_to_array(scalar, chunk.len())?),
                 })?;
-                Ok(Chunk::new(
-                    compute::filter::filter_chunk(
-                        &chunk,
-                        bitvector
-                            .as_any()
-                            .downcast_ref::<BooleanArray>()
-                            .ok_or(Error::NoBooleanArrayForFilter)?,
-                    )
-                    .map_err(|err| Error::ArrowError(err))?
-                    .into_arrays()
-                    .into_iter()
-                    .map(|array| Arc::from(array) as Arc<dyn Array>)
-                    .collect::<Vec<Arc<dyn Array>>>(),
-                ))
-            })),
-            None => None,
+        let bitvector = bitvector
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .ok_or(Error::NoBooleanArrayForFilter)?;
+        // Rows for which the predicate is null are dropped.
+        let bitvector = match bitvector.validity() {
+            Some(validity) => {
+                BooleanArray::from_data(DataType::Boolean, bitvector.values() & validity, None)
+            }
+            None => bitvector.clone(),
+        };
+        Ok(Chunk::new(
+            compute::filter::filter_chunk(&chunk, &bitvector)
+                .map_err(Error::ArrowError)?
+                .into_arrays()
+                .into_iter()
+                .map(|array| Arc::from(array) as Arc<dyn Array>)
+                .collect::<Vec<Arc<dyn Array>>>(),
+        ))
+    }
+
+    fn flush(&mut self) -> Batch {
+        self.buffered_rows = 0;
+        concat_chunks(&std::mem::take(&mut self.buffer))
+    }
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for SelectionIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        let target_batch_size = match self.target_batch_size {
+            Some(target_batch_size) => target_batch_size,
+            // Batches without any selected rows are skipped.
+            None => loop {
+                match self
+                    .input_iter
+                    .next()?
+                    .and_then(|chunk| self.filter_chunk(chunk))
+                {
+                    Ok(chunk) if chunk.is_empty() => continue,
+                    res => return Some(res),
+                }
+            },
+        };
+        loop {
+            match self.input_iter.next() {
+                Some(res) => match res.and_then(|chunk| self.filter_chunk(chunk)) {
+                    Ok(chunk) => {
+                        if !chunk.is_empty() {
+                            self.buffered_rows += chunk.len();
+                            self.buffer.push(chunk);
+                        }
+                        if self.buffered_rows >= target_batch_size {
+                            return Some(self.flush());
+                        }
+                    }
+                    Err(err) => return Some(Err(err)),
+                },
+                None if self.buffer.is_empty() => return None,
+                None => return Some(self.flush()),
+            }
         }
     }
 }
@@ -242,8 +735,12 @@ impl SelectionExec {
             .pop()
             .ok_or(Error::MissingInputPhysicalPlan("Selection".to_string()))?;
         Ok(Box::new(SelectionIterator {
+            input_schema: input.schema().clone(),
             input_iter: input.execute()?,
             expr: self.expr,
+            target_batch_size: self.target_batch_size,
+            buffer: Vec::new(),
+            buffered_rows: 0,
         }))
     }
 }
@@ -253,6 +750,7 @@ pub struct AggregateExec {
     schema: Schema,
     group_exprs: Vec<Box<dyn PhysicalExpression>>,
     agg_exprs: Vec<Box<dyn PhysicalAggregateExpression>>,
+    dictionary_keys: bool,
 }
 
 impl AggregateExec {
@@ -261,16 +759,164 @@ impl AggregateExec {
         group_exprs: Vec<Box<dyn PhysicalExpression>>,
         agg_exprs: Vec<Box<dyn PhysicalAggregateExpression>>,
         schema: Schema,
-    ) -> Self {
-        AggregateExec {
+    ) -> Result<Self, Error> {
+        check_inputs("Aggregate", &input, 1)?;
+        Ok(AggregateExec {
             schema: schema,
             input: input,
             group_exprs: group_exprs,
             agg_exprs: agg_exprs,
+            dictionary_keys: false,
+        })
+    }
+
+    /// Aggregates every input separately, e.g. the partitions of a table, and merges the partial
+    /// results of each group before computing the final values. The inputs need the same schema.
+    pub fn partitioned(
+        input: Vec<PhysicalPlan>,
+        group_exprs: Vec<Box<dyn PhysicalExpression>>,
+        agg_exprs: Vec<Box<dyn PhysicalAggregateExpression>>,
+        schema: Schema,
+    ) -> Result<Self, Error> {
+        if input.is_empty() {
+            return Err(Error::InputCountMismatch("Aggregate".to_string(), 1, 0));
+        }
+        Ok(AggregateExec {
+            schema,
+            input,
+            group_exprs,
+            agg_exprs,
+            dictionary_keys: false,
+        })
+    }
+
+    /// Groups on a single string key by its dictionary codes instead of hashing the strings.
+    pub fn with_dictionary_keys(mut self, dictionary_keys: bool) -> Self {
+        self.dictionary_keys = dictionary_keys;
+        self
+    }
+}
+
+/// Assigns every distinct string a code in the order the strings are first seen.
+#[derive(Default)]
+struct StringDictionary {
+    codes: HashMap<String, u32>,
+    values: Vec<String>,
+}
+
+impl StringDictionary {
+    fn encode(&mut self, strings: &Utf8Array<i32>) -> PrimitiveArray<u32> {
+        strings
+            .iter()
+            .map(|x| {
+                x.map(|x| match self.codes.get(x) {
+                    Some(code) => *code,
+                    None => {
+                        let code = self.values.len() as u32;
+                        self.codes.insert(x.to_string(), code);
+                        self.values.push(x.to_string());
+                        code
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// The string of a group key, which is either a code or `NULL_HASH` for null.
+    fn decode(&self, key: u64) -> Arc<dyn Array> {
+        if key == NULL_HASH {
+            return Arc::new(Utf8Array::<i32>::from([None::<&str>]));
         }
+        Arc::new(Utf8Array::<i32>::from_slice([&self.values[key as usize]]))
     }
 }
 
+/// Creates an empty builder for arrays of `data_type` with room for `capacity` values.
+pub(crate) fn new_builder(
+    data_type: &DataType,
+    capacity: usize,
+) -> Result<Box<dyn MutableArray>, Error> {
+    Ok(match data_type.to_physical_type() {
+        PhysicalType::Primitive(PrimitiveType::Int32) => Box::new(
+            MutablePrimitiveArray::<i32>::with_capacity_from(capacity, data_type.clone()),
+        ),
+        PhysicalType::Primitive(PrimitiveType::Int64) => Box::new(
+            MutablePrimitiveArray::<i64>::with_capacity_from(capacity, data_type.clone()),
+        ),
+        PhysicalType::Primitive(PrimitiveType::Float32) => Box::new(
+            MutablePrimitiveArray::<f32>::with_capacity_from(capacity, data_type.clone()),
+        ),
+        PhysicalType::Primitive(PrimitiveType::Float64) => Box::new(
+            MutablePrimitiveArray::<f64>::with_capacity_from(capacity, data_type.clone()),
+        ),
+        PhysicalType::Utf8 => Box::new(MutableUtf8Array::<i32>::with_capacity(capacity)),
+        PhysicalType::LargeUtf8 => Box::new(MutableUtf8Array::<i64>::with_capacity(capacity)),
+        PhysicalType::Boolean => Box::new(MutableBooleanArray::with_capacity(capacity)),
+        t => return Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+    })
+}
+
+/// Appends the values of `array` to a builder created by `new_builder`. The array needs the
+/// physical type of the builder, except for null arrays, which append nulls to any builder.
+pub(crate) fn append_to_builder(
+    builder: &mut dyn MutableArray,
+    array: &dyn Array,
+) -> Result<(), Error> {
+    fn downcast<T: 'static>(builder: &mut dyn MutableArray) -> Result<&mut T, Error> {
+        builder
+            .as_mut_any()
+            .downcast_mut::<T>()
+            .ok_or(Error::DowncastError)
+    }
+    fn append_primitive<T: NativeType>(
+        builder: &mut dyn MutableArray,
+        array: &dyn Array,
+    ) -> Result<(), Error> {
+        let array = array
+            .as_any()
+            .downcast_ref::<PrimitiveArray<T>>()
+            .ok_or(Error::DowncastError)?;
+        downcast::<MutablePrimitiveArray<T>>(builder)?.extend(array.iter().map(|x| x.copied()));
+        Ok(())
+    }
+    fn append_utf8<O: Offset>(
+        builder: &mut dyn MutableArray,
+        array: &dyn Array,
+    ) -> Result<(), Error> {
+        let array = array
+            .as_any()
+            .downcast_ref::<Utf8Array<O>>()
+            .ok_or(Error::DowncastError)?;
+        let builder = downcast::<MutableUtf8Array<O>>(builder)?;
+        array.iter().for_each(|x| builder.push(x));
+        Ok(())
+    }
+    if array.data_type() == &DataType::Null {
+        (0..array.len()).for_each(|_| builder.push_null());
+        return Ok(());
+    }
+    match builder.data_type().to_physical_type() {
+        PhysicalType::Primitive(PrimitiveType::Int32) => append_primitive::<i32>(builder, array),
+        PhysicalType::Primitive(PrimitiveType::Int64) => append_primitive::<i64>(builder, array),
+        PhysicalType::Primitive(PrimitiveType::Float32) => append_primitive::<f32>(builder, array),
+        PhysicalType::Primitive(PrimitiveType::Float64) => append_primitive::<f64>(builder, array),
+        PhysicalType::Utf8 => append_utf8::<i32>(builder, array),
+        PhysicalType::LargeUtf8 => append_utf8::<i64>(builder, array),
+        PhysicalType::Boolean => {
+            let array = array
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .ok_or(Error::DowncastError)?;
+            downcast::<MutableBooleanArray>(builder)?.extend(array.iter());
+            Ok(())
+        }
+        t => Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+    }
+}
+
+/// The accumulators and key values of every group by the hash of its keys.
+type Groups = HashMap<u64, (Vec<Box<dyn Accumulator>>, Vec<Arc<dyn Array>>)>;
+
 pub struct AggregateIterator {
     output: Option<Batch>,
 }
@@ -290,193 +936,160 @@ impl AggregateExec {
         Some(&self.input)
     }
     fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
-        let mut vec = self.input;
-        let input = vec
-            .pop()
-            .ok_or(Error::MissingInputPhysicalPlan("Aggregate".to_string()))?;
-        let mut hashmap = HashMap::new();
-        input
-            .execute()?
-            .map(|res| match res {
-                Ok(batch) => {
-                    let length = batch.len();
-                    let mut hashset = HashSet::new();
-                    let group_keys = Chunk::new(
-                        self.group_exprs
-                            .iter()
-                            .map(|expr| expr.evaluate(&batch).map(|x| x.to_array(length)))
-                            .collect::<Result<Vec<Arc<dyn Array>>, Error>>()?,
-                    );
-                    let group_hashes = group_keys.iter().fold(
-                        compute::hash::hash_primitive::<u64>(&PrimitiveArray::new(
-                            DataType::UInt64,
-                            Buffer::new_zeroed(length),
-                            None,
-                        )),
-                        |acc: PrimitiveArray<u64>, x| {
-                            let new = compute::hash::hash(x.borrow()).unwrap();
-                            acc.add(&new)
-                        },
-                    );
-                    let agg_input = self
-                        .agg_exprs
-                        .iter()
-                        .map(|expr| expr.evaluate(&batch))
-                        .collect::<Result<Vec<ColumnarValue>, Error>>()?;
-                    group_hashes
-                        .iter()
-                        .enumerate()
-                        .map(|(i, key)| match key {
-                            Some(key) => {
-                                if !hashset.contains(key) {
-                                    hashset.insert(key);
-                                    let validity = Bitmap::from_trusted_len_iter(
-                                        compute::comparison::eq_scalar(
-                                            &group_hashes,
-                                            &PrimitiveScalar::new(
-                                                DataType::UInt64,
-                                                Some(key.clone()),
-                                            ),
-                                        )
-                                        .values_iter(),
-                                    );
-                                    if !hashmap.contains_key(key) {
-                                        let accumulators = self
-                                            .agg_exprs
-                                            .iter()
-                                            .enumerate()
-                                            .map(|(i, x)| x.create_accumulator(i))
-                                            .collect::<Vec<_>>();
-                                        let group_keys = group_keys
-                                            .iter()
-                                            .map(|x| x.slice(i, 1))
-                                            .map(|y| Arc::from(y))
-                                            .collect::<Vec<_>>();
-                                        hashmap.insert(*key, (accumulators, group_keys));
-                                    }
-                                    hashmap
-                                        .get_mut(key)
-                                        .unwrap()
-                                        .0
-                                        .iter_mut()
-                                        .map(|acc| acc.accumulate(&agg_input, Some(&validity)))
-                                        .collect::<Result<Vec<()>, Error>>()?;
-                                };
-                                Ok(())
-                            }
-                            None => Ok(()),
-                        })
-                        .collect::<Result<Vec<()>, Error>>()?;
-                    Ok(())
-                }
-                Err(e) => Err(e),
-            })
-            .collect::<Result<Vec<()>, Error>>()?;
-        let rows = hashmap.len();
-        let mut iter = hashmap.into_values();
-        let (accs, mut groups) = iter.next().ok_or(Error::EmptyHashmapForAggregate)?;
-        accs.into_iter()
-            .map(|x| {
-                groups.push(x.final_value()?.to_array(1));
-                Ok(())
-            })
-            .collect::<Result<Vec<()>, Error>>()?;
-        let mut columns = groups
-            .into_iter()
-            .map(|col| match col.data_type().to_physical_type() {
-                PhysicalType::Primitive(PrimitiveType::Int32) => {
-                    let mut mutable_array =
-                        MutablePrimitiveArray::with_capacity_from(rows, DataType::Int32);
-                    mutable_array.extend_from_slice(
-                        col.as_any()
-                            .downcast_ref::<PrimitiveArray<i32>>()
-                            .unwrap()
-                            .values(),
-                    );
-                    Ok(Box::new(mutable_array) as Box<dyn MutableArray>)
-                }
-                PhysicalType::Primitive(PrimitiveType::Float64) => {
-                    let mut mutable_array =
-                        MutablePrimitiveArray::with_capacity_from(rows, DataType::Float64);
-                    mutable_array.extend_from_slice(
-                        col.as_any()
-                            .downcast_ref::<PrimitiveArray<f64>>()
-                            .unwrap()
-                            .values(),
-                    );
-                    Ok(Box::new(mutable_array) as Box<dyn MutableArray>)
-                }
-                PhysicalType::Utf8 => {
-                    let mut mutable_array = MutableUtf8Array::<i32>::with_capacity(rows);
-                    mutable_array.extend_trusted_len(
-                        col.as_any()
-                            .downcast_ref::<Utf8Array<i32>>()
-                            .unwrap()
-                            .iter(),
-                    );
-                    Ok(Box::new(mutable_array) as Box<dyn MutableArray>)
-                }
-                PhysicalType::Boolean => {
-                    let mut mutable_array = MutableBooleanArray::with_capacity(rows);
-                    mutable_array.extend_trusted_len(
-                        col.as_any().downcast_ref::<BooleanArray>().unwrap().iter(),
-                    );
-                    Ok(Box::new(mutable_array) as Box<dyn MutableArray>)
-                }
-                t => Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        iter.for_each(|(accs, mut groups)| {
-            accs.into_iter()
-                .for_each(|x| groups.push(x.final_value().unwrap().to_array(1)));
-            groups
-                .into_iter()
-                .zip(columns.iter_mut())
-                .for_each(|(new, col)| match col.data_type().to_physical_type() {
-                    PhysicalType::Primitive(PrimitiveType::Int32) => {
-                        col.as_mut_any()
-                            .downcast_mut::<MutablePrimitiveArray<i32>>()
-                            .unwrap()
-                            .extend_from_slice(
-                                new.as_any()
-                                    .downcast_ref::<PrimitiveArray<i32>>()
-                                    .unwrap()
-                                    .values(),
-                            );
-                    }
-                    PhysicalType::Primitive(PrimitiveType::Float64) => {
-                        col.as_mut_any()
-                            .downcast_mut::<MutablePrimitiveArray<f64>>()
-                            .unwrap()
-                            .extend_from_slice(
-                                new.as_any()
-                                    .downcast_ref::<PrimitiveArray<f64>>()
-                                    .unwrap()
-                                    .values(),
-                            );
-                    }
-                    PhysicalType::Utf8 => {
-                        col.as_mut_any()
-                            .downcast_mut::<MutableUtf8Array<i32>>()
-                            .unwrap()
-                            .extend_trusted_len(
-                                new.as_any()
+        if self.input.is_empty() {
+            return Err(Error::MissingInputPhysicalPlan("Aggregate".to_string()));
+        }
+        let mut hashmap = Groups::new();
+        // Keys in the order their groups were first seen, which is the order of the output rows.
+        let mut order = Vec::new();
+        // The dictionary is shared by all partitions, so that equal strings get equal codes.
+        let mut dictionary = StringDictionary::default();
+        let encode = self.dictionary_keys
+            && self.group_exprs.len() == 1
+            && self.schema.fields[0].data_type == DataType::Utf8;
+        for input in self.input {
+            let input_schema = input.schema().clone();
+            let mut partial = Groups::new();
+            let mut partial_order = Vec::new();
+            input
+                .execute()?
+                .map(|res| match res {
+                    Ok(batch) => {
+                        let length = batch.len();
+                        let mut hashset = HashSet::new();
+                        let group_keys = Chunk::new(
+                            self.group_exprs
+                                .iter()
+                                .map(|expr| {
+                                    expr.evaluate(&batch, &input_schema)
+                                        .map(|x| x.to_array(length))
+                                })
+                                .collect::<Result<Vec<Arc<dyn Array>>, Error>>()?,
+                        );
+                        let (group_keys, group_hashes) = if group_keys.arrays().is_empty() {
+                            (group_keys, PrimitiveArray::from_vec(vec![0; length]))
+                        } else if encode {
+                            let codes = dictionary.encode(
+                                group_keys[0]
+                                    .as_any()
                                     .downcast_ref::<Utf8Array<i32>>()
-                                    .unwrap()
-                                    .iter(),
+                                    .ok_or(Error::DowncastError)?,
                             );
-                    }
-                    PhysicalType::Boolean => {
-                        col.as_mut_any()
-                            .downcast_mut::<MutableBooleanArray>()
-                            .unwrap()
-                            .extend_trusted_len(
-                                new.as_any().downcast_ref::<BooleanArray>().unwrap().iter(),
+                            let hashes = PrimitiveArray::from_vec(
+                                codes
+                                    .iter()
+                                    .map(|x| x.map_or(NULL_HASH, |x| *x as u64))
+                                    .collect(),
                             );
+                            (Chunk::new(vec![Arc::new(codes) as Arc<dyn Array>]), hashes)
+                        } else {
+                            let hashes = group_hashes(&group_keys)?;
+                            (group_keys, hashes)
+                        };
+                        let agg_input = self
+                            .agg_exprs
+                            .iter()
+                            .map(|expr| expr.evaluate(&batch, &input_schema))
+                            .collect::<Result<Vec<ColumnarValue>, Error>>()?;
+                        group_hashes
+                            .iter()
+                            .enumerate()
+                            .map(|(i, key)| match key {
+                                Some(key) => {
+                                    if !hashset.contains(key) {
+                                        hashset.insert(key);
+                                        let validity = Bitmap::from_trusted_len_iter(
+                                            compute::comparison::eq_scalar(
+                                                &group_hashes,
+                                                &PrimitiveScalar::new(
+                                                    DataType::UInt64,
+                                                    Some(key.clone()),
+                                                ),
+                                            )
+                                            .values_iter(),
+                                        );
+                                        if !partial.contains_key(key) {
+                                            let accumulators = self
+                                                .agg_exprs
+                                                .iter()
+                                                .enumerate()
+                                                .map(|(i, x)| x.create_accumulator(i))
+                                                .collect::<Vec<_>>();
+                                            let group_keys = group_keys
+                                                .iter()
+                                                .map(|x| x.slice(i, 1))
+                                                .map(|y| Arc::from(y))
+                                                .collect::<Vec<_>>();
+                                            partial.insert(*key, (accumulators, group_keys));
+                                            partial_order.push(*key);
+                                        }
+                                        partial
+                                            .get_mut(key)
+                                            .unwrap()
+                                            .0
+                                            .iter_mut()
+                                            .map(|acc| acc.accumulate(&agg_input, Some(&validity)))
+                                            .collect::<Result<Vec<()>, Error>>()?;
+                                    };
+                                    Ok(())
+                                }
+                                None => Ok(()),
+                            })
+                            .collect::<Result<Vec<()>, Error>>()?;
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                })
+                .collect::<Result<Vec<()>, Error>>()?;
+            // Groups of earlier partitions keep their position in the output.
+            for key in partial_order {
+                let (accumulators, group_keys) = partial.remove(&key).unwrap();
+                match hashmap.get_mut(&key) {
+                    Some((merged, _)) => merged
+                        .iter_mut()
+                        .zip(accumulators)
+                        .try_for_each(|(acc, other)| acc.merge(other))?,
+                    None => {
+                        hashmap.insert(key, (accumulators, group_keys));
+                        order.push(key);
                     }
-                    _ => (),
-                });
+                }
+            }
+        }
+        let rows = hashmap.len();
+        let mut iter = order.into_iter().map(|key| {
+            let (accs, groups) = hashmap.remove(&key).unwrap();
+            let mut values = if encode {
+                vec![dictionary.decode(key)]
+            } else {
+                groups
+            };
+            for acc in accs {
+                values.push(acc.final_value()?.to_array(1));
+            }
+            Ok(values)
         });
+        let first: Vec<Arc<dyn Array>> = iter.next().ok_or(Error::EmptyHashmapForAggregate)??;
+        // The values of the first group determine the column types. Aggregates of groups
+        // without values can be null arrays, their type is taken from the schema.
+        let mut columns = first
+            .iter()
+            .zip(self.schema.fields.iter())
+            .map(|(value, field)| match value.data_type() {
+                DataType::Null => new_builder(&field.data_type, rows),
+                data_type => new_builder(data_type, rows),
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        std::iter::once(Ok(first)).chain(iter).try_for_each(
+            |values: Result<Vec<Arc<dyn Array>>, Error>| {
+                values?
+                    .iter()
+                    .zip(columns.iter_mut())
+                    .try_for_each(|(value, column)| {
+                        append_to_builder(column.as_mut(), value.as_ref())
+                    })
+            },
+        )?;
         let columns = Chunk::new(
             columns
                 .into_iter()
@@ -488,3 +1101,1100 @@ impl AggregateExec {
         }))
     }
 }
+
+pub struct SampleExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    fraction: f64,
+    seed: u64,
+}
+
+impl SampleExec {
+    pub fn new(
+        input: Vec<PhysicalPlan>,
+        fraction: f64,
+        seed: u64,
+        schema: Schema,
+    ) -> Result<Self, Error> {
+        check_inputs("Sample", &input, 1)?;
+        Ok(SampleExec {
+            input,
+            schema,
+            fraction,
+            seed,
+        })
+    }
+}
+
+/// SplitMix64 pseudo random number generator, which is enough to draw reproducible samples.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed float in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+pub struct SampleIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    fraction: f64,
+    rng: SplitMix64,
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for SampleIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.input_iter.next().map(|res| {
+            res.and_then(|chunk| {
+                let mask = BooleanArray::from_slice(
+                    (0..chunk.len())
+                        .map(|_| self.rng.next_f64() < self.fraction)
+                        .collect::<Vec<bool>>(),
+                );
+                Ok(Chunk::new(
+                    compute::filter::filter_chunk(&chunk, &mask)
+                        .map_err(Error::ArrowError)?
+                        .into_arrays()
+                        .into_iter()
+                        .map(Arc::from)
+                        .collect::<Vec<Arc<dyn Array>>>(),
+                ))
+            })
+        })
+    }
+}
+
+impl SampleExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Sample".to_string()))?;
+        Ok(Box::new(SampleIterator {
+            input_iter: input.execute()?,
+            fraction: self.fraction,
+            rng: SplitMix64 { state: self.seed },
+        }))
+    }
+}
+
+/// Keeps the first `n` rows of the input and stops pulling batches from it afterwards.
+pub struct LimitExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    n: usize,
+}
+
+impl LimitExec {
+    pub fn new(input: Vec<PhysicalPlan>, n: usize, schema: Schema) -> Result<Self, Error> {
+        check_inputs("Limit", &input, 1)?;
+        Ok(LimitExec { input, schema, n })
+    }
+}
+
+pub struct LimitIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    remaining: usize,
+}
+
+impl<I: Iterator<Item = Batch>> LimitIterator<I> {
+    pub(crate) fn new(input_iter: I, n: usize) -> Self {
+        LimitIterator {
+            input_iter,
+            remaining: n,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for LimitIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let chunk = match self.input_iter.next()? {
+            Ok(chunk) => chunk,
+            Err(err) => return Some(Err(err)),
+        };
+        let length = self.remaining.min(chunk.len());
+        self.remaining -= length;
+        Some(Ok(Chunk::new(
+            chunk
+                .arrays()
+                .iter()
+                .map(|array| Arc::from(array.slice(0, length)))
+                .collect::<Vec<Arc<dyn Array>>>(),
+        )))
+    }
+}
+
+impl LimitExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Limit".to_string()))?;
+        Ok(Box::new(LimitIterator::new(input.execute()?, self.n)))
+    }
+}
+
+pub struct RepartitionExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    exprs: Vec<Box<dyn PhysicalExpression>>,
+    partitions: usize,
+}
+
+impl RepartitionExec {
+    pub fn new(
+        input: Vec<PhysicalPlan>,
+        exprs: Vec<Box<dyn PhysicalExpression>>,
+        partitions: usize,
+        schema: Schema,
+    ) -> Result<Self, Error> {
+        check_inputs("Repartition", &input, 1)?;
+        if partitions == 0 {
+            return Err(Error::ZeroPartitions);
+        }
+        Ok(RepartitionExec {
+            input,
+            schema,
+            exprs,
+            partitions,
+        })
+    }
+
+    /// Executes the input and routes every row to one of the partitions by the hash of the
+    /// partitioning expressions. Rows with equal keys, including rows with null keys, end up in
+    /// the same partition, so that every partition can be aggregated independently.
+    pub fn execute_partitions(self) -> Result<Vec<Box<dyn Iterator<Item = Batch>>>, Error> {
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Repartition".to_string()))?;
+        let input_schema = input.schema().clone();
+        let mut partitions: Vec<Vec<Chunk<Arc<dyn Array>>>> = vec![Vec::new(); self.partitions];
+        for batch in input.execute()? {
+            let batch = batch?;
+            let length = batch.len();
+            let keys = Chunk::new(
+                self.exprs
+                    .iter()
+                    .map(|expr| {
+                        expr.evaluate(&batch, &input_schema)
+                            .map(|x| x.to_array(length))
+                    })
+                    .collect::<Result<Vec<Arc<dyn Array>>, Error>>()?,
+            );
+            let targets = if keys.arrays().is_empty() {
+                vec![0; length]
+            } else {
+                group_hashes(&keys)?
+                    .values()
+                    .iter()
+                    .map(|hash| (hash % self.partitions as u64) as usize)
+                    .collect()
+            };
+            for (i, partition) in partitions.iter_mut().enumerate() {
+                let mask =
+                    BooleanArray::from_trusted_len_values_iter(targets.iter().map(|x| *x == i));
+                let chunk =
+                    compute::filter::filter_chunk(&batch, &mask).map_err(Error::ArrowError)?;
+                if !chunk.is_empty() {
+                    partition.push(Chunk::new(
+                        chunk
+                            .into_arrays()
+                            .into_iter()
+                            .map(Arc::from)
+                            .collect::<Vec<Arc<dyn Array>>>(),
+                    ));
+                }
+            }
+        }
+        Ok(partitions
+            .into_iter()
+            .map(|chunks| Box::new(chunks.into_iter().map(Ok)) as Box<dyn Iterator<Item = Batch>>)
+            .collect())
+    }
+}
+
+impl RepartitionExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    /// Streams the partitions one after another.
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        Ok(Box::new(self.execute_partitions()?.into_iter().flatten()))
+    }
+}
+
+pub struct ValuesExec {
+    rows: Vec<Vec<Box<dyn PhysicalExpression>>>,
+    schema: Schema,
+}
+
+impl ValuesExec {
+    pub fn new(rows: Vec<Vec<Box<dyn PhysicalExpression>>>, schema: Schema) -> Self {
+        ValuesExec { rows, schema }
+    }
+}
+
+impl ValuesExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        None
+    }
+    /// Every row needs a value for each field of the schema, no more and no fewer.
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        if let Some(row) = self
+            .rows
+            .iter()
+            .find(|row| row.len() != self.schema.fields.len())
+        {
+            return Err(Error::InvalidValues(format!(
+                "a row has {} values but the schema has {} fields",
+                row.len(),
+                self.schema.fields.len()
+            )));
+        }
+        let empty = Chunk::new(vec![]);
+        let columns = self
+            .schema
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let values = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let expr = &row[i];
+                        let value = expr.evaluate(&empty, &Schema::default())?;
+                        if value.data_type() != field.data_type {
+                            return Err(Error::InvalidValues(format!(
+                                "value `{}` of field `{}` is of type {:?} instead of {:?}",
+                                expr,
+                                field.name,
+                                value.data_type(),
+                                field.data_type
+                            )));
+                        }
+                        Ok(value.to_array(1))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                if values.is_empty() {
+                    return Ok(Arc::from(new_empty_array(field.data_type.clone())));
+                }
+                compute::concatenate::concatenate(
+                    &values.iter().map(|x| x.as_ref()).collect::<Vec<_>>(),
+                )
+                .map(Arc::from)
+                .map_err(Error::ArrowError)
+            })
+            .collect::<Result<Vec<Arc<dyn Array>>, Error>>()?;
+        Ok(Box::new(std::iter::once(Ok(Chunk::new(columns)))))
+    }
+}
+
+/// Inner equi-join. The rows of the build side are collected into a hash table over the hashes
+/// of their keys, the rows of the other side are streamed and probe the table. Candidates with
+/// the same hash are only joined if all keys are equal. The output contains the left columns
+/// followed by the right columns.
+pub struct HashJoinExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    on: Vec<(usize, usize)>,
+    build_left: bool,
+    null_equals_null: bool,
+}
+
+impl HashJoinExec {
+    /// `input` contains the left and the right input, `on` the positions of the key columns in
+    /// them.
+    pub fn new(
+        input: Vec<PhysicalPlan>,
+        on: Vec<(usize, usize)>,
+        schema: Schema,
+    ) -> Result<Self, Error> {
+        check_inputs("HashJoin", &input, 2)?;
+        Ok(HashJoinExec {
+            input,
+            schema,
+            on,
+            build_left: false,
+            null_equals_null: false,
+        })
+    }
+
+    /// Builds the hash table from the left instead of the right input.
+    pub fn with_build_left(mut self, build_left: bool) -> Self {
+        self.build_left = build_left;
+        self
+    }
+
+    pub fn build_left(&self) -> bool {
+        self.build_left
+    }
+
+    /// Lets null join keys match other null keys. By default rows with a null key don't match
+    /// any row.
+    pub fn with_null_equals_null(mut self, null_equals_null: bool) -> Self {
+        self.null_equals_null = null_equals_null;
+        self
+    }
+
+    pub fn null_equals_null(&self) -> bool {
+        self.null_equals_null
+    }
+}
+
+fn select_columns(chunk: &Chunk<Arc<dyn Array>>, columns: &[usize]) -> Chunk<Arc<dyn Array>> {
+    Chunk::new(columns.iter().map(|i| chunk[*i].clone()).collect())
+}
+
+/// Takes the rows at `indices` from every column of `chunk`.
+fn take_rows(
+    chunk: &Chunk<Arc<dyn Array>>,
+    indices: Vec<u32>,
+) -> Result<Vec<Arc<dyn Array>>, Error> {
+    let indices = PrimitiveArray::from_vec(indices);
+    chunk
+        .iter()
+        .map(|array| {
+            compute::take::take(array.as_ref(), &indices)
+                .map(Arc::from)
+                .map_err(Error::ArrowError)
+        })
+        .collect()
+}
+
+pub struct HashJoinIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    build: Chunk<Arc<dyn Array>>,
+    table: HashMap<u64, Vec<u32>>,
+    build_keys: Vec<usize>,
+    probe_keys: Vec<usize>,
+    build_left: bool,
+    null_equals_null: bool,
+}
+
+/// Hashes the join keys of every row. Unless null keys are equal, rows with a null key hash to
+/// null.
+fn join_hashes(
+    columns: &Chunk<Arc<dyn Array>>,
+    null_equals_null: bool,
+) -> Result<PrimitiveArray<u64>, Error> {
+    match null_equals_null {
+        true => group_hashes(columns),
+        false => row_hashes(columns),
+    }
+}
+
+impl<I: Iterator<Item = Batch>> HashJoinIterator<I> {
+    fn probe(&self, chunk: Chunk<Arc<dyn Array>>) -> Batch {
+        let probe_keys = select_columns(&chunk, &self.probe_keys);
+        let build_keys = select_columns(&self.build, &self.build_keys);
+        let hashes = join_hashes(&probe_keys, self.null_equals_null)?;
+        let comparators = probe_keys
+            .iter()
+            .zip(build_keys.iter())
+            .map(|(probe, build)| {
+                build_compare(probe.as_ref(), build.as_ref()).map_err(Error::ArrowError)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut probe_indices = Vec::new();
+        let mut build_indices = Vec::new();
+        hashes.iter().enumerate().for_each(|(i, hash)| {
+            // Rows with a null key hash to null and don't match any row.
+            if let Some(candidates) = hash.and_then(|hash| self.table.get(hash)) {
+                candidates
+                    .iter()
+                    .filter(|j| keys_equal(&probe_keys, &build_keys, &comparators, i, **j as usize))
+                    .for_each(|j| {
+                        probe_indices.push(i as u32);
+                        build_indices.push(*j);
+                    })
+            }
+        });
+        let probe = take_rows(&chunk, probe_indices)?;
+        let build = take_rows(&self.build, build_indices)?;
+        Ok(Chunk::new(if self.build_left {
+            build.into_iter().chain(probe).collect()
+        } else {
+            probe.into_iter().chain(build).collect()
+        }))
+    }
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for HashJoinIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.input_iter
+            .next()
+            .map(|res| res.and_then(|chunk| self.probe(chunk)))
+    }
+}
+
+impl HashJoinExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut input = self.input;
+        let right = input
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("HashJoin".to_string()))?;
+        let left = input
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("HashJoin".to_string()))?;
+        let (build, probe) = if self.build_left {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        let (build_keys, probe_keys): (Vec<usize>, Vec<usize>) = self
+            .on
+            .iter()
+            .map(|(left, right)| match self.build_left {
+                true => (*left, *right),
+                false => (*right, *left),
+            })
+            .unzip();
+
+        let build_fields = build.schema().fields.clone();
+        let chunks = build.execute()?.collect::<Result<Vec<_>, Error>>()?;
+        let build = match chunks.is_empty() {
+            true => Chunk::new(
+                build_fields
+                    .iter()
+                    .map(|field| Arc::from(new_empty_array(field.data_type.clone())))
+                    .collect(),
+            ),
+            false => concat_chunks(&chunks)?,
+        };
+        let mut table: HashMap<u64, Vec<u32>> = HashMap::new();
+        join_hashes(&select_columns(&build, &build_keys), self.null_equals_null)?
+            .iter()
+            .enumerate()
+            .for_each(|(i, hash)| {
+                if let Some(hash) = hash {
+                    table.entry(*hash).or_default().push(i as u32);
+                }
+            });
+
+        Ok(Box::new(HashJoinIterator {
+            input_iter: probe.execute()?,
+            build,
+            table,
+            build_keys,
+            probe_keys,
+            build_left: self.build_left,
+            null_equals_null: self.null_equals_null,
+        }))
+    }
+}
+
+/// Inner equi-join of two inputs that are both sorted on their keys in ascending order with
+/// nulls first. The inputs are merged with a cursor on each side: the side with the smaller key
+/// advances, equal keys join every row of the left group with every row of the right group.
+/// Only the current batch of each input is held in memory, plus the batches a group of equal
+/// keys spans. The output contains the left columns followed by the right columns.
+pub struct SortMergeJoinExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    on: Vec<(usize, usize)>,
+    null_equals_null: bool,
+}
+
+impl SortMergeJoinExec {
+    /// `input` contains the left and the right input, `on` the positions of the key columns in
+    /// them. Both inputs have to be sorted on their keys in the order of `on`.
+    pub fn new(
+        input: Vec<PhysicalPlan>,
+        on: Vec<(usize, usize)>,
+        schema: Schema,
+    ) -> Result<Self, Error> {
+        check_inputs("SortMergeJoin", &input, 2)?;
+        Ok(SortMergeJoinExec {
+            input,
+            schema,
+            on,
+            null_equals_null: false,
+        })
+    }
+
+    /// Lets null join keys match other null keys. By default rows with a null key don't match
+    /// any row.
+    pub fn with_null_equals_null(mut self, null_equals_null: bool) -> Self {
+        self.null_equals_null = null_equals_null;
+        self
+    }
+
+    pub fn null_equals_null(&self) -> bool {
+        self.null_equals_null
+    }
+}
+
+/// Compares the keys of row `i` of `left` with row `j` of `right`. Nulls are equal to each
+/// other and smaller than any value.
+fn compare_keys(
+    left: &Chunk<Arc<dyn Array>>,
+    right: &Chunk<Arc<dyn Array>>,
+    comparators: &[DynComparator],
+    i: usize,
+    j: usize,
+) -> Ordering {
+    left.iter()
+        .zip(right.iter())
+        .zip(comparators)
+        .map(
+            |((left, right), compare)| match (left.is_null(i), right.is_null(j)) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => compare(i, j),
+            },
+        )
+        .find(|x| *x != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+fn key_comparators(
+    left: &Chunk<Arc<dyn Array>>,
+    right: &Chunk<Arc<dyn Array>>,
+) -> Result<Vec<DynComparator>, Error> {
+    left.iter()
+        .zip(right.iter())
+        .map(|(left, right)| build_compare(left.as_ref(), right.as_ref()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::ArrowError)
+}
+
+/// One input of a sort-merge join with its current batch and the position of the cursor in it.
+struct MergeCursor {
+    input_iter: Box<dyn Iterator<Item = Batch>>,
+    chunk: Chunk<Arc<dyn Array>>,
+    keys: Vec<usize>,
+    position: usize,
+}
+
+impl MergeCursor {
+    /// Reads batches until the cursor points at a row. Returns false if the input is exhausted.
+    fn fill(&mut self) -> Result<bool, Error> {
+        while self.position >= self.chunk.len() {
+            match self.input_iter.next() {
+                Some(batch) => {
+                    self.chunk = batch?;
+                    self.position = 0;
+                }
+                None => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    fn key_columns(&self) -> Chunk<Arc<dyn Array>> {
+        select_columns(&self.chunk, &self.keys)
+    }
+
+    /// End of the group of rows with the same keys as the row at `start`, within the current
+    /// batch.
+    fn group_end(
+        keys: &Chunk<Arc<dyn Array>>,
+        comparators: &[DynComparator],
+        start: usize,
+    ) -> usize {
+        (start + 1..keys.len())
+            .find(|i| compare_keys(keys, keys, comparators, start, *i) != Ordering::Equal)
+            .unwrap_or(keys.len())
+    }
+
+    /// Appends batches to the rows from the cursor on until the group of the current row ends
+    /// before the end of the batch or the input is exhausted. Returns the end of the group.
+    fn complete_group(&mut self) -> Result<usize, Error> {
+        loop {
+            let keys = self.key_columns();
+            let end = Self::group_end(&keys, &key_comparators(&keys, &keys)?, self.position);
+            if end < keys.len() {
+                return Ok(end);
+            }
+            match self.input_iter.next() {
+                Some(batch) => {
+                    let rest = Chunk::new(
+                        self.chunk
+                            .iter()
+                            .map(|array| {
+                                Arc::from(array.slice(self.position, array.len() - self.position))
+                            })
+                            .collect(),
+                    );
+                    self.chunk = concat_chunks(&[rest, batch?])?;
+                    self.position = 0;
+                }
+                None => return Ok(end),
+            }
+        }
+    }
+}
+
+pub struct SortMergeJoinIterator {
+    left: MergeCursor,
+    right: MergeCursor,
+    null_equals_null: bool,
+}
+
+impl SortMergeJoinIterator {
+    /// Whether the row at `i` matches the rows with the same keys. Unless null keys are equal,
+    /// rows with a null key don't match.
+    fn matches(&self, keys: &Chunk<Arc<dyn Array>>, i: usize) -> bool {
+        self.null_equals_null || keys.iter().all(|x| !x.is_null(i))
+    }
+
+    fn output(&self, left: Vec<u32>, right: Vec<u32>) -> Batch {
+        let left = take_rows(&self.left.chunk, left)?;
+        let right = take_rows(&self.right.chunk, right)?;
+        Ok(Chunk::new(left.into_iter().chain(right).collect()))
+    }
+
+    /// Merges the current batches of both inputs. Groups of equal keys that may continue in the
+    /// next batch are completed first and joined on their own.
+    fn merge(&mut self) -> Result<Option<Chunk<Arc<dyn Array>>>, Error> {
+        loop {
+            if !self.left.fill()? || !self.right.fill()? {
+                return Ok(None);
+            }
+            let left_keys = self.left.key_columns();
+            let right_keys = self.right.key_columns();
+            let comparators = key_comparators(&left_keys, &right_keys)?;
+            let left_comparators = key_comparators(&left_keys, &left_keys)?;
+            let right_comparators = key_comparators(&right_keys, &right_keys)?;
+            let mut left_indices = Vec::new();
+            let mut right_indices = Vec::new();
+            while self.left.position < left_keys.len() && self.right.position < right_keys.len() {
+                let (i, j) = (self.left.position, self.right.position);
+                match compare_keys(&left_keys, &right_keys, &comparators, i, j) {
+                    Ordering::Less => self.left.position += 1,
+                    Ordering::Greater => self.right.position += 1,
+                    Ordering::Equal => {
+                        let left_end = MergeCursor::group_end(&left_keys, &left_comparators, i);
+                        let right_end = MergeCursor::group_end(&right_keys, &right_comparators, j);
+                        if left_end == left_keys.len() || right_end == right_keys.len() {
+                            if !left_indices.is_empty() {
+                                break;
+                            }
+                            let matches = self.matches(&left_keys, i);
+                            let left_end = self.left.complete_group()?;
+                            let right_end = self.right.complete_group()?;
+                            if matches {
+                                for i in self.left.position..left_end {
+                                    for j in self.right.position..right_end {
+                                        left_indices.push(i as u32);
+                                        right_indices.push(j as u32);
+                                    }
+                                }
+                            }
+                            let output = self.output(left_indices, right_indices)?;
+                            self.left.position = left_end;
+                            self.right.position = right_end;
+                            return Ok(Some(output));
+                        }
+                        if self.matches(&left_keys, i) {
+                            for i in i..left_end {
+                                for j in j..right_end {
+                                    left_indices.push(i as u32);
+                                    right_indices.push(j as u32);
+                                }
+                            }
+                        }
+                        self.left.position = left_end;
+                        self.right.position = right_end;
+                    }
+                }
+            }
+            if !left_indices.is_empty() {
+                return self.output(left_indices, right_indices).map(Some);
+            }
+        }
+    }
+}
+
+impl Iterator for SortMergeJoinIterator {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.merge().transpose()
+    }
+}
+
+impl SortMergeJoinExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut input = self.input;
+        let right = input
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("SortMergeJoin".to_string()))?;
+        let left = input
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("SortMergeJoin".to_string()))?;
+        let (left_keys, right_keys): (Vec<usize>, Vec<usize>) = self.on.into_iter().unzip();
+        let cursor = |input: PhysicalPlan, keys: Vec<usize>| -> Result<MergeCursor, Error> {
+            Ok(MergeCursor {
+                input_iter: input.execute()?,
+                chunk: Chunk::new(Vec::new()),
+                keys,
+                position: 0,
+            })
+        };
+        Ok(Box::new(SortMergeJoinIterator {
+            left: cursor(left, left_keys)?,
+            right: cursor(right, right_keys)?,
+            null_equals_null: self.null_equals_null,
+        }))
+    }
+}
+
+/// Keeps the first row for every distinct combination of the key columns `on`. Only the keys
+/// are hashed, rows with the same hash are compared on all keys. The keys of every input batch
+/// are kept to compare later rows against them.
+pub struct DistinctExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    on: Vec<usize>,
+}
+
+impl DistinctExec {
+    pub fn new(input: Vec<PhysicalPlan>, on: Vec<usize>, schema: Schema) -> Result<Self, Error> {
+        check_inputs("Distinct", &input, 1)?;
+        Ok(DistinctExec { input, schema, on })
+    }
+}
+
+pub struct DistinctIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    on: Vec<usize>,
+    /// Key columns of the batches seen so far.
+    keys: Vec<Chunk<Arc<dyn Array>>>,
+    /// Batch and row of the first occurrence of every distinct key, by hash.
+    seen: HashMap<u64, Vec<(usize, usize)>>,
+}
+
+/// Compares the keys of row `i` of `left` with row `j` of `right`. Nulls are equal to each other.
+fn keys_equal(
+    left: &Chunk<Arc<dyn Array>>,
+    right: &Chunk<Arc<dyn Array>>,
+    comparators: &[DynComparator],
+    i: usize,
+    j: usize,
+) -> bool {
+    left.iter()
+        .zip(right.iter())
+        .zip(comparators)
+        .all(
+            |((left, right), compare)| match (left.is_null(i), right.is_null(j)) {
+                (true, true) => true,
+                (false, false) => compare(i, j) == Ordering::Equal,
+                _ => false,
+            },
+        )
+}
+
+impl<I: Iterator<Item = Batch>> DistinctIterator<I> {
+    fn distinct(&mut self, chunk: Chunk<Arc<dyn Array>>) -> Batch {
+        let keys = select_columns(&chunk, &self.on);
+        let hashes = group_hashes(&keys)?;
+        self.keys.push(keys);
+        let batch = self.keys.len() - 1;
+        let keys = &self.keys[batch];
+        let mut comparators: HashMap<usize, Vec<DynComparator>> = HashMap::new();
+        let mut mask = Vec::with_capacity(chunk.len());
+        for (i, hash) in hashes.values().iter().enumerate() {
+            let candidates = self.seen.entry(*hash).or_default();
+            let mut duplicate = false;
+            for (other, j) in candidates.iter() {
+                if !comparators.contains_key(other) {
+                    let compare = keys
+                        .iter()
+                        .zip(self.keys[*other].iter())
+                        .map(|(left, right)| build_compare(left.as_ref(), right.as_ref()))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(Error::ArrowError)?;
+                    comparators.insert(*other, compare);
+                }
+                if keys_equal(keys, &self.keys[*other], &comparators[other], i, *j) {
+                    duplicate = true;
+                    break;
+                }
+            }
+            if !duplicate {
+                candidates.push((batch, i));
+            }
+            mask.push(!duplicate);
+        }
+        Ok(Chunk::new(
+            compute::filter::filter_chunk(&chunk, &BooleanArray::from_slice(mask))
+                .map_err(Error::ArrowError)?
+                .into_arrays()
+                .into_iter()
+                .map(Arc::from)
+                .collect::<Vec<Arc<dyn Array>>>(),
+        ))
+    }
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for DistinctIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.input_iter
+            .next()
+            .map(|res| res.and_then(|chunk| self.distinct(chunk)))
+    }
+}
+
+impl DistinctExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Distinct".to_string()))?;
+        Ok(Box::new(DistinctIterator {
+            input_iter: input.execute()?,
+            on: self.on,
+            keys: Vec::new(),
+            seen: HashMap::new(),
+        }))
+    }
+}
+
+/// Appends the columns computed by the window expressions to every batch. The batches are
+/// processed in input order.
+pub struct WindowExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    exprs: Vec<Box<dyn PhysicalWindowExpression>>,
+}
+
+impl WindowExec {
+    pub fn new(
+        input: Vec<PhysicalPlan>,
+        exprs: Vec<Box<dyn PhysicalWindowExpression>>,
+        schema: Schema,
+    ) -> Result<Self, Error> {
+        check_inputs("Window", &input, 1)?;
+        Ok(WindowExec {
+            input,
+            schema,
+            exprs,
+        })
+    }
+}
+
+pub struct WindowIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    input_schema: Schema,
+    exprs: Vec<Box<dyn PhysicalWindowExpression>>,
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for WindowIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        let exprs = &mut self.exprs;
+        let input_schema = &self.input_schema;
+        self.input_iter.next().map(|res| {
+            res.and_then(|chunk| {
+                let windows = exprs
+                    .iter_mut()
+                    .map(|expr| expr.evaluate_window(&chunk, input_schema))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Ok(Chunk::new(
+                    chunk.into_arrays().into_iter().chain(windows).collect(),
+                ))
+            })
+        })
+    }
+}
+
+impl WindowExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Window".to_string()))?;
+        Ok(Box::new(WindowIterator {
+            input_schema: input.schema().clone(),
+            input_iter: input.execute()?,
+            exprs: self.exprs,
+        }))
+    }
+}
+
+/// Turns every element of the list column at position `column` into its own row and repeats
+/// the values of the other columns for it. Rows with an empty or null list are dropped, unless
+/// `preserve_empty` is set, which keeps them as a single row with a null element.
+pub struct UnnestExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    column: usize,
+    preserve_empty: bool,
+}
+
+impl UnnestExec {
+    pub fn new(input: Vec<PhysicalPlan>, column: usize, schema: Schema) -> Result<Self, Error> {
+        check_inputs("Unnest", &input, 1)?;
+        Ok(UnnestExec {
+            input,
+            schema,
+            column,
+            preserve_empty: false,
+        })
+    }
+
+    /// Keeps rows with an empty or null list as a single row with a null element.
+    pub fn with_preserve_empty(mut self, preserve_empty: bool) -> Self {
+        self.preserve_empty = preserve_empty;
+        self
+    }
+
+    pub fn preserve_empty(&self) -> bool {
+        self.preserve_empty
+    }
+}
+
+/// Positions of the rows to repeat and of the list elements that go with them. Preserved empty
+/// lists get a null element.
+fn unnest_indices<O: Offset>(
+    list: &ListArray<O>,
+    preserve_empty: bool,
+) -> (Vec<u32>, Vec<Option<u32>>) {
+    let mut rows = Vec::new();
+    let mut elements = Vec::new();
+    for (i, offsets) in list.offsets().windows(2).enumerate() {
+        let (start, end) = match list.is_valid(i) {
+            true => (offsets[0].to_usize(), offsets[1].to_usize()),
+            false => (0, 0),
+        };
+        if start == end && preserve_empty {
+            rows.push(i as u32);
+            elements.push(None);
+        }
+        (start..end).for_each(|j| {
+            rows.push(i as u32);
+            elements.push(Some(j as u32));
+        });
+    }
+    (rows, elements)
+}
+
+pub struct UnnestIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    column: usize,
+    preserve_empty: bool,
+}
+
+impl<I: Iterator<Item = Batch>> UnnestIterator<I> {
+    fn unnest(&self, chunk: Chunk<Arc<dyn Array>>) -> Batch {
+        let array = chunk
+            .arrays()
+            .get(self.column)
+            .ok_or(Error::ExceedingBoundsError(self.column))?;
+        let (rows, elements, values) = match array.data_type() {
+            DataType::List(_) => {
+                let list = array
+                    .as_any()
+                    .downcast_ref::<ListArray<i32>>()
+                    .ok_or(Error::DowncastError)?;
+                let (rows, elements) = unnest_indices(list, self.preserve_empty);
+                (rows, elements, list.values())
+            }
+            DataType::LargeList(_) => {
+                let list = array
+                    .as_any()
+                    .downcast_ref::<ListArray<i64>>()
+                    .ok_or(Error::DowncastError)?;
+                let (rows, elements) = unnest_indices(list, self.preserve_empty);
+                (rows, elements, list.values())
+            }
+            data_type => return Err(Error::PhysicalTypeNotSuported(format!("{:?}", data_type))),
+        };
+        let elements = compute::take::take(values.as_ref(), &PrimitiveArray::from(elements))
+            .map_err(Error::ArrowError)?;
+        let mut columns = take_rows(&chunk, rows)?;
+        columns[self.column] = Arc::from(elements);
+        Ok(Chunk::new(columns))
+    }
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for UnnestIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.input_iter
+            .next()
+            .map(|res| res.and_then(|chunk| self.unnest(chunk)))
+    }
+}
+
+impl UnnestExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Unnest".to_string()))?;
+        Ok(Box::new(UnnestIterator {
+            input_iter: input.execute()?,
+            column: self.column,
+            preserve_empty: self.preserve_empty,
+        }))
+    }
+}
@@ -1,13 +1,16 @@
+use std::any::Any;
 use std::borrow::Borrow;
 use std::fmt::{self, Display};
 use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
 use std::sync::Arc;
 
-use arrow2::array::{BooleanArray, Utf8Array};
+use arrow2::array::{new_null_array, ord::build_compare, BooleanArray, Utf8Array};
 use arrow2::bitmap::Bitmap;
 use arrow2::chunk::Chunk;
-use arrow2::datatypes::{DataType, PhysicalType};
-use arrow2::scalar::{BooleanScalar, NullScalar, PrimitiveScalar, Scalar};
+use arrow2::datatypes::{DataType, PhysicalType, Schema, TimeUnit};
+use arrow2::scalar::{new_scalar, BooleanScalar, NullScalar, PrimitiveScalar, Scalar};
+use arrow2::types::NativeType;
 use arrow2::{
     array::{Array, PrimitiveArray},
     compute,
@@ -16,11 +19,17 @@ use arrow2::{
 };
 use std::ops::BitAnd;
 
-use crate::columnar_value::ColumnarValue;
+use crate::columnar_value::{scalar_to_array, ColumnarValue};
 use crate::error::Error;
+use crate::execution_context::CoercionMode;
 
+/// An expression evaluated on every input batch. `schema` is the schema of the input batches.
 pub trait PhysicalExpression: Display {
-    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error>;
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error>;
 }
 
 #[derive(Clone, Debug)]
@@ -29,35 +38,35 @@ pub struct ColumnExpression {
 }
 
 impl PhysicalExpression for ColumnExpression {
-    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
-        input
+    /// Returns the column of the input itself, which shares its buffers with the input.
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        let column = input
             .get(self.index)
-            .and_then(|x| {
-                let x: &dyn Array = x.borrow();
-                match x.data_type().to_physical_type() {
-                    PhysicalType::Primitive(PrimitiveType::Int32) => x
-                        .as_any()
-                        .downcast_ref::<PrimitiveArray<i32>>()
-                        .map(|y| ColumnarValue::Array(Arc::new(y.clone()) as Arc<dyn Array>)),
-                    PhysicalType::Primitive(PrimitiveType::Float64) => x
-                        .as_any()
-                        .downcast_ref::<PrimitiveArray<f64>>()
-                        .map(|y| ColumnarValue::Array(Arc::new(y.clone()) as Arc<dyn Array>)),
-                    PhysicalType::Utf8 => x
-                        .as_any()
-                        .downcast_ref::<Utf8Array<i32>>()
-                        .map(|y| ColumnarValue::Array(Arc::new(y.clone()) as Arc<dyn Array>)),
-                    PhysicalType::Boolean => x
-                        .as_any()
-                        .downcast_ref::<BooleanArray>()
-                        .map(|y| ColumnarValue::Array(Arc::new(y.clone()) as Arc<dyn Array>)),
-                    _ => None,
-                }
-            })
-            .ok_or(Error::PrimitiveTypeNotSuported(format!(
-                "{:?}",
-                PrimitiveType::Int32
-            )))
+            .ok_or(Error::ExceedingBoundsError(self.index))?;
+        if matches!(
+            column.data_type().to_physical_type(),
+            PhysicalType::Primitive(PrimitiveType::Int32)
+                | PhysicalType::Primitive(PrimitiveType::Int64)
+                | PhysicalType::Primitive(PrimitiveType::Float32)
+                | PhysicalType::Primitive(PrimitiveType::Float64)
+                | PhysicalType::Utf8
+                | PhysicalType::LargeUtf8
+                | PhysicalType::Boolean
+                | PhysicalType::List
+        ) {
+            Ok(ColumnarValue::Array(column.clone()))
+        } else {
+            Err(Error::PhysicalTypeNotSuported(
+                match schema.fields.get(self.index) {
+                    Some(field) => format!("{:?} of column `{}`", column.data_type(), field.name),
+                    None => format!("{:?}", column.data_type()),
+                },
+            ))
+        }
     }
 }
 
@@ -67,6 +76,34 @@ impl fmt::Display for ColumnExpression {
     }
 }
 
+/// A column referenced by name. The position of the column is looked up in the input schema
+/// whenever the expression is evaluated, so it stays valid if an input reorders its columns.
+#[derive(Clone, Debug)]
+pub struct NamedColumnExpression {
+    pub name: String,
+}
+
+impl PhysicalExpression for NamedColumnExpression {
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        let index = schema
+            .fields
+            .iter()
+            .position(|x| x.name == self.name)
+            .ok_or_else(|| Error::ColumnNotFound(self.name.clone()))?;
+        ColumnExpression { index }.evaluate(input, schema)
+    }
+}
+
+impl fmt::Display for NamedColumnExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.name)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct LiteralBoolExpression {
     pub value: BooleanScalar,
@@ -81,7 +118,11 @@ impl LiteralBoolExpression {
 }
 
 impl PhysicalExpression for LiteralBoolExpression {
-    fn evaluate(&self, _input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+    fn evaluate(
+        &self,
+        _input: &Chunk<Arc<dyn Array>>,
+        _schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
         Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
     }
 }
@@ -106,7 +147,11 @@ impl LiteralStringExpression {
 }
 
 impl PhysicalExpression for LiteralStringExpression {
-    fn evaluate(&self, _input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+    fn evaluate(
+        &self,
+        _input: &Chunk<Arc<dyn Array>>,
+        _schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
         Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
     }
 }
@@ -131,7 +176,11 @@ impl LiteralIntegerExpression {
 }
 
 impl PhysicalExpression for LiteralIntegerExpression {
-    fn evaluate(&self, _input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+    fn evaluate(
+        &self,
+        _input: &Chunk<Arc<dyn Array>>,
+        _schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
         Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
     }
 }
@@ -142,6 +191,35 @@ impl fmt::Display for LiteralIntegerExpression {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct LiteralLongExpression {
+    pub(crate) value: PrimitiveScalar<i64>,
+}
+
+impl LiteralLongExpression {
+    pub fn new(value: i64) -> Self {
+        LiteralLongExpression {
+            value: PrimitiveScalar::new(DataType::Int64, Some(value)),
+        }
+    }
+}
+
+impl PhysicalExpression for LiteralLongExpression {
+    fn evaluate(
+        &self,
+        _input: &Chunk<Arc<dyn Array>>,
+        _schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
+    }
+}
+
+impl fmt::Display for LiteralLongExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:?}", self.value)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct LiteralFloatExpression {
     pub(crate) value: PrimitiveScalar<f64>,
@@ -156,7 +234,11 @@ impl LiteralFloatExpression {
 }
 
 impl PhysicalExpression for LiteralFloatExpression {
-    fn evaluate(&self, _input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+    fn evaluate(
+        &self,
+        _input: &Chunk<Arc<dyn Array>>,
+        _schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
         Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
     }
 }
@@ -167,18 +249,259 @@ impl fmt::Display for LiteralFloatExpression {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct LiteralIntervalExpression {
+    pub(crate) value: PrimitiveScalar<i64>,
+}
+
+impl LiteralIntervalExpression {
+    pub fn new(seconds: i64) -> Self {
+        LiteralIntervalExpression {
+            value: PrimitiveScalar::new(DataType::Duration(TimeUnit::Second), Some(seconds)),
+        }
+    }
+}
+
+impl PhysicalExpression for LiteralIntervalExpression {
+    fn evaluate(
+        &self,
+        _input: &Chunk<Arc<dyn Array>>,
+        _schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
+    }
+}
+
+impl fmt::Display for LiteralIntervalExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:?}", self.value)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LiteralNullExpression {
+    pub(crate) data_type: DataType,
+}
+
+impl LiteralNullExpression {
+    pub fn new(data_type: DataType) -> Self {
+        LiteralNullExpression { data_type }
+    }
+}
+
+impl PhysicalExpression for LiteralNullExpression {
+    fn evaluate(
+        &self,
+        _input: &Chunk<Arc<dyn Array>>,
+        _schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(new_scalar(
+            &*new_null_array(self.data_type.clone(), 1),
+            0,
+        )))
+    }
+}
+
+impl fmt::Display for LiteralNullExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#NULL")
+    }
+}
+
+/// Promotes two numeric or string values of different types to their common type, so that
+/// they can be compared or combined arithmetically. Other values are returned unchanged.
+fn coerce_types(
+    left: ColumnarValue,
+    right: ColumnarValue,
+) -> Result<(ColumnarValue, ColumnarValue), Error> {
+    let data_type = match (left.data_type(), right.data_type()) {
+        (DataType::Float32 | DataType::Float64, DataType::Int32 | DataType::Int64)
+        | (DataType::Int32 | DataType::Int64, DataType::Float32 | DataType::Float64)
+        | (DataType::Float32, DataType::Float64)
+        | (DataType::Float64, DataType::Float32) => DataType::Float64,
+        (DataType::Int32, DataType::Int64) | (DataType::Int64, DataType::Int32) => DataType::Int64,
+        (DataType::Utf8, DataType::LargeUtf8) | (DataType::LargeUtf8, DataType::Utf8) => {
+            DataType::LargeUtf8
+        }
+        _ => return Ok((left, right)),
+    };
+    Ok((
+        cast_value(left, &data_type)?,
+        cast_value(right, &data_type)?,
+    ))
+}
+
+fn cast_value(value: ColumnarValue, data_type: &DataType) -> Result<ColumnarValue, Error> {
+    if &value.data_type() == data_type {
+        return Ok(value);
+    }
+    let is_scalar = matches!(value, ColumnarValue::Scalar(_));
+    let array = value.to_array(1);
+    let cast: Arc<dyn Array> = match (array.data_type(), data_type) {
+        (DataType::Int32, DataType::Float64) => Arc::new(compute::arity::unary(
+            array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .ok_or(Error::DowncastError)?,
+            |x| x as f64,
+            DataType::Float64,
+        )),
+        (DataType::Int64, DataType::Float64) => Arc::new(compute::arity::unary(
+            array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i64>>()
+                .ok_or(Error::DowncastError)?,
+            |x| x as f64,
+            DataType::Float64,
+        )),
+        (DataType::Float32, DataType::Float64) => Arc::new(compute::arity::unary(
+            array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f32>>()
+                .ok_or(Error::DowncastError)?,
+            |x| x as f64,
+            DataType::Float64,
+        )),
+        (DataType::Int32, DataType::Int64) => Arc::new(compute::arity::unary(
+            array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .ok_or(Error::DowncastError)?,
+            |x| x as i64,
+            DataType::Int64,
+        )),
+        (DataType::Utf8, DataType::LargeUtf8) => Arc::new(Utf8Array::<i64>::from_trusted_len_iter(
+            array
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .ok_or(Error::DowncastError)?
+                .iter(),
+        )),
+        (from, to) => {
+            return Err(Error::PhysicalTypeNotSuported(format!(
+                "{:?} as {:?}",
+                from, to
+            )))
+        }
+    };
+    if is_scalar {
+        Ok(ColumnarValue::Scalar(new_scalar(&*cast, 0)))
+    } else {
+        Ok(ColumnarValue::Array(cast))
+    }
+}
+
+pub struct CastExpression {
+    expr: Box<dyn PhysicalExpression>,
+    data_type: DataType,
+    mode: CoercionMode,
+}
+
+impl CastExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>, data_type: DataType, mode: CoercionMode) -> Self {
+        CastExpression {
+            expr,
+            data_type,
+            mode,
+        }
+    }
+}
+
+impl PhysicalExpression for CastExpression {
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        let value = self.expr.evaluate(input, schema)?;
+        match (value.data_type(), &self.data_type) {
+            (DataType::Utf8, DataType::Int32) => self.parse::<i32>(value),
+            (DataType::Utf8, DataType::Int64) => self.parse::<i64>(value),
+            (DataType::Utf8, DataType::Float64) => self.parse::<f64>(value),
+            (DataType::Utf8, DataType::Date32) => self.parse_date(value),
+            _ => cast_value(value, &self.data_type),
+        }
+    }
+}
+
+impl CastExpression {
+    fn parse<T: NativeType + FromStr>(&self, value: ColumnarValue) -> Result<ColumnarValue, Error> {
+        let is_scalar = matches!(value, ColumnarValue::Scalar(_));
+        let array = value.to_array(1);
+        let strings = array
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .ok_or(Error::DowncastError)?;
+        let values = strings
+            .iter()
+            .map(|x| match x.map(|x| (x, x.trim().parse::<T>())) {
+                None => Ok(None),
+                Some((_, Ok(value))) => Ok(Some(value)),
+                Some((x, Err(_))) => match self.mode {
+                    CoercionMode::Strict => Err(Error::CastError(
+                        x.to_string(),
+                        format!("{:?}", self.data_type),
+                    )),
+                    CoercionMode::Lenient => Ok(None),
+                },
+            })
+            .collect::<Result<Vec<Option<T>>, Error>>()?;
+        let cast = PrimitiveArray::<T>::from(values).to(self.data_type.clone());
+        if is_scalar {
+            Ok(ColumnarValue::Scalar(new_scalar(&cast, 0)))
+        } else {
+            Ok(ColumnarValue::Array(Arc::new(cast)))
+        }
+    }
+
+    /// Parses `%Y-%m-%d` dates with the cast kernel, which turns unparseable values into nulls.
+    /// In strict mode they are an error instead.
+    fn parse_date(&self, value: ColumnarValue) -> Result<ColumnarValue, Error> {
+        let is_scalar = matches!(value, ColumnarValue::Scalar(_));
+        let array = value.to_array(1);
+        let strings = array
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .ok_or(Error::DowncastError)?;
+        let cast = compute::cast::utf8_to_date32(strings);
+        if let CoercionMode::Strict = self.mode {
+            if let Some(i) = (0..strings.len()).find(|i| strings.is_valid(*i) && cast.is_null(*i)) {
+                return Err(Error::CastError(
+                    strings.value(i).to_string(),
+                    format!("{:?}", self.data_type),
+                ));
+            }
+        }
+        if is_scalar {
+            Ok(ColumnarValue::Scalar(new_scalar(&cast, 0)))
+        } else {
+            Ok(ColumnarValue::Array(Arc::new(cast)))
+        }
+    }
+}
+
+impl fmt::Display for CastExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cast {} as {:?}", self.expr, self.data_type)
+    }
+}
+
 macro_rules! comparisonExpression {
-    ($i: ident, $name1: ident, $name2: ident, $op: ident, $op_name: expr) => {
+    ($i: ident, $name1: ident, $name2: ident, $name3: ident, $op_name: expr) => {
         pub struct $i {
             left: Box<dyn PhysicalExpression>,
             right: Box<dyn PhysicalExpression>,
         }
 
         impl PhysicalExpression for $i {
-            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
-                let l = self.left.evaluate(input)?;
-                let r = self.right.evaluate(input)?;
-                match (l, r) {
+            fn evaluate(
+                &self,
+                input: &Chunk<Arc<dyn Array>>,
+                schema: &Schema,
+            ) -> Result<ColumnarValue, Error> {
+                let l = self.left.evaluate(input, schema)?;
+                let r = self.right.evaluate(input, schema)?;
+                match coerce_types(l, r)? {
                     (ColumnarValue::Array(left), ColumnarValue::Array(right)) => {
                         if left.len() == right.len() {
                             Ok(ColumnarValue::Array(Arc::new(compute::comparison::$name1(
@@ -197,14 +520,16 @@ macro_rules! comparisonExpression {
                         ))))
                     }
                     (ColumnarValue::Scalar(left), ColumnarValue::Array(right)) => {
-                        Ok(ColumnarValue::Array(Arc::new(compute::comparison::$name2(
+                        Ok(ColumnarValue::Array(Arc::new(compute::comparison::$name3(
                             &*right, &*left,
                         ))))
                     }
                     (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => {
-                        Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(Some(
-                            left.$op(&*right),
-                        )))))
+                        let result =
+                            compute::comparison::$name2(&*scalar_to_array(left, 1)?, &*right);
+                        Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(
+                            result.is_valid(0).then(|| result.value(0)),
+                        ))))
                     }
                 }
             }
@@ -215,13 +540,149 @@ macro_rules! comparisonExpression {
                 left: Box<dyn PhysicalExpression>,
                 right: Box<dyn PhysicalExpression>,
             ) -> Self {
-                $i {
-                    left: left,
-                    right: right,
+                $i { left, right }
+            }
+        }
+
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} {} {}", self.left, $op_name, self.right)
+            }
+        }
+    };
+}
+comparisonExpression!(EqExpression, eq, eq_scalar, eq_scalar, "==".to_string());
+comparisonExpression!(NeqExpression, neq, neq_scalar, neq_scalar, "!=".to_string());
+comparisonExpression!(GtExpression, gt, gt_scalar, lt_scalar, ">".to_string());
+comparisonExpression!(
+    GtEqExpression,
+    gt_eq,
+    gt_eq_scalar,
+    lt_eq_scalar,
+    ">=".to_string()
+);
+comparisonExpression!(LtExpression, lt, lt_scalar, gt_scalar, "<".to_string());
+comparisonExpression!(
+    LtEqExpression,
+    lt_eq,
+    lt_eq_scalar,
+    gt_eq_scalar,
+    "<=".to_string()
+);
+
+/// Equality where two nulls are equal and a null is unequal to any value. The result is never
+/// null.
+pub struct NullSafeEqExpression {
+    left: Box<dyn PhysicalExpression>,
+    right: Box<dyn PhysicalExpression>,
+}
+
+impl PhysicalExpression for NullSafeEqExpression {
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        let l = self.left.evaluate(input, schema)?;
+        let r = self.right.evaluate(input, schema)?;
+        let (left, right, is_scalar) = match coerce_types(l, r)? {
+            (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => {
+                (scalar_to_array(left, 1)?, scalar_to_array(right, 1)?, true)
+            }
+            (left, right) => {
+                let array = |value: ColumnarValue| match value {
+                    ColumnarValue::Array(array) => Ok(array),
+                    ColumnarValue::Scalar(scalar) => scalar_to_array(scalar, input.len()),
+                };
+                (array(left)?, array(right)?, false)
+            }
+        };
+        if left.len() != right.len() {
+            return Err(Error::DifferentSizes(
+                format!("{:?}", left),
+                format!("{:?}", right),
+            ));
+        }
+        let eq = compute::comparison::eq(&*left, &*right);
+        let values = (0..left.len())
+            .map(|i| match (left.is_valid(i), right.is_valid(i)) {
+                (true, true) => eq.value(i),
+                (false, false) => true,
+                _ => false,
+            })
+            .collect::<Vec<_>>();
+        if is_scalar {
+            Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(Some(
+                values[0],
+            )))))
+        } else {
+            Ok(ColumnarValue::Array(Arc::new(BooleanArray::from_slice(
+                values,
+            ))))
+        }
+    }
+}
+
+impl NullSafeEqExpression {
+    pub fn new(left: Box<dyn PhysicalExpression>, right: Box<dyn PhysicalExpression>) -> Self {
+        NullSafeEqExpression { left, right }
+    }
+}
+
+impl fmt::Display for NullSafeEqExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} <=> {}", self.left, self.right)
+    }
+}
+
+macro_rules! booleanExpression {
+    ($i: ident, $name1: ident, $name2: ident, $op: tt, $op_name: expr) => {
+        pub struct $i {
+            left: Box<dyn PhysicalExpression>,
+            right: Box<dyn PhysicalExpression>,
+        }
+
+        impl PhysicalExpression for $i {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>, schema: &Schema) -> Result<ColumnarValue, Error> {
+                let l = self.left.evaluate(input, schema)?;
+                let r = self.right.evaluate(input, schema)?;
+                match (l, r) {
+                    (ColumnarValue::Array(left), ColumnarValue::Array(right)) => {
+                        Ok(ColumnarValue::Array(Arc::new(
+                            compute::boolean::$name1(as_boolean_array(&*left)?, as_boolean_array(&*right)?)
+                                .map_err(Error::ArrowError)?,
+                        )))
+                    }
+                    (ColumnarValue::Array(array), ColumnarValue::Scalar(scalar))
+                    | (ColumnarValue::Scalar(scalar), ColumnarValue::Array(array)) => {
+                        Ok(ColumnarValue::Array(Arc::new(compute::boolean::$name2(
+                            as_boolean_array(&*array)?,
+                            as_boolean_scalar(&*scalar)?,
+                        ))))
+                    }
+                    (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => {
+                        let value = match (
+                            as_boolean_scalar(&*left)?.value(),
+                            as_boolean_scalar(&*right)?.value(),
+                        ) {
+                            (Some(left), Some(right)) => Some(left $op right),
+                            _ => None,
+                        };
+                        Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(value))))
+                    }
                 }
             }
         }
 
+        impl $i {
+            pub fn new(
+                left: Box<dyn PhysicalExpression>,
+                right: Box<dyn PhysicalExpression>,
+            ) -> Self {
+                $i { left, right }
+            }
+        }
+
         impl fmt::Display for $i {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                 write!(f, "{} {} {}", self.left, $op_name, self.right)
@@ -229,26 +690,46 @@ macro_rules! comparisonExpression {
         }
     };
 }
-comparisonExpression!(EqExpression, eq, eq_scalar, eq, "==".to_string());
-comparisonExpression!(NeqExpression, neq, neq_scalar, ne, "!=".to_string());
+
+fn as_boolean_array(array: &dyn Array) -> Result<&BooleanArray, Error> {
+    array
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .ok_or(Error::DowncastError)
+}
+
+fn as_boolean_scalar(scalar: &dyn Scalar) -> Result<&BooleanScalar, Error> {
+    scalar
+        .as_any()
+        .downcast_ref::<BooleanScalar>()
+        .ok_or(Error::DowncastError)
+}
+
+booleanExpression!(AndExpression, and, and_scalar, &&, "&&".to_string());
+booleanExpression!(OrExpression, or, or_scalar, ||, "||".to_string());
 
 macro_rules! mathExpression {
-    ($i: ident, $name1: ident, $name2: ident, $op: ident, $op_name: expr) => {
+    ($i: ident, $name1: path, $name2: path, $op: ident, $op_name: expr) => {
         pub struct $i {
             left: Box<dyn PhysicalExpression>,
             right: Box<dyn PhysicalExpression>,
         }
 
         impl PhysicalExpression for $i {
-            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
-                let left = self.left.evaluate(input)?;
-                let right = self.right.evaluate(input)?;
+            fn evaluate(
+                &self,
+                input: &Chunk<Arc<dyn Array>>,
+                schema: &Schema,
+            ) -> Result<ColumnarValue, Error> {
+                let (left, right) = coerce_types(
+                    self.left.evaluate(input, schema)?,
+                    self.right.evaluate(input, schema)?,
+                )?;
+                let data_types = (left.data_type(), right.data_type());
                 match (left, right) {
                     (ColumnarValue::Array(left), ColumnarValue::Array(right)) => {
                         if left.len() == right.len() {
-                            Ok(ColumnarValue::Array(Arc::from(
-                                compute::arithmetics::$name1(&*left, &*right),
-                            )))
+                            Ok(ColumnarValue::Array(Arc::from($name1(&*left, &*right))))
                         } else {
                             Err(Error::DifferentSizes(
                                 format!("{:?}", left),
@@ -257,68 +738,60 @@ macro_rules! mathExpression {
                         }
                     }
                     (ColumnarValue::Array(left), ColumnarValue::Scalar(right)) => {
-                        Ok(ColumnarValue::Array(Arc::from(
-                            compute::arithmetics::$name2(&*left, &*right),
-                        )))
+                        Ok(ColumnarValue::Array(Arc::from($name2(&*left, &*right)?)))
                     }
                     (ColumnarValue::Scalar(left), ColumnarValue::Array(right)) => {
-                        Ok(ColumnarValue::Array(Arc::from(
-                            compute::arithmetics::$name2(&*right, &*left),
-                        )))
+                        // Sub and div are not commutative, so the scalar is broadcast to an
+                        // array instead of swapping the operands.
+                        let left = scalar_to_array(left, right.len())?;
+                        Ok(ColumnarValue::Array(Arc::from($name1(&*left, &*right))))
                     }
-                    (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => {
-                        match (
-                            left.data_type().to_physical_type(),
-                            right.data_type().to_physical_type(),
-                        ) {
-                            (
-                                PhysicalType::Primitive(PrimitiveType::Float64),
-                                PhysicalType::Primitive(PrimitiveType::Float64),
-                            ) => {
-                                let (left, right) = (
-                                    left.as_any()
-                                        .downcast_ref::<PrimitiveScalar<f64>>()
-                                        .ok_or(Error::DowncastError)?,
-                                    right
-                                        .as_any()
-                                        .downcast_ref::<PrimitiveScalar<f64>>()
-                                        .ok_or(Error::DowncastError)?,
-                                );
-                                Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+                    (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => match data_types
+                    {
+                        (DataType::Float64, DataType::Float64) => {
+                            let (left, right) = (
+                                left.as_any()
+                                    .downcast_ref::<PrimitiveScalar<f64>>()
+                                    .ok_or(Error::DowncastError)?,
+                                right
+                                    .as_any()
+                                    .downcast_ref::<PrimitiveScalar<f64>>()
+                                    .ok_or(Error::DowncastError)?,
+                            );
+                            Ok(ColumnarValue::Scalar(Box::new(
+                                PrimitiveScalar::<f64>::new(
                                     DataType::Float64,
                                     match (left.value(), right.value()) {
-                                        (Some(left), Some(right)) => Some(left.$op(right)),
+                                        (Some(left), Some(right)) => left.$op(right).into(),
                                         _ => None,
                                     },
-                                ))))
-                            }
-                            (
-                                PhysicalType::Primitive(PrimitiveType::Int32),
-                                PhysicalType::Primitive(PrimitiveType::Int32),
-                            ) => {
-                                let (left, right) = (
-                                    left.as_any()
-                                        .downcast_ref::<PrimitiveScalar<i32>>()
-                                        .ok_or(Error::DowncastError)?,
-                                    right
-                                        .as_any()
-                                        .downcast_ref::<PrimitiveScalar<i32>>()
-                                        .ok_or(Error::DowncastError)?,
-                                );
-                                Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+                                ),
+                            )))
+                        }
+                        (DataType::Int32, DataType::Int32) => {
+                            let (left, right) = (
+                                left.as_any()
+                                    .downcast_ref::<PrimitiveScalar<i32>>()
+                                    .ok_or(Error::DowncastError)?,
+                                right
+                                    .as_any()
+                                    .downcast_ref::<PrimitiveScalar<i32>>()
+                                    .ok_or(Error::DowncastError)?,
+                            );
+                            Ok(ColumnarValue::Scalar(Box::new(
+                                PrimitiveScalar::<i32>::new(
                                     DataType::Int32,
                                     match (left.value(), right.value()) {
-                                        (Some(left), Some(right)) => Some(left.$op(right)),
+                                        (Some(left), Some(right)) => left.$op(right).into(),
                                         _ => None,
                                     },
-                                ))))
-                            }
-                            _ => Err(Error::PhysicalTypeNotSuported(format!(
-                                "{:?}",
-                                left.data_type()
-                            ))),
+                                ),
+                            )))
                         }
-                    }
+                        (data_type, _) => {
+                            Err(Error::PhysicalTypeNotSuported(format!("{:?}", data_type)))
+                        }
+                    },
                 }
             }
         }
@@ -328,10 +801,7 @@ macro_rules! mathExpression {
                 left: Box<dyn PhysicalExpression>,
                 right: Box<dyn PhysicalExpression>,
             ) -> Self {
-                $i {
-                    left: left,
-                    right: right,
-                }
+                $i { left, right }
             }
         }
 
@@ -343,10 +813,287 @@ macro_rules! mathExpression {
     };
 }
 
-mathExpression!(AddExpression, add, add_scalar, add, "+".to_string());
-mathExpression!(SubExpression, sub, sub_scalar, sub, "-".to_string());
-mathExpression!(MulExpression, mul, mul_scalar, mul, "*".to_string());
-mathExpression!(DivExpression, div, div_scalar, div, "/".to_string());
+macro_rules! scalarKernel {
+    ($name: ident, $kernel: path, $can: path, $op_name: expr) => {
+        /// Applies the arrow2 kernel, which panics on operands it doesn't support.
+        fn $name(lhs: &dyn Array, rhs: &dyn Scalar) -> Result<Box<dyn Array>, Error> {
+            if !$can(lhs.data_type(), rhs.data_type()) {
+                return Err(Error::PhysicalTypeNotSuported(format!(
+                    "{:?} {} {:?}",
+                    lhs.data_type(),
+                    $op_name,
+                    rhs.data_type()
+                )));
+            }
+            Ok($kernel(lhs, rhs))
+        }
+    };
+}
+
+scalarKernel!(
+    add_scalar,
+    compute::arithmetics::add_scalar,
+    compute::arithmetics::can_add,
+    "+"
+);
+scalarKernel!(
+    sub_scalar,
+    compute::arithmetics::sub_scalar,
+    compute::arithmetics::can_sub,
+    "-"
+);
+scalarKernel!(
+    mul_scalar,
+    compute::arithmetics::mul_scalar,
+    compute::arithmetics::can_mul,
+    "*"
+);
+scalarKernel!(
+    div_scalar,
+    compute::arithmetics::div_scalar,
+    compute::arithmetics::can_div,
+    "/"
+);
+
+/// Remainder of two values, which is null instead of a panic for a zero divisor of an integer
+/// type.
+trait NullableRem: Sized {
+    fn nullable_rem(self, rhs: Self) -> Option<Self>;
+}
+
+impl NullableRem for i32 {
+    fn nullable_rem(self, rhs: Self) -> Option<Self> {
+        self.checked_rem(rhs)
+    }
+}
+
+impl NullableRem for f64 {
+    fn nullable_rem(self, rhs: Self) -> Option<Self> {
+        Some(self % rhs)
+    }
+}
+
+/// Remainder of two arrays. Unlike `compute::arithmetics::rem` it's null for a zero divisor of
+/// an integer type.
+fn rem(lhs: &dyn Array, rhs: &dyn Array) -> Box<dyn Array> {
+    macro_rules! checked_rem {
+        ($t: ty) => {
+            match (
+                lhs.as_any().downcast_ref::<PrimitiveArray<$t>>(),
+                rhs.as_any().downcast_ref::<PrimitiveArray<$t>>(),
+            ) {
+                (Some(lhs), Some(rhs)) => {
+                    Box::new(compute::arithmetics::basic::checked_rem(lhs, rhs))
+                }
+                _ => compute::arithmetics::rem(lhs, rhs),
+            }
+        };
+    }
+    match lhs.data_type() {
+        DataType::Int32 => checked_rem!(i32),
+        DataType::Int64 => checked_rem!(i64),
+        _ => compute::arithmetics::rem(lhs, rhs),
+    }
+}
+
+/// Remainder of an array and a scalar, which arrow2 only provides for primitive arrays. It's null
+/// for a zero divisor of an integer type.
+fn rem_scalar(lhs: &dyn Array, rhs: &dyn Scalar) -> Result<Box<dyn Array>, Error> {
+    macro_rules! rem_primitive {
+        ($t: ty, $kernel: path) => {{
+            let lhs = lhs
+                .as_any()
+                .downcast_ref::<PrimitiveArray<$t>>()
+                .ok_or(Error::DowncastError)?;
+            let rhs = rhs
+                .as_any()
+                .downcast_ref::<PrimitiveScalar<$t>>()
+                .ok_or(Error::DowncastError)?;
+            Ok(match rhs.value() {
+                Some(rhs) => Box::new($kernel(lhs, &rhs)),
+                None => new_null_array(lhs.data_type().clone(), lhs.len()),
+            })
+        }};
+    }
+    match (lhs.data_type(), rhs.data_type()) {
+        (DataType::Int32, DataType::Int32) => {
+            rem_primitive!(i32, compute::arithmetics::basic::checked_rem_scalar)
+        }
+        (DataType::Int64, DataType::Int64) => {
+            rem_primitive!(i64, compute::arithmetics::basic::checked_rem_scalar)
+        }
+        (DataType::Float32, DataType::Float32) => {
+            rem_primitive!(f32, compute::arithmetics::basic::rem_scalar)
+        }
+        (DataType::Float64, DataType::Float64) => {
+            rem_primitive!(f64, compute::arithmetics::basic::rem_scalar)
+        }
+        (lhs, rhs) => Err(Error::PhysicalTypeNotSuported(format!(
+            "{:?} % {:?}",
+            lhs, rhs
+        ))),
+    }
+}
+
+mathExpression!(
+    AddExpression,
+    compute::arithmetics::add,
+    add_scalar,
+    add,
+    "+".to_string()
+);
+mathExpression!(
+    SubExpression,
+    compute::arithmetics::sub,
+    sub_scalar,
+    sub,
+    "-".to_string()
+);
+mathExpression!(
+    MulExpression,
+    compute::arithmetics::mul,
+    mul_scalar,
+    mul,
+    "*".to_string()
+);
+mathExpression!(
+    DivExpression,
+    compute::arithmetics::div,
+    div_scalar,
+    div,
+    "/".to_string()
+);
+mathExpression!(
+    ModExpression,
+    rem,
+    rem_scalar,
+    nullable_rem,
+    "%".to_string()
+);
+
+macro_rules! temporalExpression {
+    ($i: ident, $name: ident, $op_name: expr) => {
+        pub struct $i {
+            expr: Box<dyn PhysicalExpression>,
+        }
+
+        impl PhysicalExpression for $i {
+            fn evaluate(
+                &self,
+                input: &Chunk<Arc<dyn Array>>,
+                schema: &Schema,
+            ) -> Result<ColumnarValue, Error> {
+                match self.expr.evaluate(input, schema)? {
+                    ColumnarValue::Array(array) => {
+                        let result =
+                            compute::temporal::$name(&*array).map_err(Error::ArrowError)?;
+                        Ok(ColumnarValue::Array(Arc::new(compute::arity::unary(
+                            &result,
+                            |x| x as i32,
+                            DataType::Int32,
+                        ))))
+                    }
+                    ColumnarValue::Scalar(scalar) => Err(Error::PhysicalTypeNotSuported(format!(
+                        "{:?}",
+                        scalar.data_type()
+                    ))),
+                }
+            }
+        }
+
+        impl $i {
+            pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+                $i { expr }
+            }
+        }
+
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} ({})", $op_name, self.expr)
+            }
+        }
+    };
+}
+
+temporalExpression!(YearExpression, year, "year");
+temporalExpression!(MonthExpression, month, "month");
+temporalExpression!(DayExpression, day, "day");
+
+/// A user-defined scalar function. It is registered with `ExecutionContext::register_udf` and
+/// used in queries through `prelude::udf`.
+pub trait ScalarUdf {
+    fn evaluate(&self, args: &[ColumnarValue]) -> Result<ColumnarValue, Error>;
+}
+
+impl<F: Fn(&[ColumnarValue]) -> Result<ColumnarValue, Error>> ScalarUdf for F {
+    fn evaluate(&self, args: &[ColumnarValue]) -> Result<ColumnarValue, Error> {
+        self(args)
+    }
+}
+
+impl fmt::Debug for dyn ScalarUdf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ScalarUdf")
+    }
+}
+
+pub struct ScalarUdfExpression {
+    name: String,
+    data_type: DataType,
+    udf: Arc<dyn ScalarUdf>,
+    args: Vec<Box<dyn PhysicalExpression>>,
+}
+
+impl PhysicalExpression for ScalarUdfExpression {
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        let args = self
+            .args
+            .iter()
+            .map(|x| x.evaluate(input, schema))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let result = self.udf.evaluate(&args)?;
+        if result.data_type() != self.data_type {
+            return Err(Error::ReturnTypeMismatch(
+                self.name.clone(),
+                format!("{:?}", result.data_type()),
+                format!("{:?}", self.data_type),
+            ));
+        }
+        Ok(result)
+    }
+}
+
+impl ScalarUdfExpression {
+    pub fn new(
+        name: String,
+        data_type: DataType,
+        udf: Arc<dyn ScalarUdf>,
+        args: Vec<Box<dyn PhysicalExpression>>,
+    ) -> Self {
+        ScalarUdfExpression {
+            name,
+            data_type,
+            udf,
+            args,
+        }
+    }
+}
+
+impl fmt::Display for ScalarUdfExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{} ({})", self.name, args)
+    }
+}
 
 pub trait Accumulator {
     fn accumulate(
@@ -354,18 +1101,70 @@ pub trait Accumulator {
         input: &Vec<ColumnarValue>,
         validity: Option<&Bitmap>,
     ) -> Result<(), Error>;
+    /// Adds the values accumulated by `other`, an accumulator of the same aggregate that
+    /// aggregated another part of the input.
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error>;
     fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error>;
+    /// Used by `merge` to downcast the other accumulator.
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
 }
 
 pub trait PhysicalAggregateExpression: PhysicalExpression {
     fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator>;
 }
 
+/// A user-defined aggregate. It is registered with `ExecutionContext::register_aggregate` and
+/// used in queries through `prelude::aggregate`.
+pub trait AggregateUdf: fmt::Debug {
+    fn name(&self) -> &str;
+    /// Type of the final value of the accumulators.
+    fn output_type(&self) -> DataType;
+    /// Creates the accumulator of one group, which aggregates `input[index]`.
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator>;
+}
+
+pub struct AggregateUdfExpression {
+    udf: Arc<dyn AggregateUdf>,
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl PhysicalExpression for AggregateUdfExpression {
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input, schema)
+    }
+}
+
+impl PhysicalAggregateExpression for AggregateUdfExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        self.udf.create_accumulator(index)
+    }
+}
+
+impl AggregateUdfExpression {
+    pub fn new(udf: Arc<dyn AggregateUdf>, expr: Box<dyn PhysicalExpression>) -> Self {
+        AggregateUdfExpression { udf, expr }
+    }
+}
+
+impl fmt::Display for AggregateUdfExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.udf.name(), self.expr)
+    }
+}
+
 macro_rules! aggregateExpression {
     ($acc: ident,$expr: ident, $name1: ident, $name2: ident, $op_name: expr) => {
+        /// NaNs are skipped like nulls, unless a group only has NaNs, in which case the result
+        /// is NaN.
         pub struct $acc {
             value: Box<dyn Scalar>,
             index: usize,
+            /// The type of the skipped NaNs, if a NaN was skipped.
+            nan: Option<DataType>,
         }
 
         impl Accumulator for $acc {
@@ -383,12 +1182,45 @@ macro_rules! aggregateExpression {
                             (None, Some(val)) => Some(val.clone()),
                             (None, None) => None,
                         };
+                        let nans = match expr.data_type().to_physical_type() {
+                            PhysicalType::Primitive(PrimitiveType::Float32) => {
+                                let floats = expr
+                                    .as_any()
+                                    .downcast_ref::<PrimitiveArray<f32>>()
+                                    .ok_or(Error::DowncastError)?;
+                                Some(Bitmap::from_trusted_len_iter(
+                                    floats.values().iter().map(|x| x.is_nan()),
+                                ))
+                            }
+                            PhysicalType::Primitive(PrimitiveType::Float64) => {
+                                let floats = expr
+                                    .as_any()
+                                    .downcast_ref::<PrimitiveArray<f64>>()
+                                    .ok_or(Error::DowncastError)?;
+                                Some(Bitmap::from_trusted_len_iter(
+                                    floats.values().iter().map(|x| x.is_nan()),
+                                ))
+                            }
+                            _ => None,
+                        };
+                        let val = match nans {
+                            Some(nans) => Some(self.skip_nans(&nans, expr.data_type(), val)),
+                            None => val,
+                        };
                         compute::aggregate::$name1(
                             &*(expr.borrow() as &dyn Array).with_validity(val),
                         )
                         .map_err(|err| Error::ArrowError(err))
                     }
                     ColumnarValue::Scalar(scalar) => match scalar.data_type().to_physical_type() {
+                        PhysicalType::Primitive(PrimitiveType::Float32) => Ok(Box::new(
+                            scalar
+                                .as_any()
+                                .downcast_ref::<PrimitiveScalar<f32>>()
+                                .ok_or(Error::DowncastError)?
+                                .clone(),
+                        )
+                            as Box<dyn Scalar>),
                         PhysicalType::Primitive(PrimitiveType::Float64) => Ok(Box::new(
                             scalar
                                 .as_any()
@@ -405,13 +1237,99 @@ macro_rules! aggregateExpression {
                                 .clone(),
                         )
                             as Box<dyn Scalar>),
+                        PhysicalType::Utf8 => Ok(Box::new(
+                            scalar
+                                .as_any()
+                                .downcast_ref::<Utf8Scalar<i32>>()
+                                .ok_or(Error::DowncastError)?
+                                .clone(),
+                        ) as Box<dyn Scalar>),
                         x => Err(Error::PhysicalTypeNotSuported(format!("{:?}", x))),
                     },
                 }?;
+                self.update(new)
+            }
+            fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+                let other = other
+                    .into_any()
+                    .downcast::<$acc>()
+                    .map_err(|_| Error::DowncastError)?;
+                self.nan = self.nan.take().or(other.nan);
+                self.update(other.value)
+            }
+            fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+                match self.nan {
+                    Some(DataType::Float32) if !self.value.is_valid() => {
+                        return Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+                            DataType::Float32,
+                            Some(f32::NAN),
+                        ))))
+                    }
+                    Some(data_type) if !self.value.is_valid() => {
+                        return Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+                            data_type,
+                            Some(f64::NAN),
+                        ))))
+                    }
+                    _ => (),
+                }
+                Ok(ColumnarValue::Scalar(self.value))
+            }
+            fn into_any(self: Box<Self>) -> Box<dyn Any> {
+                self
+            }
+        }
+
+        impl $acc {
+            /// Removes the NaNs, given by `nans`, from the valid rows of a column of floats of type
+            /// `data_type`, given by `validity`.
+            fn skip_nans(
+                &mut self,
+                nans: &Bitmap,
+                data_type: &DataType,
+                validity: Option<Bitmap>,
+            ) -> Bitmap {
+                let valid = match validity {
+                    Some(validity) => validity,
+                    None => Bitmap::from_trusted_len_iter(std::iter::repeat(true).take(nans.len())),
+                };
+                let valid_nans = &valid & nans;
+                if valid_nans.null_count() < valid_nans.len() {
+                    self.nan = Some(data_type.clone());
+                }
+                &valid & &!nans
+            }
+
+            /// Replaces the value if `new` comes first in the order of the aggregate.
+            fn update(&mut self, new: Box<dyn Scalar>) -> Result<(), Error> {
                 let bool = match (
                     new.data_type().to_physical_type(),
                     self.value.data_type().to_physical_type(),
                 ) {
+                    (
+                        PhysicalType::Primitive(PrimitiveType::Float32),
+                        PhysicalType::Primitive(PrimitiveType::Float32),
+                    ) => {
+                        let (left, right) = (
+                            new.as_any()
+                                .downcast_ref::<PrimitiveScalar<f32>>()
+                                .ok_or(Error::DowncastError)?,
+                            self.value
+                                .as_any()
+                                .downcast_ref::<PrimitiveScalar<f32>>()
+                                .ok_or(Error::DowncastError)?,
+                        );
+                        match (left.value(), right.value()) {
+                            (Some(left), _) if left.is_nan() => {
+                                self.nan = Some(new.data_type().clone());
+                                Ok(false)
+                            }
+                            (Some(left), Some(right)) => Ok(left.$name2(&right)),
+                            // A group slice without any valid value doesn't change the result.
+                            (None, _) => Ok(false),
+                            (Some(_), None) => Ok(true),
+                        }
+                    }
                     (
                         PhysicalType::Primitive(PrimitiveType::Float64),
                         PhysicalType::Primitive(PrimitiveType::Float64),
@@ -426,8 +1344,14 @@ macro_rules! aggregateExpression {
                                 .ok_or(Error::DowncastError)?,
                         );
                         match (left.value(), right.value()) {
+                            (Some(left), _) if left.is_nan() => {
+                                self.nan = Some(new.data_type().clone());
+                                Ok(false)
+                            }
                             (Some(left), Some(right)) => Ok(left.$name2(&right)),
-                            _ => Err(Error::DowncastError),
+                            // A group slice without any valid value doesn't change the result.
+                            (None, _) => Ok(false),
+                            (Some(_), None) => Ok(true),
                         }
                     }
                     (
@@ -445,12 +1369,58 @@ macro_rules! aggregateExpression {
                         );
                         match (left.value(), right.value()) {
                             (Some(left), Some(right)) => Ok(left.$name2(&right)),
-                            _ => Err(Error::DowncastError),
+                            // A group slice without any valid value doesn't change the result.
+                            (None, _) => Ok(false),
+                            (Some(_), None) => Ok(true),
+                        }
+                    }
+                    // Strings are compared lexicographically.
+                    (PhysicalType::Utf8, PhysicalType::Utf8) => {
+                        let (left, right) = (
+                            new.as_any()
+                                .downcast_ref::<Utf8Scalar<i32>>()
+                                .ok_or(Error::DowncastError)?,
+                            self.value
+                                .as_any()
+                                .downcast_ref::<Utf8Scalar<i32>>()
+                                .ok_or(Error::DowncastError)?,
+                        );
+                        match (left.value(), right.value()) {
+                            (Some(left), Some(right)) => Ok(left.$name2(right)),
+                            // A group slice without any valid value doesn't change the result.
+                            (None, _) => Ok(false),
+                            (Some(_), None) => Ok(true),
+                        }
+                    }
+                    (PhysicalType::Primitive(PrimitiveType::Float32), PhysicalType::Null) => {
+                        match new
+                            .as_any()
+                            .downcast_ref::<PrimitiveScalar<f32>>()
+                            .ok_or(Error::DowncastError)?
+                            .value()
+                        {
+                            Some(value) if value.is_nan() => {
+                                self.nan = Some(new.data_type().clone());
+                                Ok(false)
+                            }
+                            _ => Ok(true),
                         }
                     }
                     (PhysicalType::Primitive(PrimitiveType::Float64), PhysicalType::Null) => {
-                        Ok(true)
+                        match new
+                            .as_any()
+                            .downcast_ref::<PrimitiveScalar<f64>>()
+                            .ok_or(Error::DowncastError)?
+                            .value()
+                        {
+                            Some(value) if value.is_nan() => {
+                                self.nan = Some(new.data_type().clone());
+                                Ok(false)
+                            }
+                            _ => Ok(true),
+                        }
                     }
+                    (PhysicalType::Utf8, PhysicalType::Null) => Ok(true),
                     (PhysicalType::Primitive(PrimitiveType::Int32), PhysicalType::Null) => Ok(true),
                     _ => Err(Error::PhysicalTypeNotSuported(format!(
                         "{:?}, {:?}",
@@ -463,17 +1433,18 @@ macro_rules! aggregateExpression {
                 };
                 Ok(())
             }
-            fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
-                Ok(ColumnarValue::Scalar(self.value))
-            }
         }
         pub struct $expr {
             expr: Box<dyn PhysicalExpression>,
         }
 
         impl PhysicalExpression for $expr {
-            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
-                self.expr.evaluate(input)
+            fn evaluate(
+                &self,
+                input: &Chunk<Arc<dyn Array>>,
+                schema: &Schema,
+            ) -> Result<ColumnarValue, Error> {
+                self.expr.evaluate(input, schema)
             }
         }
 
@@ -482,6 +1453,7 @@ macro_rules! aggregateExpression {
                 Box::new($acc {
                     value: Box::new(NullScalar::new()),
                     index: index,
+                    nan: None,
                 })
             }
         }
@@ -500,6 +1472,539 @@ macro_rules! aggregateExpression {
     };
 }
 
+/// Counts the non-null values of each group.
+pub struct CountAccumulator {
+    value: i64,
+    index: usize,
+}
+
+impl Accumulator for CountAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &Vec<ColumnarValue>,
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let count = match &input[self.index] {
+            ColumnarValue::Array(expr) => match (expr.validity(), validity) {
+                (Some(val1), Some(val2)) => {
+                    let val = val1.bitand(val2);
+                    val.len() - val.null_count()
+                }
+                (Some(val), None) | (None, Some(val)) => val.len() - val.null_count(),
+                (None, None) => expr.len(),
+            },
+            ColumnarValue::Scalar(scalar) => match (scalar.is_valid(), validity) {
+                (false, _) => 0,
+                (true, Some(val)) => val.len() - val.null_count(),
+                (true, None) => 1,
+            },
+        };
+        self.value += count as i64;
+        Ok(())
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        let other = other
+            .into_any()
+            .downcast::<CountAccumulator>()
+            .map_err(|_| Error::DowncastError)?;
+        self.value += other.value;
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+            DataType::Int64,
+            Some(self.value),
+        ))))
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+pub struct CountExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl PhysicalExpression for CountExpression {
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input, schema)
+    }
+}
+
+impl PhysicalAggregateExpression for CountExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(CountAccumulator { value: 0, index })
+    }
+}
+
+impl CountExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        CountExpression { expr }
+    }
+}
+
+impl fmt::Display for CountExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "count {}", self.expr)
+    }
+}
+
+/// Averages the non-null values of each group. Groups without any values average to null.
+pub struct AvgAccumulator {
+    sum: f64,
+    count: i64,
+    index: usize,
+}
+
+impl Accumulator for AvgAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &Vec<ColumnarValue>,
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let expr = match &input[self.index] {
+            ColumnarValue::Array(expr) => expr,
+            ColumnarValue::Scalar(scalar) => {
+                return Err(Error::PhysicalTypeNotSuported(format!(
+                    "{:?}",
+                    scalar.data_type()
+                )))
+            }
+        };
+        let val = match (expr.validity(), validity) {
+            (Some(val1), Some(val2)) => Some(val1.bitand(val2)),
+            (Some(val), None) | (None, Some(val)) => Some(val.clone()),
+            (None, None) => None,
+        };
+        let values: Vec<f64> = match expr.data_type().to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Float64) => expr
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .ok_or(Error::DowncastError)?
+                .values()
+                .to_vec(),
+            PhysicalType::Primitive(PrimitiveType::Int32) => expr
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .ok_or(Error::DowncastError)?
+                .values()
+                .iter()
+                .map(|x| *x as f64)
+                .collect(),
+            PhysicalType::Primitive(PrimitiveType::Int64) => expr
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i64>>()
+                .ok_or(Error::DowncastError)?
+                .values()
+                .iter()
+                .map(|x| *x as f64)
+                .collect(),
+            x => return Err(Error::PhysicalTypeNotSuported(format!("{:?}", x))),
+        };
+        match val {
+            Some(val) => values.iter().zip(val.iter()).for_each(|(value, valid)| {
+                if valid {
+                    self.sum += value;
+                    self.count += 1;
+                }
+            }),
+            None => {
+                self.sum += values.iter().sum::<f64>();
+                self.count += values.len() as i64;
+            }
+        }
+        Ok(())
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        let other = other
+            .into_any()
+            .downcast::<AvgAccumulator>()
+            .map_err(|_| Error::DowncastError)?;
+        self.sum += other.sum;
+        self.count += other.count;
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        let value = if self.count == 0 {
+            None
+        } else {
+            Some(self.sum / self.count as f64)
+        };
+        Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+            DataType::Float64,
+            value,
+        ))))
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+pub struct AvgExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl PhysicalExpression for AvgExpression {
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input, schema)
+    }
+}
+
+impl PhysicalAggregateExpression for AvgExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(AvgAccumulator {
+            sum: 0.0,
+            count: 0,
+            index,
+        })
+    }
+}
+
+impl AvgExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        AvgExpression { expr }
+    }
+}
+
+impl fmt::Display for AvgExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "avg {}", self.expr)
+    }
+}
+
 aggregateExpression!(MaxAccumulator, MaxExpression, max, gt, "max".to_string());
 aggregateExpression!(MinAccumulator, MinExpression, min, lt, "min".to_string());
-// aggregateExpression!(SumAccumulator, SumExpression, sum, add, "sum".to_string());
+
+/// Sums the non-null values of each group. Integers are accumulated in an i64, which returns an
+/// error on overflow, and floats in an f64. Groups without any values sum to null.
+pub struct SumAccumulator {
+    int_sum: i64,
+    float_sum: f64,
+    count: i64,
+    data_type: DataType,
+    index: usize,
+}
+
+impl Accumulator for SumAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &Vec<ColumnarValue>,
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let expr = match &input[self.index] {
+            ColumnarValue::Array(expr) => expr,
+            ColumnarValue::Scalar(scalar) => {
+                return Err(Error::PhysicalTypeNotSuported(format!(
+                    "{:?}",
+                    scalar.data_type()
+                )))
+            }
+        };
+        let val = match (expr.validity(), validity) {
+            (Some(val1), Some(val2)) => Some(val1.bitand(val2)),
+            (Some(val), None) | (None, Some(val)) => Some(val.clone()),
+            (None, None) => None,
+        };
+        let valid = |i: usize| val.as_ref().map(|val| val.get_bit(i)).unwrap_or(true);
+        let mut add_int = |x: i64| -> Result<(), Error> {
+            self.int_sum = self
+                .int_sum
+                .checked_add(x)
+                .ok_or_else(|| Error::IntegerOverflow("sum".to_string()))?;
+            self.count += 1;
+            Ok(())
+        };
+        match expr.data_type().to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Int32) => expr
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .ok_or(Error::DowncastError)?
+                .values()
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| valid(*i))
+                .try_for_each(|(_, x)| add_int(*x as i64))?,
+            PhysicalType::Primitive(PrimitiveType::Int64) => expr
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i64>>()
+                .ok_or(Error::DowncastError)?
+                .values()
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| valid(*i))
+                .try_for_each(|(_, x)| add_int(*x))?,
+            PhysicalType::Primitive(PrimitiveType::Float32) => expr
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f32>>()
+                .ok_or(Error::DowncastError)?
+                .values()
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| valid(*i))
+                .for_each(|(_, x)| {
+                    self.float_sum += *x as f64;
+                    self.count += 1;
+                }),
+            PhysicalType::Primitive(PrimitiveType::Float64) => expr
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .ok_or(Error::DowncastError)?
+                .values()
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| valid(*i))
+                .for_each(|(_, x)| {
+                    self.float_sum += *x;
+                    self.count += 1;
+                }),
+            x => return Err(Error::PhysicalTypeNotSuported(format!("{:?}", x))),
+        };
+        Ok(())
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        let other = other
+            .into_any()
+            .downcast::<SumAccumulator>()
+            .map_err(|_| Error::DowncastError)?;
+        self.int_sum = self
+            .int_sum
+            .checked_add(other.int_sum)
+            .ok_or_else(|| Error::IntegerOverflow("sum".to_string()))?;
+        self.float_sum += other.float_sum;
+        self.count += other.count;
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        let has_values = self.count > 0;
+        match self.data_type {
+            DataType::Int64 => Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+                DataType::Int64,
+                has_values.then_some(self.int_sum),
+            )))),
+            DataType::Float64 => Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+                DataType::Float64,
+                has_values.then_some(self.float_sum),
+            )))),
+            x => Err(Error::PhysicalTypeNotSuported(format!("{:?}", x))),
+        }
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+pub struct SumExpression {
+    expr: Box<dyn PhysicalExpression>,
+    data_type: DataType,
+}
+
+impl PhysicalExpression for SumExpression {
+    fn evaluate(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input, schema)
+    }
+}
+
+impl PhysicalAggregateExpression for SumExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(SumAccumulator {
+            int_sum: 0,
+            float_sum: 0.0,
+            count: 0,
+            data_type: self.data_type.clone(),
+            index,
+        })
+    }
+}
+
+impl SumExpression {
+    /// `data_type` is the type of the sum, Int64 for integer and Float64 for float inputs.
+    pub fn new(expr: Box<dyn PhysicalExpression>, data_type: DataType) -> Self {
+        SumExpression { expr, data_type }
+    }
+}
+
+impl fmt::Display for SumExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sum {}", self.expr)
+    }
+}
+
+/// An expression that is computed over all rows of its input in order. It keeps state between
+/// the batches, e.g. a running total.
+pub trait PhysicalWindowExpression: Display {
+    fn evaluate_window(
+        &mut self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<Arc<dyn Array>, Error>;
+}
+
+/// Adds the values of `array` to `total` and returns the total after every row. Nulls don't
+/// change the total. `add` returns None if the total overflows.
+fn running_total<T: NativeType, S: NativeType>(
+    array: &dyn Array,
+    total: &mut S,
+    add: impl Fn(S, T) -> Option<S>,
+) -> Result<Arc<dyn Array>, Error> {
+    let array = array
+        .as_any()
+        .downcast_ref::<PrimitiveArray<T>>()
+        .ok_or(Error::DowncastError)?;
+    Ok(Arc::new(PrimitiveArray::from_vec(
+        array
+            .iter()
+            .map(|value| {
+                if let Some(value) = value {
+                    *total = add(*total, *value)
+                        .ok_or_else(|| Error::IntegerOverflow("cumsum".to_string()))?;
+                }
+                Ok(*total)
+            })
+            .collect::<Result<_, Error>>()?,
+    )))
+}
+
+pub struct CumSumExpression {
+    expr: Box<dyn PhysicalExpression>,
+    int_sum: i64,
+    float_sum: f64,
+}
+
+impl CumSumExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        CumSumExpression {
+            expr,
+            int_sum: 0,
+            float_sum: 0.0,
+        }
+    }
+}
+
+impl PhysicalWindowExpression for CumSumExpression {
+    fn evaluate_window(
+        &mut self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<Arc<dyn Array>, Error> {
+        let array = self.expr.evaluate(input, schema)?.to_array(input.len());
+        match array.data_type().to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Int32) => {
+                running_total(array.as_ref(), &mut self.int_sum, |total, x: i32| {
+                    total.checked_add(x as i64)
+                })
+            }
+            PhysicalType::Primitive(PrimitiveType::Int64) => {
+                running_total(array.as_ref(), &mut self.int_sum, |total, x: i64| {
+                    total.checked_add(x)
+                })
+            }
+            PhysicalType::Primitive(PrimitiveType::Float32) => {
+                running_total(array.as_ref(), &mut self.float_sum, |total, x: f32| {
+                    Some(total + x as f64)
+                })
+            }
+            PhysicalType::Primitive(PrimitiveType::Float64) => {
+                running_total(array.as_ref(), &mut self.float_sum, |total, x: f64| {
+                    Some(total + x)
+                })
+            }
+            x => Err(Error::PhysicalTypeNotSuported(format!("{:?}", x))),
+        }
+    }
+}
+
+impl fmt::Display for CumSumExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cumsum {}", self.expr)
+    }
+}
+
+/// Ranks the rows by a sort key, assuming the input is sorted by it. A new rank starts whenever
+/// the key differs from the key of the previous row, nulls are equal to each other.
+pub struct RankExpression {
+    expr: Box<dyn PhysicalExpression>,
+    dense: bool,
+    /// Key of the last row of the previous batch.
+    last: Option<Arc<dyn Array>>,
+    rows: i64,
+    rank: i64,
+}
+
+impl RankExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>, dense: bool) -> Self {
+        RankExpression {
+            expr,
+            dense,
+            last: None,
+            rows: 0,
+            rank: 0,
+        }
+    }
+}
+
+/// Whether row `i` of `left` and row `j` of `right` hold the same value.
+fn values_equal(left: &dyn Array, right: &dyn Array, i: usize, j: usize) -> Result<bool, Error> {
+    match (left.is_null(i), right.is_null(j)) {
+        (true, true) => Ok(true),
+        (false, false) => Ok(build_compare(left, right).map_err(Error::ArrowError)?(i, j)
+            == std::cmp::Ordering::Equal),
+        _ => Ok(false),
+    }
+}
+
+impl PhysicalWindowExpression for RankExpression {
+    fn evaluate_window(
+        &mut self,
+        input: &Chunk<Arc<dyn Array>>,
+        schema: &Schema,
+    ) -> Result<Arc<dyn Array>, Error> {
+        let array = self.expr.evaluate(input, schema)?.to_array(input.len());
+        let compare = build_compare(array.as_ref(), array.as_ref()).map_err(Error::ArrowError)?;
+        let mut ranks = Vec::with_capacity(array.len());
+        for i in 0..array.len() {
+            let tie = match (i, &self.last) {
+                (0, None) => false,
+                (0, Some(last)) => values_equal(last.as_ref(), array.as_ref(), 0, 0)?,
+                (i, _) => match (array.is_null(i - 1), array.is_null(i)) {
+                    (true, true) => true,
+                    (false, false) => compare(i - 1, i) == std::cmp::Ordering::Equal,
+                    _ => false,
+                },
+            };
+            if !tie {
+                self.rank = if self.dense {
+                    self.rank + 1
+                } else {
+                    self.rows + 1
+                };
+            }
+            self.rows += 1;
+            ranks.push(self.rank);
+        }
+        if !array.is_empty() {
+            self.last = Some(Arc::from(array.slice(array.len() - 1, 1)));
+        }
+        Ok(Arc::new(PrimitiveArray::from_vec(ranks)))
+    }
+}
+
+impl fmt::Display for RankExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.dense {
+            true => write!(f, "dense_rank {}", self.expr),
+            false => write!(f, "rank {}", self.expr),
+        }
+    }
+}
@@ -1,13 +1,33 @@
+use arrow2::datatypes::DataType;
+
 use crate::logical_plan::logical_expression::{
-    Column, LiteralBool, LiteralFloat, LiteralInteger, LiteralString, LogicalExpression, Max, Min,
+    AggregateFunction, Avg, Column, Count, CumSum, Day, LiteralBool, LiteralFloat, LiteralInteger,
+    LiteralInterval, LiteralLong, LiteralNull, LiteralString, LogicalExpression, Max, Min, Month,
+    Rank, ScalarFunction, Sum, Wildcard, Year,
 };
 
+use crate::physical_plan::physical_expressions::AggregateUdf;
+
+pub use crate::columnar_value::ColumnarValue;
 pub use crate::logical_plan::logical_expression::LogicalExpressionMethods;
+pub use crate::physical_plan::Batch;
 
 pub fn col(name: &str) -> LogicalExpression {
     LogicalExpression::Column(Column::new(name.to_string()))
 }
 
+/// All columns of the input.
+pub fn wildcard() -> LogicalExpression {
+    all_except(vec![])
+}
+
+/// All columns of the input except the given ones.
+pub fn all_except(except: Vec<&str>) -> LogicalExpression {
+    LogicalExpression::Wildcard(Wildcard::new(
+        except.into_iter().map(|x| x.to_string()).collect(),
+    ))
+}
+
 pub fn lit_string(name: &str) -> LogicalExpression {
     LogicalExpression::LiteralString(LiteralString::new(name.to_string()))
 }
@@ -16,6 +36,10 @@ pub fn lit_int(value: i32) -> LogicalExpression {
     LogicalExpression::LiteralInteger(LiteralInteger::new(value))
 }
 
+pub fn lit_i64(value: i64) -> LogicalExpression {
+    LogicalExpression::LiteralLong(LiteralLong::new(value))
+}
+
 pub fn lit_float(value: f64) -> LogicalExpression {
     LogicalExpression::LiteralFloat(LiteralFloat::new(value))
 }
@@ -24,6 +48,19 @@ pub fn lit_bool(value: bool) -> LogicalExpression {
     LogicalExpression::LiteralBool(LiteralBool::new(value))
 }
 
+/// A null of the given type. Comparisons with it are null, so selections drop every row.
+pub fn lit_null(data_type: DataType) -> LogicalExpression {
+    LogicalExpression::LiteralNull(LiteralNull::new(data_type))
+}
+
+pub fn lit_interval_seconds(seconds: i64) -> LogicalExpression {
+    LogicalExpression::LiteralInterval(LiteralInterval::new(seconds))
+}
+
+pub fn lit_interval_days(days: i64) -> LogicalExpression {
+    lit_interval_seconds(days * 24 * 60 * 60)
+}
+
 pub fn max(expr: LogicalExpression) -> LogicalExpression {
     LogicalExpression::Max(Box::new(Max::new(expr)))
 }
@@ -31,3 +68,62 @@ pub fn max(expr: LogicalExpression) -> LogicalExpression {
 pub fn min(expr: LogicalExpression) -> LogicalExpression {
     LogicalExpression::Min(Box::new(Min::new(expr)))
 }
+
+pub fn sum(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Sum(Box::new(Sum::new(expr)))
+}
+
+pub fn avg(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Avg(Box::new(Avg::new(expr)))
+}
+
+pub fn count(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Count(Box::new(Count::new(expr)))
+}
+
+/// Calls the user-defined scalar function `name`, whose values have type `return_type`. The
+/// function has to be registered with the execution context that runs the query.
+pub fn udf(name: &str, return_type: DataType, args: Vec<LogicalExpression>) -> LogicalExpression {
+    LogicalExpression::ScalarUdf(Box::new(ScalarFunction::new(
+        name.to_string(),
+        return_type,
+        args,
+    )))
+}
+
+/// Aggregates `expr` with a user-defined aggregate. The aggregate has to be registered with the
+/// execution context that runs the query.
+pub fn aggregate(udf: &dyn AggregateUdf, expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::AggregateFunction(Box::new(AggregateFunction::new(
+        udf.name().to_string(),
+        udf.output_type(),
+        expr,
+    )))
+}
+
+/// Running total of `expr`, to be used with `DataFrameTrait::window`.
+pub fn cumsum(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::CumSum(Box::new(CumSum::new(expr)))
+}
+
+/// Rank by the sort key `expr`, with gaps after ties. The input has to be sorted by the key.
+pub fn rank(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Rank(Box::new(Rank::new(expr, false)))
+}
+
+/// Rank by the sort key `expr`, without gaps after ties. The input has to be sorted by the key.
+pub fn dense_rank(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Rank(Box::new(Rank::new(expr, true)))
+}
+
+pub fn year(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Year(Box::new(Year::new(expr)))
+}
+
+pub fn month(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Month(Box::new(Month::new(expr)))
+}
+
+pub fn day(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Day(Box::new(Day::new(expr)))
+}
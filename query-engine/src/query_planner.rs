@@ -1,14 +1,16 @@
 use arrow2::{
-    datatypes::DataType,
+    datatypes::{DataType, Schema},
     scalar::{BooleanScalar, PrimitiveScalar, Utf8Scalar},
 };
 
 use crate::{
     error::Error,
-    logical_plan::{logical_expression::LogicalExpression, LogicalPlan},
+    execution_context::ExecutionConfig,
+    logical_plan::{expand_wildcards, logical_expression::LogicalExpression, LogicalPlan, Values},
     physical_plan::{
-        physical_expressions::*, AggregateExec, PhysicalPlan, ProjectionExec, ScanExec,
-        SelectionExec,
+        physical_expressions::*, AggregateExec, DistinctExec, HashJoinExec, LimitExec,
+        PhysicalPlan, ProjectionExec, SampleExec, ScanExec, SelectionExec, SortMergeJoinExec,
+        UnnestExec, ValuesExec, WindowExec,
     },
 };
 
@@ -16,6 +18,7 @@ impl LogicalExpression {
     pub(crate) fn to_physical_expression(
         self,
         input: &LogicalPlan,
+        config: &ExecutionConfig,
     ) -> Result<Box<dyn PhysicalExpression>, Error> {
         match self {
             LogicalExpression::Column(col) => input
@@ -25,6 +28,15 @@ impl LogicalExpression {
                 .position(|x| x.name == col.name)
                 .ok_or(Error::NoFieldInLogicalPlan(format!("{}", col)))
                 .map(|index| Box::new(ColumnExpression { index }) as Box<dyn PhysicalExpression>),
+            LogicalExpression::Alias(alias) => alias.expr.to_physical_expression(input, config),
+            LogicalExpression::Cast(cast) => {
+                let expr = cast.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(CastExpression::new(
+                    expr,
+                    cast.data_type,
+                    config.coercion_mode,
+                )) as Box<dyn PhysicalExpression>)
+            }
             LogicalExpression::LiteralBool(bool) => Ok(Box::new(LiteralBoolExpression {
                 value: BooleanScalar::new(Some(bool.value)),
             })
@@ -37,46 +49,127 @@ impl LogicalExpression {
                 value: PrimitiveScalar::new(DataType::Int32, Some(int.value)),
             })
                 as Box<dyn PhysicalExpression>),
+            LogicalExpression::LiteralLong(long) => {
+                Ok(Box::new(LiteralLongExpression::new(long.value)) as Box<dyn PhysicalExpression>)
+            }
             LogicalExpression::LiteralFloat(float) => Ok(Box::new(LiteralFloatExpression {
                 value: PrimitiveScalar::new(DataType::Float64, Some(float.value)),
             })
                 as Box<dyn PhysicalExpression>),
+            LogicalExpression::LiteralNull(null) => {
+                Ok(Box::new(LiteralNullExpression::new(null.data_type))
+                    as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::LiteralInterval(interval) => {
+                Ok(Box::new(LiteralIntervalExpression::new(interval.seconds))
+                    as Box<dyn PhysicalExpression>)
+            }
             LogicalExpression::Eq(eq) => {
-                let left = eq.left.to_physical_expression(input)?;
-                let right = eq.right.to_physical_expression(input)?;
+                let left = eq.left.to_physical_expression(input, config)?;
+                let right = eq.right.to_physical_expression(input, config)?;
                 Ok(Box::new(EqExpression::new(left, right)) as Box<dyn PhysicalExpression>)
             }
             LogicalExpression::Neq(neq) => {
-                let left = neq.left.to_physical_expression(input)?;
-                let right = neq.right.to_physical_expression(input)?;
+                let left = neq.left.to_physical_expression(input, config)?;
+                let right = neq.right.to_physical_expression(input, config)?;
                 Ok(Box::new(NeqExpression::new(left, right)) as Box<dyn PhysicalExpression>)
             }
+            LogicalExpression::NullSafeEq(eq) => {
+                let left = eq.left.to_physical_expression(input, config)?;
+                let right = eq.right.to_physical_expression(input, config)?;
+                Ok(Box::new(NullSafeEqExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::Gt(gt) => {
+                let left = gt.left.to_physical_expression(input, config)?;
+                let right = gt.right.to_physical_expression(input, config)?;
+                Ok(Box::new(GtExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::GtEq(gteq) => {
+                let left = gteq.left.to_physical_expression(input, config)?;
+                let right = gteq.right.to_physical_expression(input, config)?;
+                Ok(Box::new(GtEqExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::Lt(lt) => {
+                let left = lt.left.to_physical_expression(input, config)?;
+                let right = lt.right.to_physical_expression(input, config)?;
+                Ok(Box::new(LtExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::LtEq(lteq) => {
+                let left = lteq.left.to_physical_expression(input, config)?;
+                let right = lteq.right.to_physical_expression(input, config)?;
+                Ok(Box::new(LtEqExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::And(and) => {
+                let left = and.left.to_physical_expression(input, config)?;
+                let right = and.right.to_physical_expression(input, config)?;
+                Ok(Box::new(AndExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::Or(or) => {
+                let left = or.left.to_physical_expression(input, config)?;
+                let right = or.right.to_physical_expression(input, config)?;
+                Ok(Box::new(OrExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
             LogicalExpression::Add(add) => {
-                let left = add.left.to_physical_expression(input)?;
-                let right = add.right.to_physical_expression(input)?;
+                let left = add.left.to_physical_expression(input, config)?;
+                let right = add.right.to_physical_expression(input, config)?;
                 Ok(Box::new(AddExpression::new(left, right)) as Box<dyn PhysicalExpression>)
             }
             LogicalExpression::Sub(sub) => {
-                let left = sub.left.to_physical_expression(input)?;
-                let right = sub.right.to_physical_expression(input)?;
+                let left = sub.left.to_physical_expression(input, config)?;
+                let right = sub.right.to_physical_expression(input, config)?;
                 Ok(Box::new(SubExpression::new(left, right)) as Box<dyn PhysicalExpression>)
             }
             LogicalExpression::Mul(mul) => {
-                let left = mul.left.to_physical_expression(input)?;
-                let right = mul.right.to_physical_expression(input)?;
+                let left = mul.left.to_physical_expression(input, config)?;
+                let right = mul.right.to_physical_expression(input, config)?;
                 Ok(Box::new(MulExpression::new(left, right)) as Box<dyn PhysicalExpression>)
             }
             LogicalExpression::Div(div) => {
-                let left = div.left.to_physical_expression(input)?;
-                let right = div.right.to_physical_expression(input)?;
+                let left = div.left.to_physical_expression(input, config)?;
+                let right = div.right.to_physical_expression(input, config)?;
                 Ok(Box::new(DivExpression::new(left, right)) as Box<dyn PhysicalExpression>)
             }
+            LogicalExpression::Mod(modu) => {
+                let left = modu.left.to_physical_expression(input, config)?;
+                let right = modu.right.to_physical_expression(input, config)?;
+                Ok(Box::new(ModExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::ScalarUdf(fun) => {
+                let udf = config
+                    .functions
+                    .get(fun.name())
+                    .ok_or_else(|| Error::FunctionNotFound(fun.name().to_string()))?
+                    .clone();
+                let name = fun.name().to_string();
+                let data_type = fun.data_type().clone();
+                let args = fun
+                    .args
+                    .into_iter()
+                    .map(|x| x.to_physical_expression(input, config))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Ok(
+                    Box::new(ScalarUdfExpression::new(name, data_type, udf, args))
+                        as Box<dyn PhysicalExpression>,
+                )
+            }
+            LogicalExpression::Year(year) => {
+                let expr = year.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(YearExpression::new(expr)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::Month(month) => {
+                let expr = month.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(MonthExpression::new(expr)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::Day(day) => {
+                let expr = day.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(DayExpression::new(expr)) as Box<dyn PhysicalExpression>)
+            }
             LogicalExpression::Max(max) => {
-                let expr = max.expr.to_physical_expression(input)?;
+                let expr = max.expr.to_physical_expression(input, config)?;
                 Ok(Box::new(MaxExpression::new(expr)) as Box<dyn PhysicalExpression>)
             }
             LogicalExpression::Min(min) => {
-                let expr = min.expr.to_physical_expression(input)?;
+                let expr = min.expr.to_physical_expression(input, config)?;
                 Ok(Box::new(MinExpression::new(expr)) as Box<dyn PhysicalExpression>)
             }
             e => Err(Error::PhysicalExpressionNotSuported(format!("{}", e))),
@@ -86,16 +179,67 @@ impl LogicalExpression {
     pub(crate) fn to_physical_aggregate_expression(
         self,
         input: &LogicalPlan,
+        config: &ExecutionConfig,
     ) -> Result<Box<dyn PhysicalAggregateExpression>, Error> {
         match self {
+            LogicalExpression::Alias(alias) => {
+                alias.expr.to_physical_aggregate_expression(input, config)
+            }
             LogicalExpression::Max(max) => {
-                let expr = max.expr.to_physical_expression(input)?;
+                let expr = max.expr.to_physical_expression(input, config)?;
                 Ok(Box::new(MaxExpression::new(expr)) as Box<dyn PhysicalAggregateExpression>)
             }
             LogicalExpression::Min(min) => {
-                let expr = min.expr.to_physical_expression(input)?;
+                let expr = min.expr.to_physical_expression(input, config)?;
                 Ok(Box::new(MinExpression::new(expr)) as Box<dyn PhysicalAggregateExpression>)
             }
+            LogicalExpression::Count(count) => {
+                let expr = count.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(CountExpression::new(expr)) as Box<dyn PhysicalAggregateExpression>)
+            }
+            LogicalExpression::Avg(avg) => {
+                let expr = avg.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(AvgExpression::new(expr)) as Box<dyn PhysicalAggregateExpression>)
+            }
+            LogicalExpression::Sum(sum) => {
+                let data_type = sum.to_field(input)?.data_type;
+                let expr = sum.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(SumExpression::new(expr, data_type))
+                    as Box<dyn PhysicalAggregateExpression>)
+            }
+            LogicalExpression::AggregateFunction(fun) => {
+                let udf = config
+                    .aggregates
+                    .get(fun.name())
+                    .ok_or_else(|| Error::AggregateNotFound(fun.name().to_string()))?
+                    .clone();
+                let expr = fun.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(AggregateUdfExpression::new(udf, expr))
+                    as Box<dyn PhysicalAggregateExpression>)
+            }
+            LogicalExpression::Column(col) => Err(Error::NotAnAggregateExpression(col.name)),
+            e => Err(Error::PhysicalExpressionNotSuported(format!("{}", e))),
+        }
+    }
+}
+
+impl LogicalExpression {
+    pub(crate) fn create_window_expression(
+        self,
+        input: &LogicalPlan,
+        config: &ExecutionConfig,
+    ) -> Result<Box<dyn PhysicalWindowExpression>, Error> {
+        match self {
+            LogicalExpression::Alias(alias) => alias.expr.create_window_expression(input, config),
+            LogicalExpression::CumSum(cumsum) => {
+                let expr = cumsum.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(CumSumExpression::new(expr)) as Box<dyn PhysicalWindowExpression>)
+            }
+            LogicalExpression::Rank(rank) => {
+                let expr = rank.expr.to_physical_expression(input, config)?;
+                Ok(Box::new(RankExpression::new(expr, rank.dense))
+                    as Box<dyn PhysicalWindowExpression>)
+            }
             e => Err(Error::PhysicalExpressionNotSuported(format!("{}", e))),
         }
     }
@@ -103,64 +247,250 @@ impl LogicalExpression {
 
 impl LogicalPlan {
     pub(crate) fn to_physical_plan(self) -> Result<PhysicalPlan, Error> {
+        self.create_physical_plan(&ExecutionConfig::default())
+    }
+
+    pub(crate) fn create_physical_plan(
+        self,
+        config: &ExecutionConfig,
+    ) -> Result<PhysicalPlan, Error> {
         match self {
-            LogicalPlan::Scan(scan) => Ok(PhysicalPlan::Scan(ScanExec::new(
-                scan.data_source,
-                scan.projection,
-                scan.schema,
-            ))),
+            LogicalPlan::Scan(scan) => Ok(PhysicalPlan::Scan(
+                ScanExec::new(scan.data_source, scan.projection, scan.schema)
+                    .with_ordinals(scan.ordinals)
+                    .with_filters(scan.filters)
+                    .with_batch_size(config.batch_size)?
+                    .with_limit(scan.limit),
+            )),
             LogicalPlan::Projection(proj) => {
                 let input = &proj.children[0];
-                let exprs = proj
-                    .exprs
+                let schema = proj.schema()?.clone();
+                let exprs = expand_wildcards(proj.exprs, input)?;
+                let exprs = exprs
                     .into_iter()
-                    .map(|x| x.to_physical_expression(input))
+                    .map(|x| x.to_physical_expression(input, config))
                     .collect::<Result<Vec<_>, _>>()?;
                 let children = proj
                     .children
                     .into_iter()
-                    .map(|x| x.to_physical_plan())
+                    .map(|x| x.create_physical_plan(config))
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(PhysicalPlan::Projection(ProjectionExec::new(
-                    children,
-                    exprs,
-                    proj.schema,
-                )))
+                    children, exprs, schema,
+                )?))
             }
             LogicalPlan::Selection(sel) => {
-                let expr = sel.expr.to_physical_expression(&sel.children[0])?;
+                let schema = sel.schema()?.clone();
+                let field = sel.expr.to_field(&sel.children[0])?;
+                if field.data_type != DataType::Boolean {
+                    return Err(Error::NonBooleanPredicate(
+                        format!("{}", sel.expr),
+                        format!("{:?}", field.data_type),
+                    ));
+                }
+                let expr = sel.expr.to_physical_expression(&sel.children[0], config)?;
                 let children = sel
                     .children
                     .into_iter()
-                    .map(|x| x.to_physical_plan())
+                    .map(|x| x.create_physical_plan(config))
                     .collect::<Result<Vec<_>, _>>()?;
-                Ok(PhysicalPlan::Selection(SelectionExec::new(
-                    children, expr, sel.schema,
-                )))
+                Ok(PhysicalPlan::Selection(
+                    SelectionExec::new(children, expr, schema)?
+                        .with_target_batch_size(config.target_batch_size),
+                ))
             }
             LogicalPlan::Aggregate(agg) => {
                 let input = &agg.children[0];
+                // Converted first, so that a plain column among the aggregates is reported as
+                // such rather than as a duplicate of the group column.
+                let agg_exprs = agg
+                    .aggregate_exprs
+                    .iter()
+                    .cloned()
+                    .map(|x| x.to_physical_aggregate_expression(input, config))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let schema = agg.schema()?.clone();
+                if let Some(values) = agg.statistics_values() {
+                    return LogicalPlan::Values(values).create_physical_plan(config);
+                }
                 let group_exprs = agg
                     .group_exprs
                     .into_iter()
-                    .map(|x| x.to_physical_expression(input))
+                    .map(|x| x.to_physical_expression(input, config))
                     .collect::<Result<Vec<_>, _>>()?;
-                let agg_exprs = agg
-                    .aggregate_exprs
+                let children = agg
+                    .children
                     .into_iter()
-                    .map(|x| x.to_physical_aggregate_expression(input))
+                    .map(|x| x.create_physical_plan(config))
                     .collect::<Result<Vec<_>, _>>()?;
-                let children = agg
+                Ok(PhysicalPlan::Aggregate(
+                    AggregateExec::new(children, group_exprs, agg_exprs, schema)?
+                        .with_dictionary_keys(config.dictionary_group_keys),
+                ))
+            }
+            LogicalPlan::Values(values) => {
+                // The rows only contain literals, so they don't reference any input columns.
+                let input = LogicalPlan::Values(Values::new(Schema::default(), vec![]));
+                let rows = values
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        row.into_iter()
+                            .map(|x| x.to_physical_expression(&input, config))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PhysicalPlan::Values(ValuesExec::new(rows, values.schema)))
+            }
+            LogicalPlan::Sample(sample) => {
+                let schema = sample.schema()?.clone();
+                let children = sample
                     .children
                     .into_iter()
-                    .map(|x| x.to_physical_plan())
+                    .map(|x| x.create_physical_plan(config))
                     .collect::<Result<Vec<_>, _>>()?;
-                Ok(PhysicalPlan::Aggregate(AggregateExec::new(
+                Ok(PhysicalPlan::Sample(SampleExec::new(
                     children,
-                    group_exprs,
-                    agg_exprs,
-                    agg.schema,
-                )))
+                    sample.fraction,
+                    sample.seed,
+                    schema,
+                )?))
+            }
+            LogicalPlan::Limit(limit) => {
+                let schema = limit.schema()?.clone();
+                let children = limit
+                    .children
+                    .into_iter()
+                    .map(|x| x.limit_push_down(limit.n).create_physical_plan(config))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PhysicalPlan::Limit(LimitExec::new(
+                    children, limit.n, schema,
+                )?))
+            }
+            LogicalPlan::Join(join) => {
+                let schema = join.schema()?.clone();
+                let position = |input: &LogicalPlan, name: &str| {
+                    input
+                        .schema()?
+                        .fields
+                        .iter()
+                        .position(|x| x.name == name)
+                        .ok_or_else(|| Error::NoFieldInLogicalPlan(name.to_string()))
+                };
+                let on = join
+                    .on
+                    .iter()
+                    .map(|(left, right)| {
+                        Ok((
+                            position(&join.children[0], left)?,
+                            position(&join.children[1], right)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                // Inputs that are both sorted on their keys are merged instead of hashed.
+                let (left_keys, right_keys): (Vec<String>, Vec<String>) =
+                    join.on.iter().cloned().unzip();
+                let sorted =
+                    |input: &LogicalPlan, keys: &[String]| input.sorted_by().starts_with(keys);
+                if !left_keys.is_empty()
+                    && sorted(&join.children[0], &left_keys)
+                    && sorted(&join.children[1], &right_keys)
+                {
+                    let children = join
+                        .children
+                        .into_iter()
+                        .map(|x| x.create_physical_plan(config))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    return Ok(PhysicalPlan::SortMergeJoin(
+                        SortMergeJoinExec::new(children, on, schema)?
+                            .with_null_equals_null(config.null_equals_null),
+                    ));
+                }
+                // The hash table is built from the input with fewer rows.
+                let build_left = match (
+                    join.children[0].estimated_rows(),
+                    join.children[1].estimated_rows(),
+                ) {
+                    (Some(left), Some(right)) => left < right,
+                    _ => false,
+                };
+                let children = join
+                    .children
+                    .into_iter()
+                    .map(|x| x.create_physical_plan(config))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PhysicalPlan::HashJoin(
+                    HashJoinExec::new(children, on, schema)?
+                        .with_build_left(build_left)
+                        .with_null_equals_null(config.null_equals_null),
+                ))
+            }
+            LogicalPlan::Window(window) => {
+                let schema = window.schema()?.clone();
+                let input = &window.children[0];
+                let exprs = window
+                    .exprs
+                    .into_iter()
+                    .map(|x| x.create_window_expression(input, config))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let children = window
+                    .children
+                    .into_iter()
+                    .map(|x| x.create_physical_plan(config))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PhysicalPlan::Window(WindowExec::new(
+                    children, exprs, schema,
+                )?))
+            }
+            LogicalPlan::Unnest(unnest) => {
+                let schema = unnest.schema()?.clone();
+                let input = unnest.children[0].schema()?;
+                let column = input
+                    .fields
+                    .iter()
+                    .position(|x| x.name == unnest.column)
+                    .ok_or_else(|| Error::NoFieldInLogicalPlan(unnest.column.clone()))?;
+                match &input.fields[column].data_type {
+                    DataType::List(_) | DataType::LargeList(_) => (),
+                    data_type => {
+                        return Err(Error::NotAList(
+                            unnest.column.clone(),
+                            format!("{:?}", data_type),
+                        ))
+                    }
+                }
+                let children = unnest
+                    .children
+                    .into_iter()
+                    .map(|x| x.create_physical_plan(config))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PhysicalPlan::Unnest(
+                    UnnestExec::new(children, column, schema)?
+                        .with_preserve_empty(unnest.preserve_empty),
+                ))
+            }
+            LogicalPlan::Distinct(distinct) => {
+                let schema = distinct.schema()?.clone();
+                let input = distinct.children[0].schema()?;
+                let on = distinct
+                    .on
+                    .iter()
+                    .map(|name| {
+                        input
+                            .fields
+                            .iter()
+                            .position(|x| &x.name == name)
+                            .ok_or_else(|| Error::NoFieldInLogicalPlan(name.clone()))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let children = distinct
+                    .children
+                    .into_iter()
+                    .map(|x| x.create_physical_plan(config))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PhysicalPlan::Distinct(DistinctExec::new(
+                    children, on, schema,
+                )?))
             }
         }
     }
@@ -1,7 +1,49 @@
-use arrow2::array::{PrimitiveArray, Utf8Array};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    sync::Arc,
+};
+
+use arrow2::{
+    array::{
+        new_null_array, Array, BooleanArray, ListArray, MutableListArray, MutablePrimitiveArray,
+        NullArray, PrimitiveArray, TryExtend, Utf8Array,
+    },
+    bitmap::Bitmap,
+    chunk::Chunk,
+    compute,
+    datatypes::{DataType, Field, Schema},
+    io::parquet::read::read_metadata,
+    scalar::{BooleanScalar, PrimitiveScalar, Utf8Scalar},
+};
 
 use crate::{
+    columnar_value::ColumnarValue,
+    data_sink::{write_parquet, ParquetCompression, WriteParquetOptions},
+    data_source::{
+        CsvReadOptions, DataSource, ParquetDataSource, ParquetFilesDataSource, ParquetReadOptions,
+    },
     dataframe::{DataFrame, DataFrameTrait},
+    error::Error,
+    execution_context::{CoercionMode, ExecutionConfig, ExecutionContext},
+    logical_plan::{
+        format_logical_plan,
+        logical_expression::LogicalExpression,
+        optimizer::{PrunablePredicate, PruningLiteral, PruningOperator},
+        Limit, LogicalPlan, Projection, Scan, Selection,
+    },
+    physical_plan::{
+        append_to_builder, concat_chunks, new_builder,
+        physical_expressions::{
+            Accumulator, AggregateUdf, AvgExpression, ColumnExpression, CountExpression,
+            LiteralBoolExpression, LiteralFloatExpression, LiteralIntegerExpression,
+            LiteralStringExpression, MaxExpression, MinExpression, NamedColumnExpression,
+            PhysicalAggregateExpression, PhysicalExpression, SumExpression,
+        },
+        row_hashes, AggregateExec, DistinctExec, HashJoinExec, PhysicalPlan, ProjectionExec,
+        RepartitionExec, SampleExec, SelectionExec,
+    },
     prelude::*,
 };
 
@@ -28,7 +70,7 @@ use crate::{
 #[test]
 fn test_schema() {
     let df = DataFrame::parquet("src/tests/userdata.parquet");
-    assert_eq!(format!("{:?}", df.schema().fields), "[Field { name: \"registration_dttm\", data_type: Timestamp(Nanosecond, None), is_nullable: true, metadata: {} }, Field { name: \"id\", data_type: Int32, is_nullable: true, metadata: {} }, Field { name: \"first_name\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"last_name\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"email\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"gender\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"ip_address\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"cc\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"country\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"birthdate\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"salary\", data_type: Float64, is_nullable: true, metadata: {} }, Field { name: \"title\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"comments\", data_type: Utf8, is_nullable: true, metadata: {} }]");
+    assert_eq!(format!("{:?}", df.schema().unwrap().fields), "[Field { name: \"registration_dttm\", data_type: Timestamp(Nanosecond, None), is_nullable: true, metadata: {} }, Field { name: \"id\", data_type: Int32, is_nullable: true, metadata: {} }, Field { name: \"first_name\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"last_name\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"email\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"gender\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"ip_address\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"cc\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"country\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"birthdate\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"salary\", data_type: Float64, is_nullable: true, metadata: {} }, Field { name: \"title\", data_type: Utf8, is_nullable: true, metadata: {} }, Field { name: \"comments\", data_type: Utf8, is_nullable: true, metadata: {} }]");
 }
 
 #[test]
@@ -189,19 +231,4747 @@ fn test_filter() {
 
 #[test]
 fn test_max() {
+    let mut expected: HashMap<String, Option<f64>> = HashMap::new();
+    DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("country"), col("salary")])
+        .execute()
+        .unwrap()
+        .iter()
+        .for_each(|chunk| {
+            let countries = chunk[0]
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .expect("Failed to downcast array to utf8 array.");
+            let salaries = chunk[1]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.");
+            countries
+                .iter()
+                .zip(salaries.iter())
+                .for_each(|(country, salary)| {
+                    let entry = expected.entry(country.unwrap().to_string()).or_default();
+                    *entry = match (*entry, salary.copied()) {
+                        (Some(max), Some(salary)) => Some(max.max(salary)),
+                        (max, salary) => max.or(salary),
+                    };
+                })
+        });
+
     let result = DataFrame::parquet("src/tests/userdata.parquet")
         .aggregate(vec![col("country")], vec![max(col("salary"))])
         .execute()
         .unwrap();
+    let result = result[0][0]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .expect("Failed to downcast array to utf8 array.")
+        .iter()
+        .map(|x| x.unwrap().to_string())
+        .zip(
+            result[0][1]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .map(|x| x.copied()),
+        )
+        .collect::<HashMap<String, Option<f64>>>();
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_filter_non_boolean_predicate() {
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("salary"))
+        .execute();
+    assert!(matches!(
+        result,
+        Err(Error::NonBooleanPredicate(expr, data_type)) if expr == "#salary" && data_type == "Float64"
+    ));
+}
+
+#[test]
+fn test_projection_duplicate_column() {
+    let df = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id"), col("id").alias("id_copy")]);
+    assert_eq!(df.schema().unwrap().fields.len(), 2);
+    let plan = df.logical_plan().optimize();
+    match &plan {
+        LogicalPlan::Projection(proj) => match &proj.children[0] {
+            LogicalPlan::Scan(scan) => assert_eq!(scan.projection, Some(vec!["id".to_string()])),
+            _ => panic!("Expected scan below projection."),
+        },
+        _ => panic!("Expected projection at the top of the plan."),
+    }
+    let result = plan
+        .to_physical_plan()
+        .unwrap()
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(result[0].arrays().len(), 2);
+    let first = result[0][0]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i32>>()
+        .expect("Failed to downcast array to primitive array.");
+    let second = result[0][1]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i32>>()
+        .expect("Failed to downcast array to primitive array.");
+    assert_eq!(first, second);
+    assert_eq!(first.value(0), 1);
+}
+
+#[test]
+fn test_execution_context_table() {
+    let mut ctx = ExecutionContext::new();
+    ctx.register_parquet("users", "src/tests/userdata.parquet")
+        .unwrap();
+    let result = ctx
+        .table("users")
+        .unwrap()
+        .project(vec![col("first_name")])
+        .execute()
+        .unwrap();
     assert_eq!(
-        format!(
-            "{:?}",
-            result[0][0]
+        result[0][0]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .expect("Failed to downcast array to utf8 array.")
+            .value(0),
+        "Amanda"
+    );
+    // A registered table can be scanned more than once.
+    assert_eq!(
+        ctx.table("users").unwrap().execute().unwrap()[0].len(),
+        1000
+    );
+    assert!(matches!(
+        ctx.table("unknown"),
+        Err(Error::TableNotFound(name)) if name == "unknown"
+    ));
+}
+
+#[test]
+fn test_selection_target_batch_size() {
+    let filter = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(col("country").eq(lit_string("China")))
+    };
+    // The plan is executed as built, the optimizer would move the filter into the scan.
+    let execute = |config: &ExecutionConfig| {
+        filter()
+            .logical_plan()
+            .create_physical_plan(config)
+            .unwrap()
+            .execute()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    };
+    let small_batches = execute(&ExecutionConfig {
+        batch_size: Some(50),
+        ..Default::default()
+    });
+    assert_eq!(small_batches.len(), 20);
+    let total_rows = small_batches.iter().map(|x| x.len()).sum::<usize>();
+
+    let ctx = ExecutionContext::with_config(ExecutionConfig {
+        batch_size: Some(50),
+        target_batch_size: Some(64),
+        ..Default::default()
+    });
+    let result = execute(ctx.config());
+    assert!(result.len() < small_batches.len());
+    assert_eq!(result.iter().map(|x| x.len()).sum::<usize>(), total_rows);
+    let (last, batches) = result.split_last().unwrap();
+    assert!(batches.iter().all(|x| x.len() >= 64));
+    assert!(!last.is_empty());
+}
+
+#[test]
+fn test_zero_batch_size() {
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id")])
+        .execute_with_config(&ExecutionConfig {
+            batch_size: Some(0),
+            ..Default::default()
+        });
+    assert!(matches!(result, Err(Error::ZeroBatchSize)));
+}
+
+#[test]
+fn test_prunable_predicates() {
+    let selection = Selection::new(
+        DataFrame::parquet("src/tests/userdata.parquet").logical_plan(),
+        col("id")
+            .gt(lit_int(5))
+            .and(col("salary").eq(lit_float(1.0)))
+            .and(lit_int(10).gteq(col("id"))),
+    );
+    assert_eq!(
+        selection.prunable_predicates(),
+        vec![
+            PrunablePredicate {
+                column: "id".to_string(),
+                op: PruningOperator::Gt,
+                literal: PruningLiteral::Integer(5),
+            },
+            PrunablePredicate {
+                column: "salary".to_string(),
+                op: PruningOperator::Eq,
+                literal: PruningLiteral::Float(1.0),
+            },
+            PrunablePredicate {
+                column: "id".to_string(),
+                op: PruningOperator::LtEq,
+                literal: PruningLiteral::Integer(10),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_prunable_predicates_skip_unsupported() {
+    let selection = Selection::new(
+        DataFrame::parquet("src/tests/userdata.parquet").logical_plan(),
+        col("id")
+            .lt(lit_int(5))
+            .or(col("id").gt(lit_int(10)))
+            .and(col("id").eq(col("salary")))
+            .and(col("gender").neq(lit_string("Female"))),
+    );
+    assert_eq!(
+        selection.prunable_predicates(),
+        vec![PrunablePredicate {
+            column: "gender".to_string(),
+            op: PruningOperator::Neq,
+            literal: PruningLiteral::String("Female".to_string()),
+        }]
+    );
+}
+
+#[test]
+fn test_projection_list_column() {
+    let mut tags = MutableListArray::<i32, MutablePrimitiveArray<i32>>::new();
+    tags.try_extend(vec![
+        Some(vec![Some(1), Some(2)]),
+        None,
+        Some(vec![Some(3)]),
+    ])
+    .unwrap();
+    let tags: ListArray<i32> = tags.into();
+    let schema = Schema::from(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("tags", tags.data_type().clone(), true),
+    ]);
+    let chunk = Chunk::new(vec![
+        Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3])) as Arc<dyn Array>,
+        Arc::new(tags.clone()),
+    ]);
+
+    let result = DataFrame::memory(schema, vec![chunk])
+        .project(vec![col("tags")])
+        .execute()
+        .unwrap();
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<ListArray<i32>>()
+            .expect("Failed to downcast array to list array."),
+        &tags
+    );
+}
+
+#[test]
+fn test_sample() {
+    let rows = |fraction: f64, seed: u64| {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .sample(fraction, seed)
+            .execute()
+            .unwrap()
+            .iter()
+            .map(|x| x.len())
+            .sum::<usize>()
+    };
+    assert_eq!(rows(1.0, 42), 1000);
+    assert_eq!(rows(0.0, 42), 0);
+    let sampled = rows(0.1, 42);
+    assert!(sampled > 0 && sampled < 200);
+    assert_eq!(rows(0.1, 42), sampled);
+}
+
+#[test]
+fn test_between() {
+    let mut sorted = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("salary")])
+        .execute()
+        .unwrap()
+        .iter()
+        .flat_map(|chunk| {
+            chunk[0]
                 .as_any()
                 .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .flatten()
+                .copied()
+                .collect::<Vec<f64>>()
+        })
+        .collect::<Vec<f64>>();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let (low, high) = (sorted[100], sorted[200]);
+
+    let selected = |predicate: LogicalExpression| {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(predicate)
+            .project(vec![col("salary")])
+            .execute()
+            .unwrap()
+            .iter()
+            .flat_map(|chunk| {
+                chunk[0]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<f64>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .iter()
+                    .map(|x| x.copied())
+                    .collect::<Vec<Option<f64>>>()
+            })
+            .collect::<Vec<Option<f64>>>()
+    };
+    let between = selected(col("salary").between(lit_float(low), lit_float(high)));
+    let explicit = selected(
+        col("salary")
+            .gteq(lit_float(low))
+            .and(col("salary").lteq(lit_float(high))),
+    );
+    assert_eq!(between, explicit);
+    assert_eq!(
+        between.len(),
+        sorted.iter().filter(|x| **x >= low && **x <= high).count()
+    );
+    assert!(between.contains(&Some(low)));
+    assert!(between.contains(&Some(high)));
+}
+
+#[test]
+fn test_write_parquet_row_groups() {
+    let path = std::env::temp_dir().join("query_engine_test_write_parquet_row_groups.parquet");
+    let path = path.to_str().unwrap();
+    for compression in [
+        ParquetCompression::Uncompressed,
+        ParquetCompression::Snappy,
+        ParquetCompression::Zstd,
+    ] {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![col("id"), col("first_name"), col("salary")])
+            .write_parquet_with(
+                path,
+                &WriteParquetOptions {
+                    row_group_size: 300,
+                    compression,
+                },
+            )
+            .unwrap();
+        let metadata = read_metadata(&mut std::fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(
+            metadata
+                .row_groups
+                .iter()
+                .map(|x| x.num_rows())
+                .collect::<Vec<_>>(),
+            vec![300, 300, 300, 100]
+        );
+        let result = DataFrame::parquet(path)
+            .project(vec![col("first_name")])
+            .execute()
+            .unwrap();
+        assert_eq!(result.iter().map(|x| x.len()).sum::<usize>(), 1000);
+        assert_eq!(
+            result[0][0]
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .expect("Failed to downcast array to utf8 array.")
+                .value(0),
+            "Amanda"
+        );
+    }
+    std::fs::remove_file(path).unwrap();
+
+    // A query failing after its first batch fails the write, the error names the file.
+    let chunk = |columns: usize| {
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([1, 2]))
+                as Arc<dyn Array>;
+            columns
+        ])
+    };
+    let result = DataFrame::memory(
+        Schema::from(vec![Field::new("id", DataType::Int32, false)]),
+        vec![chunk(1), chunk(2)],
+    )
+    .write_parquet(path);
+    let message = result.unwrap_err().to_string();
+    assert!(message.contains(path));
+    assert!(!std::path::Path::new(path).exists());
+    assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+
+    // A file can't be created in a directory that doesn't exist.
+    let missing = std::env::temp_dir()
+        .join("query_engine_test_missing_directory")
+        .join("data.parquet");
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id")])
+        .write_parquet(missing.to_str().unwrap());
+    assert!(matches!(result, Err(Error::IoError(_))));
+    assert!(!missing.exists());
+}
+
+#[test]
+fn test_execute_with_progress() {
+    let schema = Schema::from(vec![Field::new("id", DataType::Int32, false)]);
+    let chunks = (0..4)
+        .map(|i| {
+            Chunk::new(vec![Arc::new(PrimitiveArray::<i32>::from_vec(
+                (i * 10..i * 10 + 10).collect(),
+            )) as Arc<dyn Array>])
+        })
+        .collect::<Vec<_>>();
+    let progress = Rc::new(RefCell::new(Vec::new()));
+    let callback_progress = progress.clone();
+    let mut stream = DataFrame::memory(schema, chunks)
+        .filter(col("id").gteq(lit_int(5)))
+        .execute_with_progress(move |rows| callback_progress.borrow_mut().push(rows))
+        .unwrap();
+
+    // The callback is driven by consuming the stream.
+    assert!(stream.next().unwrap().is_ok());
+    assert_eq!(*progress.borrow(), vec![5]);
+    let total = 5 + stream.map(|x| x.unwrap().len()).sum::<usize>();
+    assert_eq!(*progress.borrow(), vec![5, 15, 25, 35]);
+    assert_eq!(progress.borrow().last(), Some(&total));
+}
+
+#[test]
+fn test_output_schema() {
+    let schema = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(
+            vec![col("country"), col("gender")],
+            vec![max(col("salary")), min(col("id"))],
+        )
+        .output_schema()
+        .unwrap();
+    assert_eq!(
+        schema
+            .fields
+            .iter()
+            .map(|x| (x.name.as_str(), x.data_type.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("country", DataType::Utf8),
+            ("gender", DataType::Utf8),
+            ("max", DataType::Float64),
+            ("min", DataType::Int32),
+        ]
+    );
+}
+
+#[test]
+fn test_literal_expression_types() {
+    let chunk = Chunk::new(vec![
+        Arc::new(PrimitiveArray::<i32>::from_slice([1, 2])) as Arc<dyn Array>
+    ]);
+    let schema = Schema::from(vec![Field::new("a", DataType::Int32, false)]);
+    let literals: Vec<(Box<dyn PhysicalExpression>, DataType)> = vec![
+        (
+            Box::new(LiteralBoolExpression::new(true)),
+            DataType::Boolean,
+        ),
+        (
+            Box::new(LiteralStringExpression::new("a".to_string())),
+            DataType::Utf8,
+        ),
+        (Box::new(LiteralIntegerExpression::new(1)), DataType::Int32),
+        (
+            Box::new(LiteralFloatExpression::new(1.0)),
+            DataType::Float64,
+        ),
+    ];
+    for (literal, data_type) in literals {
+        match literal.evaluate(&chunk, &schema).unwrap() {
+            ColumnarValue::Scalar(scalar) => assert_eq!(scalar.data_type(), &data_type),
+            ColumnarValue::Array(_) => panic!("Literal `{}` evaluated to an array.", literal),
+        }
+    }
+}
+
+#[test]
+fn test_columnar_value_helpers() {
+    let array = ColumnarValue::Array(Arc::new(PrimitiveArray::<f64>::from_slice([1.0, 2.0, 3.0])));
+    assert_eq!(array.data_type(), DataType::Float64);
+
+    let scalar = ColumnarValue::Scalar(Box::new(PrimitiveScalar::<i32>::new(
+        DataType::Int32,
+        Some(1),
+    )));
+    assert_eq!(scalar.data_type(), DataType::Int32);
+}
+
+#[test]
+fn test_reversed_scalar_arithmetic() {
+    use crate::logical_plan::logical_expression::{Div, Sub};
+
+    let sub = |left, right| LogicalExpression::Sub(Box::new(Sub::new(left, right)));
+    let div = |left, right| LogicalExpression::Div(Box::new(Div::new(left, right)));
+    let schema = Schema::from(vec![Field::new("salary", DataType::Float64, false)]);
+    let chunks = vec![Chunk::new(vec![
+        Arc::new(PrimitiveArray::<f64>::from_slice([10.0, 20.0, 50.0])) as Arc<dyn Array>,
+    ])];
+    let evaluate = |expr: LogicalExpression| {
+        DataFrame::memory(schema.clone(), chunks.clone())
+            .project(vec![expr])
+            .execute()
+            .unwrap()[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec()
+    };
+    assert_eq!(
+        evaluate(sub(lit_float(100.0), col("salary"))),
+        vec![90.0, 80.0, 50.0]
+    );
+    assert_eq!(
+        evaluate(sub(col("salary"), lit_float(100.0))),
+        vec![-90.0, -80.0, -50.0]
+    );
+    assert_eq!(
+        evaluate(div(lit_float(100.0), col("salary"))),
+        vec![10.0, 5.0, 2.0]
+    );
+}
+
+#[test]
+fn test_add_interval() {
+    use crate::logical_plan::logical_expression::Add;
+
+    let add = |left, right| LogicalExpression::Add(Box::new(Add::new(left, right)));
+    let schema = Schema::from(vec![Field::new("date", DataType::Date32, false)]);
+    let chunks = vec![Chunk::new(vec![Arc::new(
+        PrimitiveArray::<i32>::from_slice([0, 19000]).to(DataType::Date32),
+    ) as Arc<dyn Array>])];
+    let result = DataFrame::memory(schema, chunks)
+        .project(vec![add(col("date"), lit_interval_days(30))])
+        .execute()
+        .unwrap();
+    assert_eq!(result[0][0].data_type(), &DataType::Date32);
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![30, 19030]
+    );
+
+    let timestamps = |expr: LogicalExpression| {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![expr])
+            .execute()
+            .unwrap()[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .clone()
+    };
+    let original = timestamps(col("registration_dttm"));
+    let shifted = timestamps(add(col("registration_dttm"), lit_interval_days(30)));
+    assert_eq!(shifted.data_type(), original.data_type());
+    assert_eq!(
+        shifted.value(0) - original.value(0),
+        30 * 24 * 60 * 60 * 1_000_000_000
+    );
+}
+
+#[test]
+fn test_temporal_extraction() {
+    let years = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![year(col("registration_dttm"))])
+        .execute()
+        .unwrap();
+    assert_eq!(years[0].columns()[0].data_type(), &DataType::Int32);
+    assert!(years.iter().all(|chunk| {
+        chunk[0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .iter()
+            .all(|year| *year == 2016)
+    }));
+
+    let schema = Schema::from(vec![Field::new("date", DataType::Date32, false)]);
+    let chunks = vec![Chunk::new(vec![Arc::new(
+        PrimitiveArray::<i32>::from_slice([0, 365, 400, 19000]).to(DataType::Date32),
+    ) as Arc<dyn Array>])];
+    let result = DataFrame::memory(schema, chunks)
+        .project(vec![
+            year(col("date")),
+            month(col("date")),
+            day(col("date")),
+        ])
+        .execute()
+        .unwrap();
+    let values = |j: usize| {
+        result[0][j]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec()
+    };
+    assert_eq!(values(0), vec![1970, 1971, 1971, 2022]);
+    assert_eq!(values(1), vec![1, 1, 2, 1]);
+    assert_eq!(values(2), vec![1, 1, 5, 8]);
+}
+
+#[test]
+fn test_group_by_year() {
+    let years = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(vec![year(col("registration_dttm"))], vec![max(col("id"))])
+        .execute()
+        .unwrap()
+        .iter()
+        .flat_map(|chunk| {
+            chunk[0]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .expect("Failed to downcast array to primitive array.")
+                .values()
+                .to_vec()
+        })
+        .collect::<Vec<i32>>();
+    assert_eq!(years, vec![2016]);
+    let rows = years
+        .iter()
+        .map(|year_value| {
+            DataFrame::parquet("src/tests/userdata.parquet")
+                .filter(year(col("registration_dttm")).eq(lit_int(*year_value)))
+                .execute()
                 .unwrap()
-                .value(0)
+                .iter()
+                .map(|chunk| chunk.len())
+                .sum::<usize>()
+        })
+        .collect::<Vec<usize>>();
+    assert_eq!(rows, vec![1000]);
+
+    let schema = Schema::from(vec![
+        Field::new("date", DataType::Date32, false),
+        Field::new("value", DataType::Int32, false),
+    ]);
+    let chunks = vec![Chunk::new(vec![
+        Arc::new(PrimitiveArray::<i32>::from_slice([0, 365, 400, 19000]).to(DataType::Date32))
+            as Arc<dyn Array>,
+        Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3, 4])) as Arc<dyn Array>,
+    ])];
+    let result = DataFrame::memory(schema, chunks)
+        .aggregate(
+            vec![year(col("date")), month(col("date"))],
+            vec![max(col("value"))],
+        )
+        .execute()
+        .unwrap();
+    let mut groups = (0..result[0].len())
+        .map(|i| {
+            let value = |j: usize| {
+                result[0][j]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<i32>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .value(i)
+            };
+            (value(0), value(1), value(2))
+        })
+        .collect::<Vec<_>>();
+    groups.sort();
+    assert_eq!(
+        groups,
+        vec![(1970, 1, 1), (1971, 1, 2), (1971, 2, 3), (2022, 1, 4)]
+    );
+}
+
+#[test]
+fn test_row_hashes() {
+    let ids = PrimitiveArray::<i32>::from([Some(1), Some(2), Some(1), None]);
+    let names = Utf8Array::<i32>::from_slice(["a", "b", "a", "a"]);
+    let hashes = row_hashes(&Chunk::new(vec![
+        Arc::new(ids.clone()) as Arc<dyn Array>,
+        Arc::new(names.clone()) as Arc<dyn Array>,
+    ]))
+    .unwrap();
+
+    let id_hashes = compute::hash::hash(&ids).unwrap();
+    let name_hashes = compute::hash::hash(&names).unwrap();
+    assert_eq!(
+        hashes.iter().map(|x| x.copied()).collect::<Vec<_>>(),
+        vec![
+            Some(
+                id_hashes
+                    .value(0)
+                    .wrapping_mul(31)
+                    .wrapping_add(name_hashes.value(0))
+            ),
+            Some(
+                id_hashes
+                    .value(1)
+                    .wrapping_mul(31)
+                    .wrapping_add(name_hashes.value(1))
+            ),
+            Some(
+                id_hashes
+                    .value(0)
+                    .wrapping_mul(31)
+                    .wrapping_add(name_hashes.value(0))
+            ),
+            None,
+        ]
+    );
+    assert_eq!(hashes.value(0), hashes.value(2));
+    assert_ne!(hashes.value(0), hashes.value(1));
+
+    // Column order matters, so swapped values don't collide.
+    let swapped = |left: i32, right: i32| {
+        row_hashes(&Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([left])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from_slice([right])) as Arc<dyn Array>,
+        ]))
+        .unwrap()
+        .value(0)
+    };
+    assert_ne!(swapped(1, 2), swapped(2, 1));
+}
+
+#[test]
+fn test_filter_push_down() {
+    let query = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(lit_int(100).lt(col("id")).and(col("id").lteq(lit_int(200))))
+            .filter(col("id").eq(lit_int(150)).or(col("id").eq(lit_int(151))))
+            .project(vec![col("id"), col("first_name")])
+    };
+    let optimized = query().logical_plan().optimize();
+    let scan = match &optimized {
+        LogicalPlan::Projection(proj) => match &proj.children[0] {
+            LogicalPlan::Selection(sel) => {
+                // The disjunction can't be pushed down and stays in the selection.
+                assert_eq!(format!("{}", sel.expr), "#id == '150' || #id == '151'");
+                &sel.children[0]
+            }
+            plan => panic!("Expected a selection, got {}", plan),
+        },
+        plan => panic!("Expected a projection, got {}", plan),
+    };
+    match scan {
+        LogicalPlan::Scan(scan) => assert_eq!(
+            scan.filters,
+            vec![
+                PrunablePredicate {
+                    column: "id".to_string(),
+                    op: PruningOperator::Gt,
+                    literal: PruningLiteral::Integer(100),
+                },
+                PrunablePredicate {
+                    column: "id".to_string(),
+                    op: PruningOperator::LtEq,
+                    literal: PruningLiteral::Integer(200),
+                },
+            ]
         ),
-        "Int32[6, 7]"
+        plan => panic!("Expected a scan, got {}", plan),
+    }
+
+    let ids = |result: Vec<Chunk<Arc<dyn Array>>>| {
+        result
+            .iter()
+            .flat_map(|chunk| {
+                chunk[0]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<i32>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .iter()
+                    .map(|x| x.copied())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    let filter = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(col("id").eq(lit_int(500)))
+            .project(vec![col("id"), col("first_name")])
+    };
+    let pushed_down = ids(DataFrame::new(filter().logical_plan().optimize())
+        .execute()
+        .unwrap());
+    assert_eq!(pushed_down, ids(filter().execute().unwrap()));
+    assert_eq!(pushed_down, vec![Some(500)]);
+}
+
+#[test]
+fn test_aggregate_alias() {
+    let df = DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("salary").gt(lit_float(0.0)))
+        .aggregate(
+            vec![col("country").alias("land")],
+            vec![max(col("salary")).alias("max_salary"), min(col("salary"))],
+        );
+    let names = |schema: &Schema| {
+        schema
+            .fields
+            .iter()
+            .map(|x| x.name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(df.schema().unwrap()),
+        vec!["land", "max_salary", "min"]
+    );
+    assert_eq!(df.schema().unwrap().fields[1].data_type, DataType::Float64);
+    let result = df.execute().unwrap();
+    assert_eq!(result[0].arrays().len(), 3);
+    assert_eq!(result[0][1].data_type(), &DataType::Float64);
+}
+
+#[test]
+fn test_count_null_group() {
+    let schema = Schema::from(vec![
+        Field::new("group", DataType::Utf8, false),
+        Field::new("value", DataType::Int32, true),
+    ]);
+    let chunks = vec![Chunk::new(vec![
+        Arc::new(Utf8Array::<i32>::from_slice(["a", "b", "a", "b"])) as Arc<dyn Array>,
+        Arc::new(PrimitiveArray::<i32>::from([Some(1), None, Some(3), None])) as Arc<dyn Array>,
+    ])];
+    let result = DataFrame::memory(schema, chunks)
+        .aggregate(vec![col("group")], vec![count(col("value"))])
+        .execute()
+        .unwrap();
+    let counts = result[0][0]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .expect("Failed to downcast array to utf8 array.")
+        .iter()
+        .map(|x| x.unwrap().to_string())
+        .zip(
+            result[0][1]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .map(|x| x.copied()),
+        )
+        .collect::<HashMap<String, Option<i64>>>();
+    assert_eq!(counts["a"], Some(2));
+    assert_eq!(counts["b"], Some(0));
+}
+
+#[test]
+fn test_scan_ordinals() {
+    let path = std::env::temp_dir().join("query_engine_test_scan_ordinals.parquet");
+    let path = path.to_str().unwrap();
+    let schema = Schema::from(vec![
+        Field::new("value", DataType::Int32, false),
+        Field::new("value", DataType::Int32, false),
+    ]);
+    let chunk = Chunk::new(vec![
+        Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3])) as Arc<dyn Array>,
+        Arc::new(PrimitiveArray::<i32>::from_slice([4, 5, 6])) as Arc<dyn Array>,
+    ]);
+    write_parquet(
+        path,
+        &schema,
+        vec![Ok(chunk)].into_iter(),
+        &WriteParquetOptions::default(),
+    )
+    .unwrap();
+
+    assert!(matches!(
+        DataFrame::parquet_columns(path, vec![1, 2]),
+        Err(Error::ExceedingBoundsError(2))
+    ));
+    let df = DataFrame::parquet_columns(path, vec![1]).unwrap();
+    assert_eq!(df.schema().unwrap().fields.len(), 1);
+    let result = DataFrame::new(df.logical_plan().optimize())
+        .execute()
+        .unwrap();
+    assert_eq!(result[0].arrays().len(), 1);
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![4, 5, 6]
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_values() {
+    let schema = Schema::from(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, false),
+    ]);
+    let rows = vec![
+        vec![lit_int(1), lit_string("a")],
+        vec![lit_int(2), lit_string("b")],
+        vec![lit_int(3), lit_string("c")],
+    ];
+    let result = DataFrame::values(schema.clone(), rows)
+        .filter(col("id").gt(lit_int(1)))
+        .execute()
+        .unwrap();
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![2, 3]
+    );
+    assert_eq!(
+        result[0][1]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .expect("Failed to downcast array to utf8 array.")
+            .iter()
+            .map(|x| x.unwrap())
+            .collect::<Vec<_>>(),
+        vec!["b", "c"]
+    );
+
+    let result =
+        DataFrame::values(schema.clone(), vec![vec![lit_string("a"), lit_int(1)]]).execute();
+    assert!(matches!(result, Err(Error::InvalidValues(_))));
+    // Rows with more or fewer values than the schema has fields are rejected.
+    for row in [
+        vec![lit_int(1)],
+        vec![lit_int(1), lit_string("a"), lit_int(2)],
+    ] {
+        let expected = format!("a row has {} values but the schema has 2 fields", row.len());
+        let result =
+            DataFrame::values(schema.clone(), vec![vec![lit_int(1), lit_string("a")], row])
+                .execute();
+        assert!(matches!(result, Err(Error::InvalidValues(message)) if message == expected));
+    }
+}
+
+#[test]
+fn test_max_null_first_batch() {
+    let schema = Schema::from(vec![
+        Field::new("group", DataType::Utf8, false),
+        Field::new("value", DataType::Int32, true),
+    ]);
+    let chunks = vec![
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(["a", "a", "b"])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from([None, None, Some(7)])) as Arc<dyn Array>,
+        ]),
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(["a", "a", "b"])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from([Some(3), Some(5), None])) as Arc<dyn Array>,
+        ]),
+    ];
+    let result = DataFrame::memory(schema, chunks)
+        .aggregate(vec![col("group")], vec![max(col("value"))])
+        .execute()
+        .unwrap();
+    let maxima = result[0][0]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .expect("Failed to downcast array to utf8 array.")
+        .iter()
+        .map(|x| x.unwrap().to_string())
+        .zip(
+            result[0][1]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .map(|x| x.copied()),
+        )
+        .collect::<HashMap<String, Option<i32>>>();
+    assert_eq!(maxima["a"], Some(5));
+    assert_eq!(maxima["b"], Some(7));
+}
+
+#[test]
+fn test_max_min_avg() {
+    let mut expected: HashMap<String, (f64, f64, f64, usize)> = HashMap::new();
+    DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("country"), col("salary")])
+        .execute()
+        .unwrap()
+        .iter()
+        .for_each(|chunk| {
+            let countries = chunk[0]
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .expect("Failed to downcast array to utf8 array.");
+            let salaries = chunk[1]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.");
+            countries
+                .iter()
+                .zip(salaries.iter())
+                .for_each(|(country, salary)| {
+                    let entry = expected.entry(country.unwrap().to_string()).or_insert((
+                        f64::MIN,
+                        f64::MAX,
+                        0.0,
+                        0,
+                    ));
+                    if let Some(salary) = salary {
+                        entry.0 = entry.0.max(*salary);
+                        entry.1 = entry.1.min(*salary);
+                        entry.2 += salary;
+                        entry.3 += 1;
+                    }
+                })
+        });
+
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(
+            vec![col("country")],
+            vec![max(col("salary")), min(col("salary")), avg(col("salary"))],
+        )
+        .execute()
+        .unwrap();
+    let columns = (1..4)
+        .map(|i| {
+            result[0][i]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.")
+        })
+        .collect::<Vec<_>>();
+    let countries = result[0][0]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .expect("Failed to downcast array to utf8 array.");
+    assert_eq!(countries.len(), expected.len());
+    countries.iter().enumerate().for_each(|(i, country)| {
+        let (max, min, sum, count) = expected[country.unwrap()];
+        if count == 0 {
+            assert!(columns.iter().all(|x| x.is_null(i)));
+        } else {
+            assert_eq!(columns[0].value(i), max);
+            assert_eq!(columns[1].value(i), min);
+            assert!((columns[2].value(i) - sum / count as f64).abs() < 1e-6);
+        }
+    });
+}
+
+#[test]
+fn test_filter_all() {
+    let chained = DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("salary").gt(lit_float(200000.0)))
+        .filter(col("gender").eq(lit_string("Female")))
+        .project(vec![col("id")]);
+    let chained = DataFrame::new(chained.logical_plan().optimize())
+        .execute()
+        .unwrap();
+    let combined = DataFrame::parquet("src/tests/userdata.parquet")
+        .filter_all(vec![
+            col("salary").gt(lit_float(200000.0)),
+            col("gender").eq(lit_string("Female")),
+        ])
+        .project(vec![col("id")]);
+    let combined = DataFrame::new(combined.logical_plan().optimize())
+        .execute()
+        .unwrap();
+    let ids = |chunks: Vec<Chunk<Arc<dyn Array>>>| {
+        chunks
+            .iter()
+            .flat_map(|chunk| {
+                chunk[0]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<i32>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .values()
+                    .to_vec()
+            })
+            .collect::<Vec<_>>()
+    };
+    let chained = ids(chained);
+    assert!(!chained.is_empty());
+    assert_eq!(chained, ids(combined));
+}
+
+#[test]
+fn test_dictionary_group_keys() {
+    let aggregate = |config: &ExecutionConfig| {
+        let result = DataFrame::parquet("src/tests/userdata.parquet")
+            .aggregate(
+                vec![col("country")],
+                vec![count(col("salary")), max(col("salary"))],
+            )
+            .execute_with_config(config)
+            .unwrap();
+        let mut groups = result[0][0]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .expect("Failed to downcast array to utf8 array.")
+            .iter()
+            .zip(
+                result[0][1]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<i64>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .iter(),
+            )
+            .zip(
+                result[0][2]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<f64>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .iter(),
+            )
+            .map(|((country, count), max)| {
+                (
+                    country.unwrap().to_string(),
+                    count.copied(),
+                    max.copied().map(f64::to_bits),
+                )
+            })
+            .collect::<Vec<_>>();
+        groups.sort();
+        groups
+    };
+    let naive = aggregate(&ExecutionConfig::default());
+    let encoded = aggregate(&ExecutionConfig {
+        dictionary_group_keys: true,
+        ..Default::default()
+    });
+    assert!(naive.len() > 100);
+    assert_eq!(naive, encoded);
+}
+
+#[test]
+fn test_project_literal() {
+    let df =
+        DataFrame::parquet("src/tests/userdata.parquet").project(vec![col("id"), lit_string("id")]);
+    assert_eq!(
+        df.schema()
+            .unwrap()
+            .fields
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["id", "'id'"]
+    );
+    let result = df
+        .execute_with_config(&ExecutionConfig {
+            batch_size: Some(300),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(result.len() > 1);
+    result.iter().for_each(|chunk| {
+        let literals = chunk[1]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .expect("Failed to downcast array to utf8 array.");
+        assert_eq!(literals.len(), chunk[0].len());
+        assert!(literals.iter().all(|x| x == Some("id")));
+    });
+}
+
+#[test]
+fn test_aggregate_order() {
+    let schema = Schema::from(vec![
+        Field::new("group", DataType::Utf8, false),
+        Field::new("value", DataType::Int32, false),
+    ]);
+    let chunks = vec![
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(["b", "a", "b"])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3])) as Arc<dyn Array>,
+        ]),
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(["c", "a"])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from_slice([4, 5])) as Arc<dyn Array>,
+        ]),
+    ];
+    let result = DataFrame::memory(schema, chunks)
+        .aggregate(vec![col("group")], vec![max(col("value"))])
+        .execute()
+        .unwrap();
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .expect("Failed to downcast array to utf8 array.")
+            .iter()
+            .map(|x| x.unwrap())
+            .collect::<Vec<_>>(),
+        vec!["b", "a", "c"]
+    );
+    assert_eq!(
+        result[0][1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![3, 5, 4]
+    );
+}
+
+#[test]
+fn test_compare_float_to_int() {
+    let schema = Schema::from(vec![
+        Field::new("float", DataType::Float64, false),
+        Field::new("int", DataType::Int32, false),
+    ]);
+    let chunks = vec![Chunk::new(vec![
+        Arc::new(PrimitiveArray::<f64>::from_slice([0.5, 2.5, 3.0, 4.5])) as Arc<dyn Array>,
+        Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3, 4])) as Arc<dyn Array>,
+    ])];
+    let result = DataFrame::memory(schema.clone(), chunks.clone())
+        .filter(col("float").gt(col("int")))
+        .execute()
+        .unwrap();
+    assert_eq!(
+        result[0][1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![2, 4]
+    );
+
+    let result = DataFrame::memory(schema, chunks)
+        .filter(col("int").lteq(lit_float(2.5)))
+        .execute()
+        .unwrap();
+    assert_eq!(
+        result[0][1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn test_large_utf8() {
+    let schema = Schema::from(vec![
+        Field::new("name", DataType::LargeUtf8, false),
+        Field::new("value", DataType::Int32, false),
+    ]);
+    let chunks = vec![Chunk::new(vec![
+        Arc::new(Utf8Array::<i64>::from_slice(["a", "b", "a", "b"])) as Arc<dyn Array>,
+        Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3, 4])) as Arc<dyn Array>,
+    ])];
+    let result = DataFrame::memory(schema.clone(), chunks.clone())
+        .filter(col("name").eq(lit_string("b")))
+        .execute()
+        .unwrap();
+    assert_eq!(
+        result[0][1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![2, 4]
+    );
+
+    let result = DataFrame::memory(schema, chunks)
+        .aggregate(vec![col("name")], vec![max(col("value"))])
+        .execute()
+        .unwrap();
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<Utf8Array<i64>>()
+            .expect("Failed to downcast array to utf8 array.")
+            .iter()
+            .map(|x| x.unwrap())
+            .collect::<Vec<_>>(),
+        vec!["a", "b"]
     );
 }
+
+#[test]
+fn test_cast_coercion_mode() {
+    let df = || {
+        let schema = Schema::from(vec![Field::new("value", DataType::Utf8, true)]);
+        let chunks = vec![Chunk::new(vec![Arc::new(Utf8Array::<i32>::from([
+            Some("1"),
+            Some("x"),
+            Some(" 3"),
+            None,
+        ])) as Arc<dyn Array>])];
+        DataFrame::memory(schema, chunks).project(vec![col("value").cast(DataType::Int32)])
+    };
+
+    let result = df().execute_with_config(&ExecutionConfig::default());
+    assert!(matches!(result, Err(Error::CastError(value, _)) if value == "x"));
+
+    let result = df()
+        .execute_with_config(&ExecutionConfig {
+            coercion_mode: CoercionMode::Lenient,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.copied())
+            .collect::<Vec<_>>(),
+        vec![Some(1), None, Some(3), None]
+    );
+}
+
+#[test]
+fn test_cache() {
+    let path = "src/tests/userdata.parquet";
+    let ds = DataSource::Parquet(ParquetDataSource::new(path).unwrap());
+    let counter = ds.try_clone().unwrap();
+    let cached = DataFrame::new(LogicalPlan::Scan(Scan::new(path, ds, None)))
+        .project(vec![col("id"), col("salary")])
+        .cache()
+        .unwrap();
+    assert_eq!(counter.scans(), 1);
+
+    let high = cached
+        .try_clone()
+        .unwrap()
+        .filter(col("salary").gt(lit_float(200000.0)))
+        .execute()
+        .unwrap();
+    let low = cached
+        .filter(col("salary").lteq(lit_float(200000.0)))
+        .execute()
+        .unwrap();
+    let rows = |chunks: &Vec<Chunk<Arc<dyn Array>>>| chunks.iter().map(|x| x.len()).sum::<usize>();
+    assert!(rows(&high) > 0);
+    assert!(rows(&low) > 0);
+    assert_eq!(counter.scans(), 1);
+}
+
+#[test]
+fn test_wildcard() {
+    let input = DataFrame::parquet("src/tests/userdata.parquet");
+    let names = input
+        .schema()
+        .unwrap()
+        .fields
+        .iter()
+        .map(|x| x.name.clone())
+        .collect::<Vec<_>>();
+
+    let df = input.project(vec![wildcard()]);
+    assert_eq!(
+        df.schema()
+            .unwrap()
+            .fields
+            .iter()
+            .map(|x| x.name.clone())
+            .collect::<Vec<_>>(),
+        names
+    );
+    let result = df.execute().unwrap();
+    assert_eq!(result[0].arrays().len(), names.len());
+
+    let df = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![all_except(vec!["comments"])]);
+    assert_eq!(
+        df.schema()
+            .unwrap()
+            .fields
+            .iter()
+            .map(|x| x.name.clone())
+            .collect::<Vec<_>>(),
+        names
+            .iter()
+            .filter(|x| *x != "comments")
+            .cloned()
+            .collect::<Vec<_>>()
+    );
+    let result = DataFrame::new(df.logical_plan().optimize())
+        .execute()
+        .unwrap();
+    assert_eq!(result[0].arrays().len(), names.len() - 1);
+}
+
+#[test]
+fn test_eq_null() {
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("id").eq(lit_null(DataType::Int32)))
+        .execute()
+        .unwrap();
+    assert_eq!(result.iter().map(|x| x.len()).sum::<usize>(), 0);
+
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("salary").neq(lit_null(DataType::Int32)))
+        .execute()
+        .unwrap();
+    assert_eq!(result.iter().map(|x| x.len()).sum::<usize>(), 0);
+}
+
+#[test]
+fn test_rename_columns() {
+    let df = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id"), col("salary")])
+        .rename_columns(vec!["key", "income"])
+        .unwrap();
+    assert_eq!(
+        df.schema()
+            .unwrap()
+            .fields
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["key", "income"]
+    );
+    let result = df
+        .filter(col("income").gt(lit_float(200000.0)))
+        .execute()
+        .unwrap();
+    assert!(result.iter().map(|x| x.len()).sum::<usize>() > 0);
+
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id"), col("salary")])
+        .rename_columns(vec!["key"]);
+    assert!(matches!(result, Err(Error::ColumnCountMismatch(2, 1))));
+}
+
+#[test]
+fn test_group_by_nullable_bool() {
+    let schema = Schema::from(vec![
+        Field::new("flag", DataType::Boolean, true),
+        Field::new("value", DataType::Int32, false),
+    ]);
+    let chunks = vec![Chunk::new(vec![
+        Arc::new(BooleanArray::from([
+            Some(true),
+            None,
+            Some(false),
+            Some(true),
+            None,
+        ])) as Arc<dyn Array>,
+        Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3, 4, 5])) as Arc<dyn Array>,
+    ])];
+    let result = DataFrame::memory(schema, chunks)
+        .aggregate(
+            vec![col("flag")],
+            vec![max(col("value")), count(col("value"))],
+        )
+        .execute()
+        .unwrap();
+    assert_eq!(result[0].len(), 3);
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .expect("Failed to downcast array to boolean array.")
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![Some(true), None, Some(false)]
+    );
+    assert_eq!(
+        result[0][1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![4, 5, 3]
+    );
+    assert_eq!(
+        result[0][2]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![2, 2, 1]
+    );
+}
+
+#[test]
+fn test_serialize_plan() {
+    let plan = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(col("salary").gt(lit_float(200000.0)))
+            .project(vec![col("id"), col("first_name").alias("name")])
+            .logical_plan()
+    };
+    let json = serde_json::to_string(&plan()).unwrap();
+    let deserialized: LogicalPlan = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        format_logical_plan(&deserialized, 0),
+        format_logical_plan(&plan(), 0)
+    );
+    assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+
+    let ids = |plan: LogicalPlan| {
+        DataFrame::new(plan)
+            .execute()
+            .unwrap()
+            .iter()
+            .flat_map(|chunk| {
+                chunk[0]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<i32>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .values()
+                    .to_vec()
+            })
+            .collect::<Vec<_>>()
+    };
+    let expected = ids(plan());
+    assert!(!expected.is_empty());
+    assert_eq!(ids(deserialized), expected);
+
+    let schema = Schema::from(vec![Field::new("id", DataType::Int32, false)]);
+    assert!(serde_json::to_string(&DataFrame::memory(schema, vec![]).logical_plan()).is_err());
+}
+
+#[test]
+fn test_estimated_rows() {
+    let path = std::env::temp_dir().join("query_engine_test_estimated_rows.parquet");
+    let path = path.to_str().unwrap();
+    let schema = Schema::from(vec![Field::new("id", DataType::Int32, false)]);
+    let chunk = Chunk::new(vec![
+        Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3])) as Arc<dyn Array>
+    ]);
+    write_parquet(
+        path,
+        &schema,
+        vec![Ok(chunk)].into_iter(),
+        &WriteParquetOptions::default(),
+    )
+    .unwrap();
+
+    let large = DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("salary").gt(lit_float(200000.0)))
+        .project(vec![col("id")])
+        .logical_plan();
+    let small = DataFrame::parquet(path).sample(0.5, 42).logical_plan();
+    assert_eq!(large.estimated_rows(), Some(1000));
+    assert_eq!(small.estimated_rows(), Some(2));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_fluent_arithmetic() {
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![
+            col("id"),
+            col("id").add(lit_int(1)),
+            col("id").modu(lit_int(3)),
+            lit_int(10).modu(col("id")).alias("rmod"),
+        ])
+        .execute()
+        .unwrap();
+    let column = |i: usize| {
+        result[0][i]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec()
+    };
+    let ids = column(0);
+    assert_eq!(column(1), ids.iter().map(|x| x + 1).collect::<Vec<_>>());
+    assert_eq!(column(2), ids.iter().map(|x| x % 3).collect::<Vec<_>>());
+    assert_eq!(column(3), ids.iter().map(|x| 10 % x).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_modulo_by_zero() {
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![
+            col("id").modu(lit_int(0)).alias("scalar"),
+            col("id").modu(col("id").sub(col("id"))).alias("array"),
+            lit_int(7).modu(lit_int(0)).alias("literal"),
+            col("salary").modu(lit_float(0.0)).alias("float"),
+        ])
+        .execute()
+        .unwrap();
+    // An integer remainder by zero is null, a float one is NaN.
+    for column in &result[0].arrays()[..3] {
+        assert_eq!(column.null_count(), column.len());
+    }
+    let floats = result[0][3]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<f64>>()
+        .expect("Failed to downcast array to primitive array.");
+    assert!(floats.iter().flatten().all(|x| x.is_nan()));
+}
+
+#[test]
+fn test_modulo_mismatched_types() {
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id"), col("id").modu(lit_float(2.5))])
+        .execute()
+        .unwrap();
+    let ids = result[0][0]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i32>>()
+        .expect("Failed to downcast array to primitive array.");
+    let remainders = result[0][1]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<f64>>()
+        .expect("Failed to downcast array to primitive array.");
+    assert_eq!(
+        remainders.values().to_vec(),
+        ids.values()
+            .iter()
+            .map(|x| *x as f64 % 2.5)
+            .collect::<Vec<_>>()
+    );
+
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id").modu(lit_string("a"))])
+        .execute();
+    assert!(matches!(result, Err(Error::PhysicalTypeNotSuported(_))));
+}
+
+#[test]
+fn test_arithmetic_operators() {
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![
+            col("id"),
+            col("id") + lit_int(1),
+            (col("id") - lit_int(1)) * lit_int(2) / lit_int(4),
+            col("salary"),
+            col("salary") * lit_float(1.1),
+        ])
+        .execute()
+        .unwrap();
+    let ints = |i: usize| {
+        result[0][i]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec()
+    };
+    let ids = ints(0);
+    assert_eq!(ints(1), ids.iter().map(|x| x + 1).collect::<Vec<_>>());
+    assert_eq!(
+        ints(2),
+        ids.iter().map(|x| (x - 1) * 2 / 4).collect::<Vec<_>>()
+    );
+    let floats = |i: usize| {
+        result[0][i]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.copied())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        floats(4),
+        floats(3)
+            .iter()
+            .map(|x| x.map(|x| x * 1.1))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_aggregate_helpers() {
+    assert!(matches!(sum(col("salary")), LogicalExpression::Sum(_)));
+    assert!(matches!(avg(col("salary")), LogicalExpression::Avg(_)));
+    assert!(matches!(count(col("salary")), LogicalExpression::Count(_)));
+    assert!(matches!(max(col("salary")), LogicalExpression::Max(_)));
+    assert!(matches!(min(col("salary")), LogicalExpression::Min(_)));
+    assert_eq!(sum(col("salary")).to_string(), "sum (#salary)");
+}
+
+#[test]
+fn test_scan_schema_mismatch() {
+    let schema = Schema::from(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("value", DataType::Int32, false),
+    ]);
+    let chunks = vec![
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([1, 2])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from_slice([10, 20])) as Arc<dyn Array>,
+        ]),
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([3])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from_slice([30])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from_slice([300])) as Arc<dyn Array>,
+        ]),
+    ];
+    let df = || DataFrame::memory(schema.clone(), chunks.clone());
+    assert!(matches!(df().execute(), Err(Error::SchemaMismatch(_))));
+
+    // A projection pushed into the scan doesn't hide the extra column.
+    let projected = DataFrame::new(df().project(vec![col("id")]).optimized_logical_plan());
+    assert!(matches!(projected.execute(), Err(Error::SchemaMismatch(_))));
+}
+
+#[test]
+fn test_scan_changed_parquet_file() {
+    let dir = std::env::temp_dir().join("query_engine_test_scan_changed_parquet_file");
+    std::fs::create_dir_all(&dir).unwrap();
+    let write = |name: &str, columns: Vec<LogicalExpression>| {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(columns)
+            .write_parquet(dir.join(name).to_str().unwrap())
+            .unwrap();
+    };
+    write("a.parquet", vec![col("id"), col("country")]);
+    write("b.parquet", vec![col("id"), col("country")]);
+    let df = DataFrame::parquet_glob(dir.join("*.parquet").to_str().unwrap());
+
+    // Cloning the plan reopens the files, which now have differing schemas.
+    write("b.parquet", vec![col("salary"), col("id"), col("country")]);
+    let result = df.try_clone().unwrap().execute();
+    let path = dir.join("b.parquet");
+    assert!(matches!(
+        result,
+        Err(Error::SchemaMismatch(message)) if message.contains(path.to_str().unwrap())
+    ));
+    let result = df.try_clone().unwrap().project(vec![col("id")]).execute();
+    assert!(matches!(result, Err(Error::SchemaMismatch(_))));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_sum_int32_overflow() {
+    let schema = Schema::from(vec![
+        Field::new("group", DataType::Utf8, false),
+        Field::new("value", DataType::Int32, false),
+    ]);
+    let chunks = vec![Chunk::new(vec![
+        Arc::new(Utf8Array::<i32>::from_slice(["a", "a", "b"])) as Arc<dyn Array>,
+        Arc::new(PrimitiveArray::<i32>::from_slice([i32::MAX, i32::MAX, 1])) as Arc<dyn Array>,
+    ])];
+    let df =
+        DataFrame::memory(schema, chunks).aggregate(vec![col("group")], vec![sum(col("value"))]);
+    assert_eq!(df.schema().unwrap().fields[1].data_type, DataType::Int64);
+    let result = df.execute().unwrap();
+    assert_eq!(
+        result[0][1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![2 * i32::MAX as i64, 1]
+    );
+}
+
+#[test]
+fn test_sum_int64_overflow() {
+    let schema = Schema::from(vec![Field::new("value", DataType::Int64, false)]);
+    let chunks = vec![Chunk::new(vec![
+        Arc::new(PrimitiveArray::<i64>::from_slice([i64::MAX, 1])) as Arc<dyn Array>,
+    ])];
+    let result = DataFrame::memory(schema, chunks)
+        .aggregate(vec![], vec![sum(col("value"))])
+        .execute();
+    assert!(matches!(result, Err(Error::IntegerOverflow(name)) if name == "sum"));
+}
+
+#[test]
+fn test_sum_float32() {
+    let schema = Schema::from(vec![Field::new("value", DataType::Float32, true)]);
+    let chunks =
+        vec![Chunk::new(vec![
+            Arc::new(PrimitiveArray::<f32>::from([Some(1.5), None, Some(2.25)])) as Arc<dyn Array>,
+        ])];
+    let df = DataFrame::memory(schema, chunks).aggregate(vec![], vec![sum(col("value"))]);
+    // Float32 values are summed into a Float64.
+    assert_eq!(df.schema().unwrap().fields[0].data_type, DataType::Float64);
+    let result = df.execute().unwrap();
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![3.75]
+    );
+}
+
+#[test]
+fn test_group_by_alias() {
+    let query = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![
+                col("id").modu(lit_int(10)).alias("bucket"),
+                col("salary"),
+            ])
+            .aggregate(vec![col("bucket")], vec![count(col("salary"))])
+    };
+    let optimized = DataFrame::new(query().logical_plan().optimize());
+    for df in [query(), optimized] {
+        assert_eq!(df.schema().unwrap().fields[0].name, "bucket");
+        let result = df.execute().unwrap();
+        let mut buckets = result[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec();
+        buckets.sort_unstable();
+        assert_eq!(buckets, (0..10).collect::<Vec<i32>>());
+    }
+}
+
+#[test]
+fn test_join_composite_key() {
+    let left = || {
+        DataFrame::memory(
+            Schema::from(vec![
+                Field::new("a", DataType::Int32, false),
+                Field::new("b", DataType::Utf8, false),
+                Field::new("l", DataType::Int32, false),
+            ]),
+            vec![Chunk::new(vec![
+                Arc::new(PrimitiveArray::<i32>::from_slice([1, 1, 2, 2])) as Arc<dyn Array>,
+                Arc::new(Utf8Array::<i32>::from_slice(["x", "y", "x", "y"])) as Arc<dyn Array>,
+                Arc::new(PrimitiveArray::<i32>::from_slice([10, 20, 30, 40])) as Arc<dyn Array>,
+            ])],
+        )
+    };
+    let right = || {
+        DataFrame::memory(
+            Schema::from(vec![
+                Field::new("x", DataType::Int32, false),
+                Field::new("y", DataType::Utf8, false),
+                Field::new("r", DataType::Int32, false),
+            ]),
+            vec![Chunk::new(vec![
+                Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 1])) as Arc<dyn Array>,
+                Arc::new(Utf8Array::<i32>::from_slice(["y", "x", "z"])) as Arc<dyn Array>,
+                Arc::new(PrimitiveArray::<i32>::from_slice([100, 200, 300])) as Arc<dyn Array>,
+            ])],
+        )
+    };
+    let build_left = |df: DataFrame| match df
+        .logical_plan()
+        .create_physical_plan(&ExecutionConfig::default())
+        .unwrap()
+    {
+        PhysicalPlan::HashJoin(join) => join.build_left(),
+        _ => panic!("Expected a hash join."),
+    };
+    let pairs = |df: DataFrame, first: usize, second: usize| {
+        let mut pairs = df
+            .execute()
+            .unwrap()
+            .iter()
+            .flat_map(|chunk| {
+                let values = |i: usize| {
+                    chunk[i]
+                        .as_any()
+                        .downcast_ref::<PrimitiveArray<i32>>()
+                        .expect("Failed to downcast array to primitive array.")
+                        .values()
+                        .to_vec()
+                };
+                values(first).into_iter().zip(values(second))
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs
+    };
+
+    let df = left().join(right(), vec![("a", "x"), ("b", "y")]);
+    assert_eq!(df.schema().unwrap().fields.len(), 6);
+    assert!(!build_left(
+        left().join(right(), vec![("a", "x"), ("b", "y")])
+    ));
+    assert_eq!(pairs(df, 2, 5), vec![(20, 100), (30, 200)]);
+    let optimized = left()
+        .join(right(), vec![("a", "x"), ("b", "y")])
+        .logical_plan()
+        .optimize();
+    assert_eq!(
+        pairs(DataFrame::new(optimized), 2, 5),
+        vec![(20, 100), (30, 200)]
+    );
+
+    // The smaller right input is now on the left, so the hash table is built from it.
+    let df = right().join(left(), vec![("x", "a"), ("y", "b")]);
+    assert!(build_left(
+        right().join(left(), vec![("x", "a"), ("y", "b")])
+    ));
+    assert_eq!(pairs(df, 5, 2), vec![(20, 100), (30, 200)]);
+}
+
+#[test]
+fn test_csv_without_header() {
+    let options = CsvReadOptions {
+        has_header: false,
+        delimiter: b';',
+        ..Default::default()
+    };
+    let df = DataFrame::csv_with("src/tests/headerless.csv", options);
+    assert_eq!(
+        df.schema()
+            .unwrap()
+            .fields
+            .iter()
+            .map(|x| (x.name.as_str(), x.data_type.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("col0", DataType::Int64),
+            ("col1", DataType::Utf8),
+            ("col2", DataType::Float64),
+            ("col3", DataType::Boolean),
+        ]
+    );
+    let result = df.project(vec![col("col0")]).execute().unwrap();
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn test_csv_infer_schema_rows() {
+    let path = std::env::temp_dir().join("query_engine_test_csv_infer_schema_rows.csv");
+    let mut content = "id,value\n".to_string();
+    (0..10).for_each(|i| content.push_str(&format!("{},{}\n", i, i)));
+    content.push_str("10,10.5\n");
+    std::fs::write(&path, content).unwrap();
+    let path = path.to_str().unwrap();
+    let options = |infer_schema_rows, coercion_mode| CsvReadOptions {
+        infer_schema_rows,
+        coercion_mode,
+        ..Default::default()
+    };
+
+    let df = DataFrame::csv_with(path, options(10, CoercionMode::Strict));
+    assert_eq!(df.schema().unwrap().fields[1].data_type, DataType::Int64);
+    assert!(matches!(df.execute(), Err(Error::CastError(_, _))));
+
+    let result = DataFrame::csv_with(path, options(10, CoercionMode::Lenient))
+        .execute()
+        .unwrap();
+    let values = result[0][1]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i64>>()
+        .expect("Failed to downcast array to primitive array.");
+    assert_eq!(values.null_count(), 1);
+    assert!(values.is_null(10));
+
+    let df = DataFrame::csv_with(path, options(100, CoercionMode::Strict));
+    assert_eq!(df.schema().unwrap().fields[1].data_type, DataType::Float64);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_distinct_on() {
+    let mut first_ids: HashMap<String, i32> = HashMap::new();
+    DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id"), col("country")])
+        .execute()
+        .unwrap()
+        .iter()
+        .for_each(|chunk| {
+            let ids = chunk[0]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .expect("Failed to downcast array to primitive array.");
+            let countries = chunk[1]
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .expect("Failed to downcast array to utf8 array.");
+            ids.values()
+                .iter()
+                .zip(countries.iter())
+                .for_each(|(id, country)| {
+                    first_ids.entry(country.unwrap().to_string()).or_insert(*id);
+                })
+        });
+
+    let df = DataFrame::parquet("src/tests/userdata.parquet").distinct_on(vec!["country"]);
+    let columns = df.schema().unwrap().fields.len();
+    let result = df.execute().unwrap();
+    assert_eq!(result[0].arrays().len(), columns);
+    let mut distinct: HashMap<String, i32> = HashMap::new();
+    result.iter().for_each(|chunk| {
+        let ids = chunk[1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.");
+        let countries = chunk[8]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .expect("Failed to downcast array to utf8 array.");
+        ids.values()
+            .iter()
+            .zip(countries.iter())
+            .for_each(|(id, country)| {
+                assert!(distinct.insert(country.unwrap().to_string(), *id).is_none());
+            })
+    });
+    assert_eq!(distinct.len(), first_ids.len());
+    assert_eq!(distinct, first_ids);
+}
+
+#[test]
+fn test_optimized_logical_plan() {
+    let plan = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id"), col("first_name")])
+        .filter(col("id").gt(lit_int(100)))
+        .optimized_logical_plan();
+    let scan = match &plan {
+        LogicalPlan::Selection(sel) => match &sel.children[0] {
+            LogicalPlan::Projection(proj) => &proj.children[0],
+            _ => panic!("Expected projection below selection."),
+        },
+        _ => panic!("Expected selection at the top of the plan."),
+    };
+    match scan {
+        LogicalPlan::Scan(scan) => {
+            let mut projection = scan.projection.clone().unwrap();
+            projection.sort();
+            assert_eq!(projection, vec!["first_name", "id"]);
+        }
+        _ => panic!("Expected a scan at the bottom of the plan."),
+    }
+}
+
+#[test]
+fn test_exec_input_count() {
+    let schema = Schema::default;
+    let input_count = |result: Result<(), Error>| match result {
+        Err(Error::InputCountMismatch(_, expected, got)) => (expected, got),
+        _ => panic!("Expected an input count mismatch."),
+    };
+    assert_eq!(
+        input_count(ProjectionExec::new(vec![], vec![], schema()).map(|_| ())),
+        (1, 0)
+    );
+    assert_eq!(
+        input_count(
+            SelectionExec::new(vec![], Box::new(LiteralBoolExpression::new(true)), schema())
+                .map(|_| ())
+        ),
+        (1, 0)
+    );
+    assert_eq!(
+        input_count(AggregateExec::new(vec![], vec![], vec![], schema()).map(|_| ())),
+        (1, 0)
+    );
+    assert_eq!(
+        input_count(SampleExec::new(vec![], 0.5, 0, schema()).map(|_| ())),
+        (1, 0)
+    );
+    assert_eq!(
+        input_count(DistinctExec::new(vec![], vec![], schema()).map(|_| ())),
+        (1, 0)
+    );
+    assert_eq!(
+        input_count(HashJoinExec::new(vec![], vec![], schema()).map(|_| ())),
+        (2, 0)
+    );
+}
+
+#[test]
+fn test_compare_bool_to_int() {
+    let df = || {
+        DataFrame::memory(
+            Schema::from(vec![Field::new("bool_col", DataType::Boolean, false)]),
+            vec![Chunk::new(vec![
+                Arc::new(BooleanArray::from_slice([true, false, true])) as Arc<dyn Array>,
+            ])],
+        )
+    };
+    assert!(matches!(
+        df().filter(col("bool_col").eq(lit_int(1))).execute(),
+        Err(Error::TypeMismatch(_, _))
+    ));
+    // The same comparison in a projection is reported when planning it.
+    assert!(matches!(
+        df().project(vec![col("bool_col").eq(lit_int(1))]).execute(),
+        Err(Error::TypeMismatch(_, _))
+    ));
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id").gt(lit_int(1)).eq(lit_int(1))])
+        .execute();
+    assert!(matches!(result, Err(Error::TypeMismatch(_, _))));
+    let result = df()
+        .filter(col("bool_col").eq(lit_bool(true)))
+        .execute()
+        .unwrap();
+    assert_eq!(result[0].len(), 2);
+}
+
+#[test]
+fn test_cumsum() {
+    let total: f64 = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("salary")])
+        .execute()
+        .unwrap()
+        .iter()
+        .map(|chunk| {
+            chunk[0]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .flatten()
+                .sum::<f64>()
+        })
+        .sum();
+
+    let config = ExecutionConfig {
+        batch_size: Some(100),
+        ..Default::default()
+    };
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id"), col("salary")])
+        .window(vec![cumsum(col("salary"))])
+        .execute_with_config(&config)
+        .unwrap();
+    assert!(result.len() > 1);
+    let running = result
+        .iter()
+        .flat_map(|chunk| {
+            chunk[2]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .values()
+                .to_vec()
+        })
+        .collect::<Vec<f64>>();
+    assert!(running.windows(2).all(|x| x[0] <= x[1]));
+    assert!((running.last().unwrap() - total).abs() < 1e-6 * total);
+}
+
+#[test]
+fn test_cumsum_overflow() {
+    let schema = Schema::from(vec![Field::new("value", DataType::Int64, false)]);
+    // The total overflows in the second batch.
+    let chunks = vec![
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i64>::from_slice([i64::MAX - 1])) as Arc<dyn Array>,
+        ]),
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i64>::from_slice([1, 1])) as Arc<dyn Array>
+        ]),
+    ];
+    let result = DataFrame::memory(schema, chunks)
+        .window(vec![cumsum(col("value"))])
+        .execute();
+    assert!(matches!(result, Err(Error::IntegerOverflow(name)) if name == "cumsum"));
+}
+
+#[test]
+fn test_cumsum_float32() {
+    let schema = Schema::from(vec![Field::new("value", DataType::Float32, true)]);
+    let chunks =
+        vec![Chunk::new(vec![
+            Arc::new(PrimitiveArray::<f32>::from([Some(1.5), None, Some(2.25)])) as Arc<dyn Array>,
+        ])];
+    let result = DataFrame::memory(schema, chunks)
+        .window(vec![cumsum(col("value"))])
+        .execute()
+        .unwrap();
+    assert_eq!(
+        result[0][1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .to_vec(),
+        vec![1.5, 1.5, 3.75]
+    );
+}
+
+#[test]
+fn test_rank() {
+    let schema = Schema::from(vec![Field::new("score", DataType::Int32, false)]);
+    let chunks = vec![
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([1, 1, 2, 3])) as Arc<dyn Array>,
+        ]),
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([3, 3, 4])) as Arc<dyn Array>
+        ]),
+    ];
+    let result = DataFrame::memory(schema, chunks)
+        .window(vec![rank(col("score")), dense_rank(col("score"))])
+        .execute()
+        .unwrap();
+    let ranks = |i: usize| {
+        result
+            .iter()
+            .flat_map(|chunk| {
+                chunk[i]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<i64>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .values()
+                    .to_vec()
+            })
+            .collect::<Vec<i64>>()
+    };
+    assert_eq!(ranks(1), vec![1, 1, 3, 4, 4, 4, 7]);
+    assert_eq!(ranks(2), vec![1, 1, 2, 3, 3, 3, 4]);
+}
+
+#[test]
+fn test_empty_projection() {
+    let df = || DataFrame::parquet("src/tests/userdata.parquet").project(vec![]);
+    // The error is already reported by the schema of the logical plan.
+    assert!(matches!(
+        df().logical_plan().schema(),
+        Err(Error::EmptyProjection)
+    ));
+    assert!(matches!(df().execute(), Err(Error::EmptyProjection)));
+    // Nodes above the projection report it instead of panicking.
+    assert!(matches!(
+        df().filter(col("id").gt(lit_int(1))).execute(),
+        Err(Error::EmptyProjection)
+    ));
+    assert!(matches!(
+        df().limit(5).distinct_on(vec!["id"]).execute(),
+        Err(Error::EmptyProjection)
+    ));
+}
+
+#[test]
+fn test_having_stays_above_aggregate() {
+    let query = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .aggregate(vec![col("country")], vec![count(col("salary"))])
+            .filter(col("count").gt(lit_int(10)))
+    };
+    let plan = query().optimized_logical_plan();
+    match &plan {
+        LogicalPlan::Selection(sel) => match &sel.children[0] {
+            LogicalPlan::Aggregate(agg) => match &agg.children[0] {
+                LogicalPlan::Scan(scan) => {
+                    let mut projection = scan.projection.clone().unwrap();
+                    projection.sort();
+                    assert_eq!(projection, vec!["country", "salary"]);
+                    assert!(scan.filters.is_empty());
+                }
+                _ => panic!("Expected scan below aggregate."),
+            },
+            _ => panic!("Expected aggregate below selection."),
+        },
+        _ => panic!("Expected selection at the top of the plan."),
+    }
+    let rows = |df: DataFrame| df.execute().unwrap().iter().map(|x| x.len()).sum::<usize>();
+    let optimized = rows(DataFrame::new(plan));
+    assert!(optimized > 0);
+    assert_eq!(optimized, rows(query()));
+}
+
+#[test]
+fn test_scalar_accessors() {
+    let int = ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(DataType::Int32, Some(1))));
+    let float = ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(DataType::Float64, Some(1.5))));
+    let string = ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(Some("a"))));
+    let bool = ColumnarValue::Scalar(Box::new(BooleanScalar::new(Some(true))));
+    let null = ColumnarValue::Scalar(Box::new(PrimitiveScalar::<i32>::new(DataType::Int32, None)));
+    let date = ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(DataType::Date32, Some(1))));
+    let array = ColumnarValue::Array(Arc::new(PrimitiveArray::<i32>::from_slice([1])));
+
+    assert_eq!(int.as_i32(), Some(1));
+    assert_eq!(float.as_f64(), Some(1.5));
+    assert_eq!(string.as_str(), Some("a"));
+    assert_eq!(bool.as_bool(), Some(true));
+
+    assert_eq!(float.as_i32(), None);
+    assert_eq!(date.as_i32(), None);
+    assert_eq!(null.as_i32(), None);
+    assert_eq!(array.as_i32(), None);
+    assert_eq!(int.as_f64(), None);
+    assert_eq!(int.as_str(), None);
+    assert_eq!(int.as_bool(), None);
+    assert_eq!(string.as_bool(), None);
+}
+
+#[test]
+fn test_selection_skips_empty_batches() {
+    let schema = Schema::from(vec![Field::new("id", DataType::Int32, false)]);
+    let chunks = vec![
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([1, 2])) as Arc<dyn Array>
+        ]),
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([10, 20])) as Arc<dyn Array>
+        ]),
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([3])) as Arc<dyn Array>
+        ]),
+    ];
+    let df = || DataFrame::memory(schema.clone(), chunks.clone()).filter(col("id").lt(lit_int(10)));
+    let result = df().execute().unwrap();
+    assert_eq!(
+        result.iter().map(|x| x.len()).collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+
+    // The same holds once the filter is pushed into the scan.
+    let pushed_down = DataFrame::new(df().optimized_logical_plan())
+        .execute()
+        .unwrap();
+    assert_eq!(
+        pushed_down.iter().map(|x| x.len()).collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+}
+
+#[test]
+fn test_max_min_utf8() {
+    let schema = Schema::from(vec![
+        Field::new("region", DataType::Utf8, false),
+        Field::new("country", DataType::Utf8, true),
+    ]);
+    let chunks = vec![
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(["eu", "eu", "asia"])) as Arc<dyn Array>,
+            Arc::new(Utf8Array::<i32>::from([
+                Some("France"),
+                None,
+                Some("Japan"),
+            ])) as Arc<dyn Array>,
+        ]),
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(["eu", "asia"])) as Arc<dyn Array>,
+            Arc::new(Utf8Array::<i32>::from_slice(["Spain", "China"])) as Arc<dyn Array>,
+        ]),
+    ];
+    let result = DataFrame::memory(schema, chunks)
+        .aggregate(
+            vec![col("region")],
+            vec![max(col("country")), min(col("country"))],
+        )
+        .execute()
+        .unwrap();
+    let strings = |i: usize| {
+        result[0][i]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .expect("Failed to downcast array to utf8 array.")
+            .iter()
+            .map(|x| x.unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(strings(0), vec!["eu", "asia"]);
+    assert_eq!(strings(1), vec!["Spain", "Japan"]);
+    assert_eq!(strings(2), vec!["France", "China"]);
+}
+
+#[test]
+fn test_self_join() {
+    let df = DataFrame::parquet("src/tests/userdata.parquet").project(vec![col("id")]);
+    let mut ids: HashMap<i32, usize> = HashMap::new();
+    df.try_clone()
+        .unwrap()
+        .execute()
+        .unwrap()
+        .iter()
+        .for_each(|chunk| {
+            chunk[0]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .flatten()
+                .for_each(|id| *ids.entry(*id).or_default() += 1)
+        });
+    let other = df.try_clone().unwrap();
+    let joined = df.join(other, vec![("id", "id")]);
+    // Joins are exempt from the unique names of projections and aggregates.
+    let names = joined
+        .schema()
+        .unwrap()
+        .fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["id", "id"]);
+    let result = joined.execute().unwrap();
+    let rows = result.iter().map(|x| x.len()).sum::<usize>();
+    assert_eq!(rows, ids.values().map(|x| x * x).sum::<usize>());
+    result
+        .iter()
+        .for_each(|chunk| assert_eq!(chunk[0], chunk[1]));
+}
+
+#[test]
+fn test_join_null_keys() {
+    let side = |key: &str, value: &str, keys: Vec<Option<i32>>, values: Vec<i32>| {
+        DataFrame::memory(
+            Schema::from(vec![
+                Field::new(key, DataType::Int32, true),
+                Field::new(value, DataType::Int32, false),
+            ]),
+            vec![Chunk::new(vec![
+                Arc::new(PrimitiveArray::<i32>::from(keys)) as Arc<dyn Array>,
+                Arc::new(PrimitiveArray::<i32>::from_vec(values)) as Arc<dyn Array>,
+            ])],
+        )
+    };
+    let join = || {
+        side("a", "l", vec![Some(1), None, Some(2)], vec![10, 20, 30]).join(
+            side("b", "r", vec![None, Some(1), None], vec![100, 200, 300]),
+            vec![("a", "b")],
+        )
+    };
+    let pairs = |config: &ExecutionConfig| {
+        let mut pairs = join()
+            .execute_with_config(config)
+            .unwrap()
+            .iter()
+            .flat_map(|chunk| {
+                let values = |i: usize| {
+                    chunk[i]
+                        .as_any()
+                        .downcast_ref::<PrimitiveArray<i32>>()
+                        .expect("Failed to downcast array to primitive array.")
+                        .values()
+                        .to_vec()
+                };
+                values(1).into_iter().zip(values(3)).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs
+    };
+    assert_eq!(pairs(&ExecutionConfig::default()), vec![(10, 200)]);
+    assert_eq!(
+        pairs(&ExecutionConfig {
+            null_equals_null: true,
+            ..Default::default()
+        }),
+        vec![(10, 200), (20, 100), (20, 300)]
+    );
+}
+
+#[derive(Debug)]
+struct Product;
+
+struct ProductAccumulator {
+    value: Option<i64>,
+    index: usize,
+}
+
+impl Accumulator for ProductAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &Vec<ColumnarValue>,
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let array = match &input[self.index] {
+            ColumnarValue::Array(array) => array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i64>>()
+                .ok_or(Error::DowncastError)?,
+            ColumnarValue::Scalar(_) => return Err(Error::DowncastError),
+        };
+        array
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| validity.map(|val| val.get_bit(*i)).unwrap_or(true))
+            .filter_map(|(_, x)| x)
+            .for_each(|x| self.value = Some(self.value.unwrap_or(1) * x));
+        Ok(())
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        let other = other
+            .into_any()
+            .downcast::<ProductAccumulator>()
+            .map_err(|_| Error::DowncastError)?;
+        if let Some(x) = other.value {
+            self.value = Some(self.value.unwrap_or(1) * x);
+        }
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+            DataType::Int64,
+            self.value,
+        ))))
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
+        self
+    }
+}
+
+impl AggregateUdf for Product {
+    fn name(&self) -> &str {
+        "product"
+    }
+    fn output_type(&self) -> DataType {
+        DataType::Int64
+    }
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(ProductAccumulator { value: None, index })
+    }
+}
+
+#[test]
+fn test_aggregate_udf() {
+    let df = || {
+        DataFrame::memory(
+            Schema::from(vec![
+                Field::new("g", DataType::Utf8, false),
+                Field::new("x", DataType::Int64, true),
+            ]),
+            vec![Chunk::new(vec![
+                Arc::new(Utf8Array::<i32>::from_slice(["a", "b", "a", "b", "a"])) as Arc<dyn Array>,
+                Arc::new(PrimitiveArray::<i64>::from([
+                    Some(2),
+                    Some(3),
+                    None,
+                    Some(5),
+                    Some(4),
+                ])) as Arc<dyn Array>,
+            ])],
+        )
+        .aggregate(vec![col("g")], vec![aggregate(&Product, col("x"))])
+    };
+    assert!(matches!(
+        df().execute(),
+        Err(Error::AggregateNotFound(name)) if name == "product"
+    ));
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_aggregate(Arc::new(Product));
+    let result = ctx.execute(df()).unwrap();
+    let result = result
+        .iter()
+        .flat_map(|chunk| {
+            chunk[0]
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .expect("Failed to downcast array to utf8 array.")
+                .iter()
+                .map(|x| x.unwrap().to_string())
+                .zip(
+                    chunk[1]
+                        .as_any()
+                        .downcast_ref::<PrimitiveArray<i64>>()
+                        .expect("Failed to downcast array to primitive array.")
+                        .iter()
+                        .map(|x| x.copied()),
+                )
+                .collect::<Vec<_>>()
+        })
+        .collect::<HashMap<String, Option<i64>>>();
+    assert_eq!(
+        result,
+        HashMap::from([("a".to_string(), Some(8)), ("b".to_string(), Some(15))])
+    );
+}
+
+#[test]
+fn test_scalar_udf() {
+    let df = || {
+        DataFrame::parquet("src/tests/userdata.parquet").project(vec![
+            col("salary"),
+            udf("double", DataType::Float64, vec![col("salary")]),
+        ])
+    };
+    assert!(matches!(
+        df().execute(),
+        Err(Error::FunctionNotFound(name)) if name == "double"
+    ));
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_udf("double", |args| match &args[0] {
+        ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(compute::arity::unary(
+            array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .ok_or(Error::DowncastError)?,
+            |x| x * 2.0,
+            DataType::Float64,
+        )))),
+        ColumnarValue::Scalar(_) => Err(Error::DowncastError),
+    });
+    let result = ctx.execute(df()).unwrap();
+    assert_eq!(result.iter().map(|x| x.len()).sum::<usize>(), 1000);
+    result.iter().for_each(|chunk| {
+        let values = |i: usize| {
+            chunk[i]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .map(|x| x.copied())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            values(0)
+                .into_iter()
+                .map(|x| x.map(|x| x * 2.0))
+                .collect::<Vec<_>>(),
+            values(1)
+        );
+    });
+
+    ctx.register_udf("halve", |args| match &args[0] {
+        ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(compute::arity::unary(
+            array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .ok_or(Error::DowncastError)?,
+            |x| x as f64 / 2.0,
+            DataType::Float64,
+        )))),
+        ColumnarValue::Scalar(_) => Err(Error::DowncastError),
+    });
+    let halves = |data_type: DataType| {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![col("id"), udf("halve", data_type, vec![col("id")])])
+    };
+    assert_eq!(
+        halves(DataType::Float64).schema().unwrap().fields[1].data_type,
+        DataType::Float64
+    );
+    let result = ctx.execute(halves(DataType::Float64)).unwrap();
+    result.iter().for_each(|chunk| {
+        let ids = chunk[0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.");
+        let halves = chunk[1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.");
+        assert_eq!(
+            ids.values()
+                .iter()
+                .map(|x| *x as f64 / 2.0)
+                .collect::<Vec<_>>(),
+            halves.values().to_vec()
+        );
+    });
+    assert!(matches!(
+        ctx.execute(halves(DataType::Int32)),
+        Err(Error::ReturnTypeMismatch(name, returned, declared))
+            if name == "halve" && returned == "Float64" && declared == "Int32"
+    ));
+}
+
+#[test]
+fn test_max_negative_values() {
+    let chunk = |values: Vec<Option<i32>>, floats: Vec<Option<f64>>| {
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(vec!["a"; values.len()])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from(values)) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<f64>::from(floats)) as Arc<dyn Array>,
+        ])
+    };
+    let result = DataFrame::memory(
+        Schema::from(vec![
+            Field::new("g", DataType::Utf8, false),
+            Field::new("i", DataType::Int32, true),
+            Field::new("f", DataType::Float64, true),
+        ]),
+        vec![
+            chunk(vec![None, None], vec![None, None]),
+            chunk(vec![Some(-7), Some(-3)], vec![Some(-7.5), Some(-3.5)]),
+            chunk(vec![Some(-12), None], vec![None, Some(-12.5)]),
+        ],
+    )
+    .aggregate(
+        vec![col("g")],
+        vec![max(col("i")).alias("max_i"), max(col("f")).alias("max_f")],
+    )
+    .execute()
+    .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(
+        result[0][1]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.copied())
+            .collect::<Vec<_>>(),
+        vec![Some(-3)]
+    );
+    assert_eq!(
+        result[0][2]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.copied())
+            .collect::<Vec<_>>(),
+        vec![Some(-3.5)]
+    );
+}
+
+#[test]
+fn test_explain_analyze() {
+    let df = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(col("salary").gt(lit_float(200000.0)))
+    };
+    let rows = df()
+        .execute()
+        .unwrap()
+        .iter()
+        .map(|x| x.len())
+        .sum::<usize>();
+    // The plan as built, annotated the same way explain_analyze does it.
+    let (plan, metrics) = df()
+        .logical_plan()
+        .to_physical_plan()
+        .unwrap()
+        .with_metrics();
+    plan.execute()
+        .unwrap()
+        .try_for_each(|batch| batch.map(|_| ()))
+        .unwrap();
+    let explained = metrics.to_string();
+    let lines = explained.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(&format!("Selection [rows={}, ", rows)));
+    assert!(lines[1].starts_with(" \tScan [rows=1000, "));
+    assert!(lines.iter().all(|line| line.ends_with("ms]")));
+
+    // The executed plan is optimized, the filter is applied by the scan.
+    let explained = df().explain_analyze().unwrap();
+    let lines = explained.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with(&format!("Scan [rows={}, ", rows)));
+    assert!(lines[0].ends_with("ms]"));
+}
+
+#[test]
+fn test_string_is_in_and_between() {
+    let strings = |df: DataFrame, column: &str| {
+        let result = df.project(vec![col(column)]).execute().unwrap();
+        result
+            .iter()
+            .flat_map(|chunk| {
+                chunk[0]
+                    .as_any()
+                    .downcast_ref::<Utf8Array<i32>>()
+                    .expect("Failed to downcast array to utf8 array.")
+                    .iter()
+                    .flatten()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    let all = |column: &str| strings(DataFrame::parquet("src/tests/userdata.parquet"), column);
+
+    let countries = strings(
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(col("country").is_in(vec![lit_string("Canada"), lit_string("Brazil")])),
+        "country",
+    );
+    let expected = all("country")
+        .into_iter()
+        .filter(|x| x == "Canada" || x == "Brazil")
+        .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+    assert_eq!(countries, expected);
+
+    let names = strings(
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(col("first_name").between(lit_string("A"), lit_string("M"))),
+        "first_name",
+    );
+    let expected = all("first_name")
+        .into_iter()
+        .filter(|x| x.as_str() >= "A" && x.as_str() <= "M")
+        .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+    assert_eq!(names, expected);
+
+    let none = strings(
+        DataFrame::parquet("src/tests/userdata.parquet").filter(col("country").is_in(vec![])),
+        "country",
+    );
+    assert!(none.is_empty());
+}
+
+#[test]
+fn test_plan_node_count_and_depth() {
+    let chain = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("country"), col("salary")])
+        .filter(col("salary").gt(lit_float(100000.0)))
+        .aggregate(vec![col("country")], vec![max(col("salary"))])
+        .logical_plan();
+    assert_eq!(chain.node_count(), 4);
+    assert_eq!(chain.depth(), 4);
+
+    let join = DataFrame::parquet("src/tests/userdata.parquet")
+        .join(
+            DataFrame::parquet("src/tests/userdata.parquet").filter(col("id").lt(lit_int(10))),
+            vec![("id", "id")],
+        )
+        .logical_plan();
+    assert_eq!(join.node_count(), 4);
+    assert_eq!(join.depth(), 3);
+}
+
+#[test]
+fn test_parquet_glob() {
+    let dir = std::env::temp_dir().join("query_engine_test_parquet_glob");
+    for (year, filter) in [
+        ("2020", col("id").lteq(lit_int(300))),
+        ("2021", col("id").gt(lit_int(300))),
+    ] {
+        let partition = dir.join(format!("year={}", year));
+        std::fs::create_dir_all(&partition).unwrap();
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(filter)
+            .project(vec![col("id"), col("country")])
+            .write_parquet(partition.join("data.parquet").to_str().unwrap())
+            .unwrap();
+    }
+    std::fs::write(dir.join("year=2020").join("notes.txt"), "not parquet").unwrap();
+    let pattern = dir.join("year=*").join("*.parquet");
+    let pattern = pattern.to_str().unwrap();
+
+    let result = DataFrame::parquet_glob(pattern).execute().unwrap();
+    assert_eq!(result.iter().map(|x| x.len()).sum::<usize>(), 1000);
+
+    let canada = DataFrame::parquet_glob(pattern)
+        .filter(col("country").eq(lit_string("Canada")))
+        .execute()
+        .unwrap()
+        .iter()
+        .map(|x| x.len())
+        .sum::<usize>();
+    let expected = DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("country").eq(lit_string("Canada")))
+        .execute()
+        .unwrap()
+        .iter()
+        .map(|x| x.len())
+        .sum::<usize>();
+    assert_eq!(canada, expected);
+
+    assert!(matches!(
+        ParquetFilesDataSource::glob(dir.join("missing=*").join("*.parquet").to_str().unwrap()),
+        Err(Error::NoMatchingFiles(_))
+    ));
+
+    let other = dir.join("other");
+    std::fs::create_dir_all(&other).unwrap();
+    DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id")])
+        .write_parquet(other.join("data.parquet").to_str().unwrap())
+        .unwrap();
+    assert!(matches!(
+        ParquetFilesDataSource::glob(dir.join("*").join("*.parquet").to_str().unwrap()),
+        Err(Error::IncompatibleFiles(..))
+    ));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_hive_partitioning() {
+    let dir = std::env::temp_dir().join("query_engine_test_hive_partitioning");
+    let rows = |df: DataFrame| df.execute().unwrap().iter().map(|x| x.len()).sum::<usize>();
+    let countries = ["Canada", "Brazil"];
+    for country in countries {
+        let partition = dir.join(format!("country={}", country));
+        std::fs::create_dir_all(&partition).unwrap();
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(col("country").eq(lit_string(country)))
+            .project(vec![col("id"), col("salary")])
+            .write_parquet(partition.join("data.parquet").to_str().unwrap())
+            .unwrap();
+    }
+    let pattern = dir.join("*").join("*.parquet");
+    let df = || {
+        DataFrame::parquet_glob_with(
+            pattern.to_str().unwrap(),
+            ParquetReadOptions {
+                hive_partitioning: true,
+                ..Default::default()
+            },
+        )
+    };
+
+    let schema = df().output_schema().unwrap();
+    assert_eq!(
+        schema
+            .fields
+            .iter()
+            .map(|x| (x.name.as_str(), x.data_type.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("id", DataType::Int32),
+            ("salary", DataType::Float64),
+            ("country", DataType::Utf8),
+        ]
+    );
+
+    // Files are scanned in path order, every file produces its own batches.
+    let mut files = df()
+        .execute()
+        .unwrap()
+        .iter()
+        .map(|chunk| {
+            let values = chunk[2]
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .expect("Failed to downcast array to utf8 array.")
+                .values_iter()
+                .map(|x| x.to_string())
+                .collect::<HashSet<_>>();
+            assert_eq!(values.len(), 1);
+            (values.into_iter().next().unwrap(), chunk.len())
+        })
+        .collect::<Vec<_>>();
+    files.dedup_by(|a, b| {
+        let same = a.0 == b.0;
+        if same {
+            b.1 += a.1;
+        }
+        same
+    });
+    let expected = ["Brazil", "Canada"]
+        .iter()
+        .map(|country| {
+            (
+                country.to_string(),
+                rows(
+                    DataFrame::parquet("src/tests/userdata.parquet")
+                        .filter(col("country").eq(lit_string(country))),
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(files, expected);
+
+    assert_eq!(
+        rows(df().filter(col("country").eq(lit_string("Canada")))),
+        expected[1].1
+    );
+    // The filter on the salary is applied by the files, the one on the partition column after
+    // the partition column was appended.
+    let high_salary = |df: DataFrame| {
+        rows(
+            df.filter(
+                col("country")
+                    .eq(lit_string("Canada"))
+                    .and(col("salary").gt(lit_float(200000.0))),
+            ),
+        )
+    };
+    assert_eq!(
+        high_salary(df()),
+        high_salary(DataFrame::parquet("src/tests/userdata.parquet"))
+    );
+    let result = df()
+        .aggregate(vec![col("country")], vec![count(col("country"))])
+        .execute()
+        .unwrap();
+    assert_eq!(result.iter().map(|x| x.len()).sum::<usize>(), 2);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_unify_integer_width() {
+    let dir = std::env::temp_dir().join("query_engine_test_unify_integer_width");
+    std::fs::create_dir_all(&dir).unwrap();
+    DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("id").lteq(lit_int(400)))
+        .project(vec![col("id"), col("salary")])
+        .write_parquet(dir.join("a.parquet").to_str().unwrap())
+        .unwrap();
+    DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("id").gt(lit_int(400)))
+        .project(vec![
+            col("id").cast(DataType::Int64).alias("id"),
+            col("salary"),
+        ])
+        .write_parquet(dir.join("b.parquet").to_str().unwrap())
+        .unwrap();
+    let pattern = dir.join("*.parquet");
+    let pattern = pattern.to_str().unwrap();
+    assert!(matches!(
+        ParquetFilesDataSource::glob(pattern),
+        Err(Error::IncompatibleFiles(..))
+    ));
+
+    let df = || {
+        DataFrame::parquet_glob_with(
+            pattern,
+            ParquetReadOptions {
+                unify_integer_width: Some(DataType::Int64),
+                ..Default::default()
+            },
+        )
+    };
+    let schema = df().output_schema().unwrap();
+    assert_eq!(
+        schema
+            .fields
+            .iter()
+            .map(|x| (x.name.as_str(), x.data_type.clone()))
+            .collect::<Vec<_>>(),
+        vec![("id", DataType::Int64), ("salary", DataType::Float64)]
+    );
+    let ids = df()
+        .project(vec![col("id")])
+        .execute()
+        .unwrap()
+        .iter()
+        .flat_map(|chunk| {
+            chunk[0]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .values()
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ids, (1..=1000).collect::<Vec<i64>>());
+    // The file storing Int32 ids is filtered after the cast.
+    let filtered = df()
+        .filter(col("id").gt(lit_i64(398)).and(col("id").lteq(lit_i64(402))))
+        .execute()
+        .unwrap()
+        .iter()
+        .map(|x| x.len())
+        .sum::<usize>();
+    assert_eq!(filtered, 4);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_null_safe_eq() {
+    let df = || {
+        DataFrame::memory(
+            Schema::from(vec![
+                Field::new("a", DataType::Int32, true),
+                Field::new("b", DataType::Int32, true),
+            ]),
+            vec![Chunk::new(vec![
+                Arc::new(PrimitiveArray::<i32>::from([
+                    Some(1),
+                    None,
+                    Some(3),
+                    None,
+                    Some(5),
+                ])) as Arc<dyn Array>,
+                Arc::new(PrimitiveArray::<i32>::from([
+                    Some(1),
+                    None,
+                    None,
+                    Some(4),
+                    Some(6),
+                ])) as Arc<dyn Array>,
+            ])],
+        )
+    };
+    let booleans = |expr: LogicalExpression| {
+        let result = df().project(vec![expr]).execute().unwrap();
+        result[0][0]
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .expect("Failed to downcast array to boolean array.")
+            .iter()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        booleans(col("a").null_safe_eq(col("b"))),
+        vec![
+            Some(true),
+            Some(true),
+            Some(false),
+            Some(false),
+            Some(false)
+        ]
+    );
+    assert_eq!(
+        booleans(col("a").eq(col("b"))),
+        vec![Some(true), None, None, None, Some(false)]
+    );
+    assert_eq!(
+        booleans(col("a").null_safe_eq(lit_null(DataType::Int32))),
+        vec![
+            Some(false),
+            Some(true),
+            Some(false),
+            Some(true),
+            Some(false)
+        ]
+    );
+    let rows = df()
+        .filter(col("a").null_safe_eq(col("b")))
+        .execute()
+        .unwrap()
+        .iter()
+        .map(|x| x.len())
+        .sum::<usize>();
+    assert_eq!(rows, 2);
+}
+
+#[test]
+fn test_limit_stops_parquet_scan() {
+    // Explains the query on a fresh source and counts the rows the parquet reader decoded.
+    let decoded = |query: fn(DataFrame) -> DataFrame| -> (String, usize) {
+        let path = "src/tests/userdata.parquet";
+        let ds = DataSource::Parquet(ParquetDataSource::new(path).unwrap());
+        let counter = ds.try_clone().unwrap();
+        let explained = query(DataFrame::new(LogicalPlan::Scan(Scan::new(path, ds, None))))
+            .explain_analyze()
+            .unwrap();
+        (explained, counter.decoded_rows())
+    };
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .limit(5)
+        .project(vec![col("id")])
+        .execute()
+        .unwrap();
+    assert_eq!(
+        result
+            .iter()
+            .flat_map(|chunk| {
+                chunk[0]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<i32>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .values()
+                    .to_vec()
+            })
+            .collect::<Vec<_>>(),
+        vec![1, 2, 3, 4, 5]
+    );
+
+    let (limited, rows) = decoded(|df| df.project(vec![col("id"), col("salary")]).limit(5));
+    assert!(limited.starts_with("Limit [rows=5, "));
+    assert!(rows <= 5);
+
+    // The scan filters rows, so it has to read on until the limit is reached.
+    let (filtered, rows) =
+        decoded(|df| df.filter(col("country").eq(lit_string("Canada"))).limit(5));
+    assert!(filtered.starts_with("Limit [rows=5, "));
+    assert_eq!(rows, 1000);
+}
+
+#[test]
+fn test_count_is_int64() {
+    let df = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .aggregate(vec![col("country")], vec![count(col("salary"))])
+    };
+    let field = |schema: &Schema| schema.fields[1].clone();
+    let logical = df().logical_plan();
+    let logical = field(logical.schema().unwrap());
+    assert_eq!(logical.name, "count");
+    assert_eq!(logical.data_type, DataType::Int64);
+    assert!(!logical.is_nullable);
+    assert_eq!(field(&df().output_schema().unwrap()), logical);
+
+    let result = df().execute().unwrap();
+    assert_eq!(result[0][1].data_type(), &DataType::Int64);
+}
+
+#[test]
+fn test_merge_partitioned_aggregate() {
+    let aggregates = || {
+        vec![
+            max(col("salary")),
+            min(col("salary")),
+            sum(col("salary")),
+            count(col("salary")),
+            avg(col("salary")),
+        ]
+    };
+    let single = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .aggregate(vec![col("country")], aggregates())
+    };
+    let schema = single().logical_plan().schema().unwrap().clone();
+    let single = single().execute().unwrap();
+
+    let half = |predicate: LogicalExpression| {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(predicate)
+            .project(vec![col("country"), col("salary")])
+            .logical_plan()
+            .to_physical_plan()
+            .unwrap()
+    };
+    let salary = || Box::new(ColumnExpression { index: 1 }) as Box<dyn PhysicalExpression>;
+    let aggregate_exprs = || -> Vec<Box<dyn PhysicalAggregateExpression>> {
+        vec![
+            Box::new(MaxExpression::new(salary())),
+            Box::new(MinExpression::new(salary())),
+            Box::new(SumExpression::new(salary(), DataType::Float64)),
+            Box::new(CountExpression::new(salary())),
+            Box::new(AvgExpression::new(salary())),
+        ]
+    };
+    let merged = |dictionary_keys: bool| {
+        PhysicalPlan::Aggregate(
+            AggregateExec::partitioned(
+                vec![
+                    half(col("id").lteq(lit_int(500))),
+                    half(col("id").gt(lit_int(500))),
+                ],
+                vec![Box::new(ColumnExpression { index: 0 })],
+                aggregate_exprs(),
+                schema.clone(),
+            )
+            .unwrap()
+            .with_dictionary_keys(dictionary_keys),
+        )
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+    };
+
+    let floats = |chunk: &Chunk<Arc<dyn Array>>, i: usize| {
+        chunk[i]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.copied())
+            .collect::<Vec<_>>()
+    };
+    let assert_close = |left: Vec<Option<f64>>, right: Vec<Option<f64>>| {
+        assert_eq!(left.len(), right.len());
+        left.iter()
+            .zip(right.iter())
+            .for_each(|(l, r)| match (l, r) {
+                (Some(l), Some(r)) => assert!((l - r).abs() < 1e-6 * r.abs().max(1.0)),
+                _ => assert_eq!(l, r),
+            });
+    };
+    for dictionary_keys in [false, true] {
+        let merged = merged(dictionary_keys);
+        assert_eq!(merged.len(), 1);
+        let (merged, single) = (&merged[0], &single[0]);
+        assert_eq!(merged.len(), single.len());
+        assert_eq!(merged[0].as_ref(), single[0].as_ref());
+        assert_eq!(floats(merged, 1), floats(single, 1));
+        assert_eq!(floats(merged, 2), floats(single, 2));
+        assert_close(floats(merged, 3), floats(single, 3));
+        assert_eq!(merged[4].as_ref(), single[4].as_ref());
+        assert_close(floats(merged, 5), floats(single, 5));
+    }
+}
+
+#[test]
+fn test_merge_sum_overflow() {
+    let accumulator = |value: i64| {
+        let mut accumulator =
+            SumExpression::new(Box::new(ColumnExpression { index: 0 }), DataType::Int64)
+                .create_accumulator(0);
+        accumulator
+            .accumulate(
+                &vec![ColumnarValue::Array(Arc::new(
+                    PrimitiveArray::<i64>::from_slice([value]),
+                ))],
+                None,
+            )
+            .unwrap();
+        accumulator
+    };
+    let mut merged = accumulator(i64::MAX);
+    assert!(matches!(
+        merged.merge(accumulator(1)),
+        Err(Error::IntegerOverflow(name)) if name == "sum"
+    ));
+}
+
+#[test]
+fn test_compare_int32_with_i64_literal() {
+    let ids = |predicate: LogicalExpression| {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(predicate)
+            .project(vec![col("id")])
+            .execute()
+            .unwrap()
+            .iter()
+            .flat_map(|chunk| {
+                chunk[0]
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<i32>>()
+                    .expect("Failed to downcast array to primitive array.")
+                    .values()
+                    .to_vec()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(col("id").eq(lit_i64(4))), vec![4]);
+    assert_eq!(ids(lit_i64(4).eq(col("id"))), vec![4]);
+    assert_eq!(ids(col("id").lteq(lit_i64(3))), vec![1, 2, 3]);
+    assert_eq!(ids(col("id").gt(lit_i64(998))), vec![999, 1000]);
+    assert_eq!(ids(col("id").neq(lit_i64(1))).len(), 999);
+    // The literal doesn't fit into an Int32.
+    assert!(ids(col("id").gteq(lit_i64(i64::from(i32::MAX) + 1))).is_empty());
+}
+
+#[test]
+fn test_dataframe_transform() {
+    // Reads `last_name` wherever the projection reads `first_name`.
+    let swap = |plan: LogicalPlan| match plan {
+        LogicalPlan::Projection(mut proj) => {
+            let exprs = proj
+                .exprs
+                .into_iter()
+                .map(|expr| match expr {
+                    LogicalExpression::Column(column) if column.name == "first_name" => {
+                        col("last_name").alias("first_name")
+                    }
+                    expr => expr,
+                })
+                .collect();
+            LogicalPlan::Projection(Projection::new(proj.children.pop().unwrap(), exprs))
+        }
+        plan => plan,
+    };
+    let names = |df: DataFrame| {
+        df.execute()
+            .unwrap()
+            .iter()
+            .flat_map(|chunk| {
+                chunk[0]
+                    .as_any()
+                    .downcast_ref::<Utf8Array<i32>>()
+                    .expect("Failed to downcast array to utf8 array.")
+                    .iter()
+                    .map(|x| x.unwrap().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    let df = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(col("id").lteq(lit_int(3)))
+            .project(vec![col("first_name"), col("last_name")])
+    };
+    assert_eq!(names(df()), vec!["Amanda", "Albert", "Evelyn"]);
+    assert_eq!(
+        names(df().transform(swap)),
+        vec!["Jordan", "Freeman", "Morgan"]
+    );
+    assert!(df()
+        .transform(swap)
+        .optimized_logical_plan()
+        .to_string()
+        .contains("#last_name AS first_name"));
+    // DataFrames built on top of a transformed one keep the rewrite.
+    assert_eq!(
+        names(df().transform(swap).project(vec![col("first_name")])),
+        vec!["Jordan", "Freeman", "Morgan"]
+    );
+    // The transforms are applied to the whole plan at a single point, so registering them before
+    // or after building on top of a DataFrame gives the same result, and the optimized plan is
+    // the one that is executed.
+    assert_eq!(
+        names(df().transform(swap).project(vec![col("first_name")])),
+        names(df().project(vec![col("first_name")]).transform(swap))
+    );
+    assert_eq!(
+        names(DataFrame::new(
+            df().transform(swap).optimized_logical_plan()
+        )),
+        names(df().transform(swap))
+    );
+}
+
+#[test]
+fn test_wildcard_projection_push_down() {
+    let df = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![wildcard()])
+            .filter(col("id").lteq(lit_int(3)))
+            .project(vec![col("first_name")])
+    };
+    let plan = df().optimized_logical_plan();
+    let scan = match &plan {
+        LogicalPlan::Projection(proj) => match &proj.children[0] {
+            LogicalPlan::Selection(sel) => match &sel.children[0] {
+                LogicalPlan::Projection(proj) => &proj.children[0],
+                _ => panic!("Expected the wildcard projection below the selection."),
+            },
+            _ => panic!("Expected selection below projection."),
+        },
+        _ => panic!("Expected projection at the top of the plan."),
+    };
+    match scan {
+        LogicalPlan::Scan(scan) => {
+            let mut projection = scan.projection.clone().unwrap();
+            projection.sort();
+            assert_eq!(projection, vec!["first_name", "id"]);
+        }
+        _ => panic!("Expected scan below the wildcard projection."),
+    }
+
+    let result = DataFrame::new(df().optimized_logical_plan())
+        .execute()
+        .unwrap();
+    let names = result
+        .iter()
+        .flat_map(|chunk| {
+            chunk[0]
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .expect("Failed to downcast array to utf8 array.")
+                .iter()
+                .map(|x| x.unwrap().to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Amanda", "Albert", "Evelyn"]);
+
+    // A wildcard at the top of the plan still reads every column.
+    let all = DataFrame::parquet("src/tests/userdata.parquet")
+        .filter(col("id").lteq(lit_int(3)))
+        .project(vec![wildcard()]);
+    let columns = all.schema().unwrap().fields.len();
+    let result = DataFrame::new(all.optimized_logical_plan())
+        .execute()
+        .unwrap();
+    assert_eq!(result[0].arrays().len(), columns);
+}
+
+#[test]
+fn test_column_expression_shares_array() {
+    let values: Arc<dyn Array> = Arc::new(PrimitiveArray::<i64>::from_vec((0..1000).collect()));
+    let names: Arc<dyn Array> = Arc::new(Utf8Array::<i32>::from_slice(vec!["a"; 1000]));
+    let chunk = Chunk::new(vec![values.clone(), names.clone()]);
+    let schema = Schema::from(vec![
+        Field::new("values", DataType::Int64, false),
+        Field::new("names", DataType::Utf8, false),
+    ]);
+
+    let evaluated = match (ColumnExpression { index: 0 })
+        .evaluate(&chunk, &schema)
+        .unwrap()
+    {
+        ColumnarValue::Array(array) => array,
+        ColumnarValue::Scalar(_) => panic!("Expected an array."),
+    };
+    assert!(Arc::ptr_eq(&evaluated, &values));
+    // `values`, the chunk and the result of the evaluation.
+    assert_eq!(Arc::strong_count(&values), 3);
+
+    // Projecting through a plan keeps the buffers of the input.
+    let schema = Schema::from(vec![
+        Field::new("value", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]);
+    let result = DataFrame::memory(schema, vec![chunk])
+        .project(vec![col("name"), col("value")])
+        .execute()
+        .unwrap();
+    let buffer = |array: &Arc<dyn Array>| {
+        array
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .values()
+            .as_ptr()
+    };
+    assert_eq!(buffer(&result[0][1]), buffer(&values));
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .expect("Failed to downcast array to utf8 array.")
+            .values()
+            .as_ptr(),
+        names
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .unwrap()
+            .values()
+            .as_ptr()
+    );
+}
+
+#[test]
+fn test_filter_on_computed_column() {
+    let df = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![col("id"), col("id").add(col("id")).alias("c")])
+            .filter(col("c").gt(lit_int(10)).and(col("c").lteq(lit_int(16))))
+    };
+    let rows = |df: DataFrame| {
+        df.execute()
+            .unwrap()
+            .iter()
+            .flat_map(|chunk| {
+                let column = |i: usize| {
+                    chunk[i]
+                        .as_any()
+                        .downcast_ref::<PrimitiveArray<i32>>()
+                        .expect("Failed to downcast array to primitive array.")
+                        .values()
+                        .to_vec()
+                };
+                column(0).into_iter().zip(column(1)).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    let expected = vec![(6, 12), (7, 14), (8, 16)];
+    assert_eq!(rows(df()), expected);
+    assert_eq!(
+        rows(DataFrame::new(df().optimized_logical_plan())),
+        expected
+    );
+}
+
+#[derive(Debug)]
+struct MeanF32;
+
+struct MeanF32Accumulator {
+    sum: f32,
+    count: usize,
+    index: usize,
+}
+
+impl Accumulator for MeanF32Accumulator {
+    fn accumulate(
+        &mut self,
+        input: &Vec<ColumnarValue>,
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let array = match &input[self.index] {
+            ColumnarValue::Array(array) => array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f32>>()
+                .ok_or(Error::DowncastError)?,
+            ColumnarValue::Scalar(_) => return Err(Error::DowncastError),
+        };
+        array
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| validity.map(|val| val.get_bit(*i)).unwrap_or(true))
+            .filter_map(|(_, x)| x)
+            .for_each(|x| {
+                self.sum += x;
+                self.count += 1;
+            });
+        Ok(())
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        let other = other
+            .into_any()
+            .downcast::<MeanF32Accumulator>()
+            .map_err(|_| Error::DowncastError)?;
+        self.sum += other.sum;
+        self.count += other.count;
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+            DataType::Float32,
+            (self.count > 0).then(|| self.sum / self.count as f32),
+        ))))
+    }
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
+        self
+    }
+}
+
+impl AggregateUdf for MeanF32 {
+    fn name(&self) -> &str {
+        "mean_f32"
+    }
+    fn output_type(&self) -> DataType {
+        DataType::Float32
+    }
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(MeanF32Accumulator {
+            sum: 0.0,
+            count: 0,
+            index,
+        })
+    }
+}
+
+#[test]
+fn test_aggregate_output_types() {
+    let df = DataFrame::memory(
+        Schema::from(vec![
+            Field::new("key", DataType::Int64, true),
+            Field::new("value", DataType::Float32, true),
+        ]),
+        vec![
+            Chunk::new(vec![
+                Arc::new(PrimitiveArray::<i64>::from([
+                    Some(7),
+                    Some(8),
+                    None,
+                    Some(7),
+                ])) as Arc<dyn Array>,
+                Arc::new(PrimitiveArray::<f32>::from([
+                    Some(1.0),
+                    Some(2.0),
+                    Some(3.0),
+                    Some(4.0),
+                ])) as Arc<dyn Array>,
+            ]),
+            Chunk::new(vec![
+                Arc::new(PrimitiveArray::<i64>::from([Some(9), Some(8)])) as Arc<dyn Array>,
+                Arc::new(PrimitiveArray::<f32>::from([None, Some(5.0)])) as Arc<dyn Array>,
+            ]),
+        ],
+    )
+    .aggregate(
+        vec![col("key")],
+        vec![count(col("value")), aggregate(&MeanF32, col("value"))],
+    );
+    let mut ctx = ExecutionContext::new();
+    ctx.register_aggregate(Arc::new(MeanF32));
+    let result = ctx.execute(df).unwrap();
+    assert_eq!(result.len(), 1);
+    let result = &result[0];
+    assert_eq!(result[2].data_type(), &DataType::Float32);
+
+    let keys = result[0]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i64>>()
+        .expect("Failed to downcast array to primitive array.")
+        .iter()
+        .map(|x| x.copied())
+        .collect::<Vec<_>>();
+    let counts = result[1]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i64>>()
+        .expect("Failed to downcast array to primitive array.")
+        .iter()
+        .map(|x| x.copied())
+        .collect::<Vec<_>>();
+    let means = result[2]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<f32>>()
+        .expect("Failed to downcast array to primitive array.")
+        .iter()
+        .map(|x| x.copied())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, vec![Some(7), Some(8), None, Some(9)]);
+    assert_eq!(counts, vec![Some(2), Some(2), Some(1), Some(0)]);
+    assert_eq!(means, vec![Some(2.5), Some(3.5), Some(3.0), None]);
+}
+
+#[test]
+fn test_execute_unoptimized() {
+    let df = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(col("salary").gt(lit_float(250000.0)))
+            .project(vec![col("id"), col("first_name")])
+    };
+    // The optimizer pushes the filter into the scan.
+    assert!(format_logical_plan(&df().logical_plan(), 0).contains("Selection"));
+    let optimized = df().optimized_logical_plan();
+    assert!(!format_logical_plan(&optimized, 0).contains("Selection"));
+    let optimized = DataFrame::new(optimized).execute().unwrap();
+    let unoptimized = df().execute_unoptimized().unwrap();
+    assert!(!unoptimized.is_empty());
+    assert_eq!(
+        concat_chunks(&optimized).unwrap().arrays(),
+        concat_chunks(&unoptimized).unwrap().arrays()
+    );
+    // Transforms are still applied, only the built-in optimizations are skipped.
+    let limited = df()
+        .transform(|plan| LogicalPlan::Limit(Limit::new(plan, 2)))
+        .execute_unoptimized()
+        .unwrap();
+    assert_eq!(limited.iter().map(|x| x.len()).sum::<usize>(), 2);
+}
+
+#[test]
+fn test_group_by_float() {
+    let buckets = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(
+            vec![col("id")
+                .modu(lit_int(4))
+                .cast(DataType::Float64)
+                .mul(lit_float(0.5))],
+            vec![count(col("id"))],
+        )
+        .execute()
+        .unwrap();
+    let mut buckets = buckets[0][0]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<f64>>()
+        .expect("Failed to downcast array to primitive array.")
+        .iter()
+        .zip(
+            buckets[0][1]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter(),
+        )
+        .map(|(bucket, count)| (*bucket.unwrap(), *count.unwrap()))
+        .collect::<Vec<_>>();
+    buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+    assert_eq!(
+        buckets,
+        vec![(0.0, 250), (0.5, 250), (1.0, 250), (1.5, 250)]
+    );
+
+    // Every distinct salary is a group of its own, nulls form one group.
+    let salaries = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("salary")])
+        .execute()
+        .unwrap()
+        .iter()
+        .flat_map(|chunk| {
+            chunk[0]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .map(|x| x.map(|x| x.to_bits()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<HashSet<_>>();
+    let groups = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(
+            vec![col("salary").div(lit_float(10000.0))],
+            vec![count(col("id"))],
+        )
+        .execute()
+        .unwrap();
+    assert_eq!(groups[0].len(), salaries.len());
+
+    // -0.0 equals 0.0 and all NaNs are grouped together.
+    let keys = DataFrame::memory(
+        Schema::from(vec![Field::new("key", DataType::Float64, true)]),
+        vec![Chunk::new(vec![Arc::new(PrimitiveArray::<f64>::from([
+            Some(0.0),
+            Some(-0.0),
+            Some(f64::NAN),
+            Some(1.5),
+            Some(-f64::NAN),
+            None,
+        ])) as Arc<dyn Array>])],
+    )
+    .aggregate(vec![col("key")], vec![count(col("key"))])
+    .execute()
+    .unwrap();
+    let counts = keys[0][1]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i64>>()
+        .expect("Failed to downcast array to primitive array.")
+        .values()
+        .to_vec();
+    assert_eq!(counts, vec![2, 2, 1, 0]);
+}
+
+#[test]
+fn test_aggregate_from_statistics() {
+    // The fixture only has the deprecated min and max statistics, which aren't read, so it is
+    // rewritten with statistics.
+    let fixture = "src/tests/userdata.parquet";
+    let path = std::env::temp_dir().join("query_engine_test_aggregate_from_statistics.parquet");
+    let path = path.to_str().unwrap();
+    DataFrame::parquet(fixture)
+        .project(vec![col("id"), col("salary")])
+        .write_parquet(path)
+        .unwrap();
+    let aggregates = || {
+        vec![
+            max(col("id")),
+            min(col("id")),
+            max(col("salary")).alias("max_salary"),
+            count(col("salary")),
+        ]
+    };
+    let full_scan = DataFrame::parquet(fixture)
+        .cache()
+        .unwrap()
+        .aggregate(vec![], aggregates())
+        .execute()
+        .unwrap();
+
+    let ds = DataSource::Parquet(ParquetDataSource::new(path).unwrap());
+    let counter = ds.try_clone().unwrap();
+    let from_statistics = DataFrame::new(LogicalPlan::Scan(Scan::new(path, ds, None)))
+        .aggregate(vec![], aggregates())
+        .execute()
+        .unwrap();
+    assert_eq!(counter.scans(), 0);
+    assert_eq!(from_statistics.len(), 1);
+    assert_eq!(from_statistics[0].arrays(), full_scan[0].arrays());
+
+    // The null counts of the fixture are enough for a count.
+    let ds = DataSource::Parquet(ParquetDataSource::new(fixture).unwrap());
+    let counter = ds.try_clone().unwrap();
+    let count_from_statistics = DataFrame::new(LogicalPlan::Scan(Scan::new(fixture, ds, None)))
+        .aggregate(vec![], vec![count(col("salary"))])
+        .execute()
+        .unwrap();
+    assert_eq!(counter.scans(), 0);
+    assert_eq!(
+        count_from_statistics[0].arrays()[0],
+        full_scan[0].arrays()[3]
+    );
+
+    // Without min and max statistics or with a filter the aggregate falls back to a scan.
+    let max_id = DataFrame::new(LogicalPlan::Scan(Scan::new(
+        fixture,
+        counter.try_clone().unwrap(),
+        None,
+    )))
+    .aggregate(vec![], vec![max(col("id"))])
+    .execute()
+    .unwrap();
+    assert_eq!(counter.scans(), 1);
+    assert_eq!(max_id[0].arrays()[0], full_scan[0].arrays()[0]);
+    let filtered = DataFrame::new(LogicalPlan::Scan(Scan::new(
+        path,
+        DataSource::Parquet(ParquetDataSource::new(path).unwrap()),
+        None,
+    )))
+    .filter(col("id").lteq(lit_int(500)))
+    .aggregate(vec![], vec![max(col("id"))])
+    .execute()
+    .unwrap();
+    assert_eq!(
+        filtered[0].arrays()[0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .unwrap()
+            .value(0),
+        500
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_repartition() {
+    let input = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![col("country"), col("id")])
+            .logical_plan()
+    };
+    let schema = input().schema().unwrap().clone();
+    let repartition = |partitions: usize| {
+        RepartitionExec::new(
+            vec![input().to_physical_plan().unwrap()],
+            vec![Box::new(ColumnExpression { index: 0 })],
+            partitions,
+            schema.clone(),
+        )
+    };
+
+    let partitions = repartition(4)
+        .unwrap()
+        .execute_partitions()
+        .unwrap()
+        .into_iter()
+        .map(|x| x.collect::<Result<Vec<_>, _>>().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(partitions.len(), 4);
+    let mut partition_of_country = HashMap::new();
+    let mut rows = 0;
+    for (i, partition) in partitions.iter().enumerate() {
+        for chunk in partition {
+            rows += chunk.len();
+            let countries = chunk[0]
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .expect("Failed to downcast array to utf8 array.");
+            for country in countries.values_iter() {
+                assert_eq!(
+                    *partition_of_country.entry(country.to_string()).or_insert(i),
+                    i
+                );
+            }
+        }
+    }
+    assert_eq!(rows, 1000);
+    assert!(partitions.iter().filter(|x| !x.is_empty()).count() > 1);
+
+    let streamed = PhysicalPlan::Repartition(repartition(4).unwrap())
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(streamed.iter().map(|x| x.len()).sum::<usize>(), 1000);
+    assert!(matches!(repartition(0), Err(Error::ZeroPartitions)));
+}
+
+#[test]
+fn test_named_column_expression() {
+    // The projection below the tested one reorders the columns of the scan.
+    let reordered = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![col("salary"), col("id")])
+            .logical_plan()
+            .to_physical_plan()
+            .unwrap()
+    };
+    let schema = Schema::from(vec![Field::new("id", DataType::Int32, true)]);
+    let project = |expr: Box<dyn PhysicalExpression>| {
+        PhysicalPlan::Projection(
+            ProjectionExec::new(vec![reordered()], vec![expr], schema.clone()).unwrap(),
+        )
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+    };
+
+    let by_name = project(Box::new(NamedColumnExpression {
+        name: "id".to_string(),
+    }))
+    .unwrap();
+    let ids = by_name
+        .iter()
+        .flat_map(|chunk| {
+            chunk[0]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .expect("Failed to downcast array to primitive array.")
+                .values()
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ids, (1..=1000).collect::<Vec<_>>());
+
+    let by_index = project(Box::new(ColumnExpression { index: 0 })).unwrap();
+    assert_eq!(by_index[0][0].data_type(), &DataType::Float64);
+
+    assert!(matches!(
+        project(Box::new(NamedColumnExpression {
+            name: "email".to_string(),
+        })),
+        Err(Error::ColumnNotFound(name)) if name == "email"
+    ));
+}
+
+#[test]
+fn test_expression_uses_input_schema() {
+    /// Evaluates to the name of the input column at `index`.
+    struct ColumnName {
+        index: usize,
+    }
+    impl std::fmt::Display for ColumnName {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "name(#{})", self.index)
+        }
+    }
+    impl PhysicalExpression for ColumnName {
+        fn evaluate(
+            &self,
+            _input: &Chunk<Arc<dyn Array>>,
+            schema: &Schema,
+        ) -> Result<ColumnarValue, Error> {
+            let field = schema
+                .fields
+                .get(self.index)
+                .ok_or(Error::ExceedingBoundsError(self.index))?;
+            Ok(ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(
+                Some(field.name.clone()),
+            ))))
+        }
+    }
+
+    let project = |input: DataFrame, expr: Box<dyn PhysicalExpression>, data_type: DataType| {
+        let input = input.logical_plan().to_physical_plan().unwrap();
+        let schema = Schema::from(vec![Field::new("name", data_type, false)]);
+        PhysicalPlan::Projection(ProjectionExec::new(vec![input], vec![expr], schema).unwrap())
+            .execute()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    let names = project(
+        DataFrame::parquet("src/tests/userdata.parquet"),
+        Box::new(ColumnName { index: 1 }),
+        DataType::Utf8,
+    )
+    .unwrap();
+    let names = names[0][0]
+        .as_any()
+        .downcast_ref::<Utf8Array<i32>>()
+        .expect("Failed to downcast array to utf8 array.");
+    assert!(names.values_iter().all(|x| x == "id"));
+
+    // Errors name the column, which is only known from the schema.
+    let small = DataFrame::memory(
+        Schema::from(vec![Field::new("small", DataType::Int16, false)]),
+        vec![Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i16>::from_slice([1, 2])) as Arc<dyn Array>,
+        ])],
+    );
+    match project(
+        small,
+        Box::new(ColumnExpression { index: 0 }),
+        DataType::Int16,
+    ) {
+        Err(Error::PhysicalTypeNotSuported(message)) => {
+            assert_eq!(message, "Int16 of column `small`")
+        }
+        _ => panic!("Expected an unsupported type."),
+    }
+}
+
+#[test]
+fn test_max_min_skip_nan() {
+    let schema = Schema::from(vec![
+        Field::new("group", DataType::Utf8, false),
+        Field::new("value", DataType::Float64, true),
+    ]);
+    let chunk = |groups: Vec<&str>, values: Vec<Option<f64>>| {
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(groups)) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<f64>::from(values)) as Arc<dyn Array>,
+        ])
+    };
+    let table = || {
+        DataFrame::memory(
+            schema.clone(),
+            vec![
+                chunk(
+                    vec!["a", "a", "b", "c", "d"],
+                    vec![
+                        Some(1.0),
+                        Some(f64::NAN),
+                        Some(f64::NAN),
+                        Some(f64::NAN),
+                        None,
+                    ],
+                ),
+                chunk(
+                    vec!["a", "b", "c"],
+                    vec![Some(3.0), Some(f64::NAN), Some(2.0)],
+                ),
+            ],
+        )
+    };
+    let floats = |chunk: &Chunk<Arc<dyn Array>>, i: usize| {
+        chunk[i]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.map(|x| format!("{}", x)))
+            .collect::<Vec<_>>()
+    };
+    let strings = |values: Vec<Option<&str>>| {
+        values
+            .into_iter()
+            .map(|x| x.map(|x| x.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    // NaNs are skipped unless a group only has NaNs.
+    let groups = table()
+        .aggregate(
+            vec![col("group")],
+            vec![max(col("value")), min(col("value"))],
+        )
+        .execute()
+        .unwrap();
+    assert_eq!(
+        floats(&groups[0], 1),
+        strings(vec![Some("3"), Some("NaN"), Some("2"), None])
+    );
+    assert_eq!(
+        floats(&groups[0], 2),
+        strings(vec![Some("1"), Some("NaN"), Some("2"), None])
+    );
+
+    let global = table()
+        .aggregate(vec![], vec![max(col("value")), min(col("value"))])
+        .execute()
+        .unwrap();
+    assert_eq!(floats(&global[0], 0), strings(vec![Some("3")]));
+    assert_eq!(floats(&global[0], 1), strings(vec![Some("1")]));
+}
+
+#[test]
+fn test_max_min_skip_nan_float32() {
+    let schema = Schema::from(vec![
+        Field::new("group", DataType::Utf8, false),
+        Field::new("value", DataType::Float32, true),
+    ]);
+    let chunks = vec![
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(["a", "a", "b", "c"])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<f32>::from(vec![
+                Some(1.0),
+                Some(f32::NAN),
+                Some(f32::NAN),
+                None,
+            ])) as Arc<dyn Array>,
+        ]),
+        Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from_slice(["a", "b"])) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<f32>::from(vec![Some(3.0), Some(f32::NAN)]))
+                as Arc<dyn Array>,
+        ]),
+    ];
+    let groups = DataFrame::memory(schema, chunks)
+        .aggregate(
+            vec![col("group")],
+            vec![max(col("value")), min(col("value"))],
+        )
+        .execute()
+        .unwrap();
+    let floats = |i: usize| {
+        groups[0][i]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.map(|x| format!("{}", x)))
+            .collect::<Vec<_>>()
+    };
+    // NaNs are skipped unless a group only has NaNs, the result keeps the type of the column.
+    assert_eq!(
+        floats(1),
+        vec![Some("3".to_string()), Some("NaN".to_string()), None]
+    );
+    assert_eq!(
+        floats(2),
+        vec![Some("1".to_string()), Some("NaN".to_string()), None]
+    );
+}
+
+#[test]
+fn test_array_builders() {
+    let arrays: Vec<Arc<dyn Array>> = vec![
+        Arc::new(PrimitiveArray::<i32>::from([Some(1), None, Some(3)])),
+        Arc::new(PrimitiveArray::<i64>::from([Some(1), None, Some(3)])),
+        Arc::new(PrimitiveArray::<f32>::from([Some(1.5), None, Some(3.5)])),
+        Arc::new(PrimitiveArray::<f64>::from([Some(1.5), None, Some(3.5)])),
+        Arc::new(Utf8Array::<i32>::from([Some("a"), None, Some("c")])),
+        Arc::new(Utf8Array::<i64>::from([Some("a"), None, Some("c")])),
+        Arc::new(BooleanArray::from([Some(true), None, Some(false)])),
+    ];
+    for array in arrays {
+        let mut builder = new_builder(array.data_type(), 4).unwrap();
+        append_to_builder(builder.as_mut(), array.slice(0, 2).as_ref()).unwrap();
+        append_to_builder(builder.as_mut(), array.slice(2, 1).as_ref()).unwrap();
+        // Null arrays append nulls to builders of any type.
+        append_to_builder(builder.as_mut(), &NullArray::new(DataType::Null, 1)).unwrap();
+        let built = builder.as_arc();
+
+        let expected = compute::concatenate::concatenate(&[
+            array.as_ref(),
+            new_null_array(array.data_type().clone(), 1).as_ref(),
+        ])
+        .unwrap();
+        assert_eq!(built.as_ref(), expected.as_ref());
+    }
+
+    assert!(matches!(
+        new_builder(&DataType::Int16, 1),
+        Err(Error::PhysicalTypeNotSuported(_))
+    ));
+    let mut builder = new_builder(&DataType::Int32, 1).unwrap();
+    assert!(matches!(
+        append_to_builder(builder.as_mut(), &Utf8Array::<i32>::from_slice(["a"])),
+        Err(Error::DowncastError)
+    ));
+}
+
+#[test]
+fn test_project_aggregate_results() {
+    let aggregated = || {
+        DataFrame::parquet("src/tests/userdata.parquet").aggregate(
+            vec![col("country")],
+            vec![sum(col("salary")), count(col("salary"))],
+        )
+    };
+    // The aggregate columns are named after their functions. A Float64 divided by an Int64 is
+    // a Float64, regardless of the order of the operands.
+    let derived = aggregated().project(vec![
+        col("country"),
+        col("sum").div(col("count")).alias("avg"),
+        col("count").mul(lit_float(0.5)).alias("half"),
+    ]);
+    assert_eq!(
+        derived
+            .schema()
+            .unwrap()
+            .fields
+            .iter()
+            .map(|x| (x.name.as_str(), x.data_type.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("country", DataType::Utf8),
+            ("avg", DataType::Float64),
+            ("half", DataType::Float64)
+        ]
+    );
+    let derived = derived.execute().unwrap();
+    let expected = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(vec![col("country")], vec![avg(col("salary"))])
+        .execute()
+        .unwrap();
+    let floats = |chunk: &Chunk<Arc<dyn Array>>, i: usize| {
+        chunk[i]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.copied())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(derived[0][0].as_ref(), expected[0][0].as_ref());
+    let (derived_avg, expected_avg) = (floats(&derived[0], 1), floats(&expected[0], 1));
+    assert_eq!(derived_avg.len(), expected_avg.len());
+    derived_avg
+        .iter()
+        .zip(expected_avg.iter())
+        .for_each(|(l, r)| match (l, r) {
+            (Some(l), Some(r)) => assert!((l - r).abs() < 1e-6 * r.abs().max(1.0)),
+            _ => assert_eq!(l, r),
+        });
+
+    let counts = aggregated().execute().unwrap();
+    let counts = counts[0][2]
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i64>>()
+        .expect("Failed to downcast array to primitive array.");
+    assert_eq!(
+        floats(&derived[0], 2),
+        counts
+            .values()
+            .iter()
+            .map(|x| Some(*x as f64 * 0.5))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_float32_arithmetic() {
+    let df = || {
+        DataFrame::memory(
+            Schema::from(vec![
+                Field::new("value", DataType::Float32, true),
+                Field::new("id", DataType::Int32, false),
+            ]),
+            vec![Chunk::new(vec![
+                Arc::new(PrimitiveArray::<f32>::from([Some(1.5), None, Some(2.25)]))
+                    as Arc<dyn Array>,
+                Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3])) as Arc<dyn Array>,
+            ])],
+        )
+    };
+    // Float32 operands are promoted to Float64 unless both are Float32.
+    let df = df().project(vec![
+        col("value").add(lit_float(1.0)).alias("plus_float"),
+        col("value").mul(col("id")).alias("times_id"),
+        col("value").add(col("value")).alias("twice"),
+    ]);
+    let types = df
+        .schema()
+        .unwrap()
+        .fields
+        .iter()
+        .map(|field| field.data_type.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![DataType::Float64, DataType::Float64, DataType::Float32]
+    );
+    let result = df.execute().unwrap();
+    let doubles = |i: usize| {
+        result[0][i]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<f64>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.copied())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(doubles(0), vec![Some(2.5), None, Some(3.25)]);
+    assert_eq!(doubles(1), vec![Some(1.5), None, Some(6.75)]);
+    assert_eq!(result[0][2].data_type(), &DataType::Float32);
+}
+
+#[test]
+fn test_duplicate_column_name() {
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(vec![], vec![max(col("id")), max(col("salary"))])
+        .execute();
+    assert!(matches!(result, Err(Error::DuplicateColumnName(name)) if name == "max"));
+
+    let df = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![col("id"), col("salary").alias("id")])
+    };
+    assert!(matches!(df().execute(), Err(Error::DuplicateColumnName(name)) if name == "id"));
+    // The schema of the logical plan reports the same error.
+    assert!(matches!(
+        df().logical_plan().schema(),
+        Err(Error::DuplicateColumnName(name)) if name == "id"
+    ));
+    // Nodes above the projection or aggregate report it instead of panicking.
+    assert!(matches!(
+        df().filter(col("id").gt(lit_int(1))).execute(),
+        Err(Error::DuplicateColumnName(name)) if name == "id"
+    ));
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(vec![], vec![max(col("id")), max(col("salary"))])
+        .limit(1)
+        .execute();
+    assert!(matches!(result, Err(Error::DuplicateColumnName(name)) if name == "max"));
+
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(
+            vec![],
+            vec![max(col("id")), max(col("salary")).alias("max_salary")],
+        )
+        .execute()
+        .unwrap();
+    assert_eq!(result[0].arrays().len(), 2);
+}
+
+#[test]
+fn test_sort_merge_join() {
+    let chunk = |keys: Vec<Option<i32>>, values: Vec<i32>| {
+        Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from(keys)) as Arc<dyn Array>,
+            Arc::new(PrimitiveArray::<i32>::from_vec(values)) as Arc<dyn Array>,
+        ])
+    };
+    let schema = |key: &str, value: &str| {
+        Schema::from(vec![
+            Field::new(key, DataType::Int32, true),
+            Field::new(value, DataType::Int32, false),
+        ])
+    };
+    // Groups of equal keys span several batches on both sides.
+    let left_chunks = || {
+        vec![
+            chunk(vec![None, Some(1), Some(1)], vec![0, 1, 2]),
+            chunk(vec![Some(1), Some(2), Some(4)], vec![3, 4, 5]),
+            chunk(vec![Some(4)], vec![6]),
+        ]
+    };
+    let right_chunks = || {
+        vec![
+            chunk(vec![None, Some(1)], vec![10, 11]),
+            chunk(
+                vec![Some(1), Some(3), Some(4), Some(4)],
+                vec![12, 13, 14, 15],
+            ),
+            chunk(vec![Some(5)], vec![16]),
+        ]
+    };
+    let pairs = |df: DataFrame| {
+        let mut pairs = df
+            .execute()
+            .unwrap()
+            .iter()
+            .flat_map(|chunk| {
+                let values = |i: usize| {
+                    chunk[i]
+                        .as_any()
+                        .downcast_ref::<PrimitiveArray<i32>>()
+                        .expect("Failed to downcast array to primitive array.")
+                        .values()
+                        .to_vec()
+                };
+                values(1).into_iter().zip(values(3))
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs
+    };
+
+    let sorted = || {
+        DataFrame::memory_sorted(schema("a", "l"), left_chunks(), vec!["a"])
+            .project(vec![col("a").alias("k"), col("l")])
+            .join(
+                DataFrame::memory_sorted(schema("b", "r"), right_chunks(), vec!["b"]),
+                vec![("k", "b")],
+            )
+    };
+    assert!(matches!(
+        sorted()
+            .logical_plan()
+            .create_physical_plan(&ExecutionConfig::default())
+            .unwrap(),
+        PhysicalPlan::SortMergeJoin(_)
+    ));
+    let expected = vec![
+        (1, 11),
+        (1, 12),
+        (2, 11),
+        (2, 12),
+        (3, 11),
+        (3, 12),
+        (5, 14),
+        (5, 15),
+        (6, 14),
+        (6, 15),
+    ];
+    assert_eq!(pairs(sorted()), expected);
+
+    // Without a known order the same join is hashed.
+    let unsorted = DataFrame::memory(schema("a", "l"), left_chunks()).join(
+        DataFrame::memory(schema("b", "r"), right_chunks()),
+        vec![("a", "b")],
+    );
+    assert_eq!(pairs(unsorted), expected);
+    assert!(matches!(
+        DataFrame::memory(schema("a", "l"), left_chunks())
+            .join(
+                DataFrame::memory_sorted(schema("b", "r"), right_chunks(), vec!["b"]),
+                vec![("a", "b")]
+            )
+            .logical_plan()
+            .create_physical_plan(&ExecutionConfig::default())
+            .unwrap(),
+        PhysicalPlan::HashJoin(_)
+    ));
+
+    // Null keys are merged like any other key if they are equal to each other.
+    let config = ExecutionConfig {
+        null_equals_null: true,
+        ..ExecutionConfig::default()
+    };
+    let rows = sorted()
+        .execute_with_config(&config)
+        .unwrap()
+        .iter()
+        .map(|chunk| chunk.len())
+        .sum::<usize>();
+    assert_eq!(rows, 11);
+}
+
+#[test]
+fn test_prelude_batch() {
+    use crate::prelude::Batch;
+
+    let rows = |batch: Batch| -> Result<usize, Error> { Ok(batch?.len()) };
+    let total = DataFrame::parquet("src/tests/userdata.parquet")
+        .project(vec![col("id")])
+        .execute_stream()
+        .unwrap()
+        .map(rows)
+        .sum::<Result<usize, Error>>()
+        .unwrap();
+    assert_eq!(total, 1000);
+}
+
+#[test]
+fn test_cast_strings() {
+    let df = || {
+        let schema = Schema::from(vec![
+            Field::new("zip", DataType::Utf8, true),
+            Field::new("birthdate", DataType::Utf8, true),
+        ]);
+        let chunks = vec![Chunk::new(vec![
+            Arc::new(Utf8Array::<i32>::from([Some("10115"), Some("80331"), None]))
+                as Arc<dyn Array>,
+            Arc::new(Utf8Array::<i32>::from([
+                Some("1970-01-02"),
+                Some("3/8/1971"),
+                None,
+            ])) as Arc<dyn Array>,
+        ])];
+        DataFrame::memory(schema, chunks)
+    };
+    let lenient = ExecutionConfig {
+        coercion_mode: CoercionMode::Lenient,
+        ..Default::default()
+    };
+
+    for config in [ExecutionConfig::default(), lenient.clone()] {
+        let result = df()
+            .project(vec![
+                col("zip").cast(DataType::Int32).alias("int"),
+                col("zip").cast(DataType::Float64).alias("float"),
+            ])
+            .execute_with_config(&config)
+            .unwrap();
+        assert_eq!(
+            result[0][0]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .map(|x| x.copied())
+                .collect::<Vec<_>>(),
+            vec![Some(10115), Some(80331), None]
+        );
+        assert_eq!(
+            result[0][1]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .map(|x| x.copied())
+                .collect::<Vec<_>>(),
+            vec![Some(10115.0), Some(80331.0), None]
+        );
+    }
+
+    let dates = || df().project(vec![col("birthdate").cast(DataType::Date32)]);
+    let result = dates().execute_with_config(&ExecutionConfig::default());
+    assert!(matches!(result, Err(Error::CastError(value, _)) if value == "3/8/1971"));
+
+    let result = dates().execute_with_config(&lenient).unwrap();
+    assert_eq!(result[0][0].data_type(), &DataType::Date32);
+    assert_eq!(
+        result[0][0]
+            .as_any()
+            .downcast_ref::<PrimitiveArray<i32>>()
+            .expect("Failed to downcast array to primitive array.")
+            .iter()
+            .map(|x| x.copied())
+            .collect::<Vec<_>>(),
+        vec![Some(1), None, None]
+    );
+}
+
+#[test]
+fn test_plain_column_in_aggregate() {
+    let result = DataFrame::parquet("src/tests/userdata.parquet")
+        .aggregate(vec![col("country")], vec![col("country")])
+        .execute();
+    match result {
+        Err(error @ Error::NotAnAggregateExpression(_)) => assert_eq!(
+            error.to_string(),
+            "Column `country` is not an aggregate expression. Plain columns belong in the group \
+             by list."
+        ),
+        _ => panic!("Expected a NotAnAggregateExpression error."),
+    }
+}
+
+#[test]
+fn test_broadcast_scalars() {
+    let array = ColumnarValue::Scalar(Box::new(BooleanScalar::from(Some(true)))).to_array(3);
+    assert_eq!(
+        array
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .expect("Failed to downcast array to boolean array.")
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![Some(true); 3]
+    );
+
+    let array = ColumnarValue::Scalar(Box::new(BooleanScalar::from(None))).to_array(2);
+    assert_eq!(array.data_type(), &DataType::Boolean);
+    assert_eq!(array.null_count(), 2);
+
+    let array = ColumnarValue::Scalar(Box::new(PrimitiveScalar::<i64>::from(Some(7)))).to_array(2);
+    assert_eq!(array.data_type(), &DataType::Int64);
+    assert_eq!(array.len(), 2);
+
+    let array =
+        ColumnarValue::Scalar(Box::new(PrimitiveScalar::<f32>::from(Some(0.5)))).to_array(2);
+    assert_eq!(array.data_type(), &DataType::Float32);
+    assert_eq!(array.len(), 2);
+}
+
+#[test]
+fn test_scan_without_projection() {
+    let ds = DataSource::Parquet(ParquetDataSource::new("src/tests/userdata.parquet").unwrap());
+    let fields = ds.schema().fields.len();
+    let chunks = ds
+        .scan(None, vec![], None)
+        .collect::<Result<Vec<_>, Error>>()
+        .unwrap();
+    assert!(chunks.iter().all(|chunk| chunk.arrays().len() == fields));
+    assert_eq!(chunks.iter().map(|chunk| chunk.len()).sum::<usize>(), 1000);
+}
+
+#[test]
+fn test_scan_exec_schema() {
+    let path = "src/tests/userdata.parquet";
+    for projection in [None, Some(vec!["id".to_string(), "salary".to_string()])] {
+        let ds = DataSource::Parquet(ParquetDataSource::new(path).unwrap());
+        let scan = Scan::new(path, ds, projection);
+        let schema = scan.schema.clone();
+        let plan = LogicalPlan::Scan(scan)
+            .create_physical_plan(&ExecutionConfig::default())
+            .unwrap();
+        assert!(matches!(plan, PhysicalPlan::Scan(_)));
+        assert_eq!(plan.schema(), &schema);
+    }
+}
+
+#[test]
+fn test_parquet_schema_cached() {
+    use arrow2::io::parquet::read::infer_schema;
+
+    let path = "src/tests/userdata.parquet";
+    let ds = ParquetDataSource::new(path).unwrap();
+    let metadata = read_metadata(&mut std::fs::File::open(path).unwrap()).unwrap();
+    // The stored schema is the one inferred from the metadata.
+    assert_eq!(ds.schema(), &infer_schema(&metadata).unwrap());
+    // Every call returns the stored schema instead of inferring it again.
+    assert!(std::ptr::eq(ds.schema(), ds.schema()));
+    assert_eq!(
+        DataSource::Parquet(ds).schema(),
+        infer_schema(&metadata).unwrap()
+    );
+}
+
+#[test]
+fn test_filter_or_projection_push_down() {
+    let df = || {
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .filter(
+                col("country")
+                    .eq(lit_string("Canada"))
+                    .or(col("country").eq(lit_string("Brazil"))),
+            )
+            .project(vec![col("country")])
+    };
+    let plan = df().logical_plan().optimize();
+    match &plan {
+        LogicalPlan::Projection(proj) => match &proj.children[0] {
+            LogicalPlan::Selection(sel) => match &sel.children[0] {
+                LogicalPlan::Scan(scan) => {
+                    assert_eq!(scan.projection, Some(vec!["country".to_string()]))
+                }
+                _ => panic!("Expected scan below selection."),
+            },
+            _ => panic!("Expected selection below projection."),
+        },
+        _ => panic!("Expected projection at the top of the plan."),
+    }
+
+    let countries = |chunks: Vec<Chunk<Arc<dyn Array>>>| {
+        chunks
+            .iter()
+            .flat_map(|chunk| {
+                chunk[0]
+                    .as_any()
+                    .downcast_ref::<Utf8Array<i32>>()
+                    .expect("Failed to downcast array to utf8 array.")
+                    .iter()
+                    .map(|x| x.map(|x| x.to_string()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    let expected = countries(
+        DataFrame::parquet("src/tests/userdata.parquet")
+            .project(vec![col("country")])
+            .execute()
+            .unwrap(),
+    )
+    .into_iter()
+    .filter(|x| matches!(x.as_deref(), Some("Canada") | Some("Brazil")))
+    .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+    assert_eq!(countries(df().execute().unwrap()), expected);
+}
+
+#[test]
+fn test_unnest() {
+    let df = || {
+        let mut tags = MutableListArray::<i32, MutablePrimitiveArray<i32>>::new();
+        tags.try_extend(vec![
+            Some(vec![Some(1), Some(2)]),
+            Some(vec![]),
+            None,
+            Some(vec![Some(3)]),
+        ])
+        .unwrap();
+        let tags: ListArray<i32> = tags.into();
+        let schema = Schema::from(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("tags", tags.data_type().clone(), true),
+        ]);
+        let chunk = Chunk::new(vec![
+            Arc::new(PrimitiveArray::<i32>::from_slice([1, 2, 3, 4])) as Arc<dyn Array>,
+            Arc::new(tags),
+        ]);
+        DataFrame::memory(schema, vec![chunk])
+    };
+    let columns = |df: DataFrame| {
+        let result = df.execute().unwrap();
+        let column = |i: usize| {
+            result[0][i]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .expect("Failed to downcast array to primitive array.")
+                .iter()
+                .map(|x| x.copied())
+                .collect::<Vec<_>>()
+        };
+        (column(0), column(1))
+    };
+
+    let unnested = df().unnest("tags");
+    assert_eq!(
+        unnested.schema().unwrap().fields[1].data_type,
+        DataType::Int32
+    );
+    assert_eq!(
+        columns(unnested),
+        (
+            vec![Some(1), Some(1), Some(4)],
+            vec![Some(1), Some(2), Some(3)]
+        )
+    );
+
+    assert_eq!(
+        columns(df().unnest_preserve_empty("tags")),
+        (
+            vec![Some(1), Some(1), Some(2), Some(3), Some(4)],
+            vec![Some(1), Some(2), None, None, Some(3)]
+        )
+    );
+
+    // Projection push down keeps the unnested column below the projection.
+    let plan = df()
+        .unnest("tags")
+        .project(vec![col("tags")])
+        .logical_plan()
+        .optimize();
+    let result = plan
+        .to_physical_plan()
+        .unwrap()
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(result[0].len(), 3);
+
+    let result = df().unnest("id").execute();
+    assert!(matches!(result, Err(Error::NotAList(name, _)) if name == "id"));
+}